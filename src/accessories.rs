@@ -0,0 +1,100 @@
+//! Accessories the player wears into battle, which change the turns and time they get, the
+//! hints they're given, and what the battle pays out.
+//!
+//! ```json
+//! {"accessories": [{"type": "toadAlert"}, {"type": "timePlus", "seconds": 30}]}
+//! ```
+//!
+//! The effects of every accessory worn add up, and are applied on top of the rest of the
+//! options: extra turns still stop at the most turns the solver can search.
+
+use crate::countdown::Timer;
+use crate::preset::Rules;
+use crate::rewards::RewardEstimate;
+use crate::{Ring, MAX_TURNS};
+use serde::Deserialize;
+
+/// An accessory and its effect.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Accessory {
+    /// Alerts the player to crumpled Toads on the board, so solutions say how many there are.
+    ToadAlert,
+    /// Adds `seconds` to the clock.
+    TimePlus { seconds: f64 },
+    /// Adds `turns` to the turns the player gets.
+    TurnPlus { turns: u16 },
+    /// Multiplies the coins the battle pays out.
+    CoinBoost { multiplier: f64 },
+}
+
+/// The combined effect of the accessories worn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Effects {
+    pub toad_alert: bool,
+    pub seconds: f64,
+    pub turns: u16,
+    pub coin_multiplier: f64,
+}
+
+impl Default for Effects {
+    fn default() -> Self {
+        Effects { toad_alert: false, seconds: 0.0, turns: 0, coin_multiplier: 1.0 }
+    }
+}
+
+impl Effects {
+    /// The combined effect of wearing every accessory in `accessories`.
+    pub fn of(accessories: &[Accessory]) -> Self {
+        let mut effects = Effects::default();
+        for accessory in accessories {
+            match *accessory {
+                Accessory::ToadAlert => effects.toad_alert = true,
+                Accessory::TimePlus { seconds } => effects.seconds += seconds,
+                Accessory::TurnPlus { turns } => effects.turns = effects.turns.saturating_add(turns),
+                Accessory::CoinBoost { multiplier } => effects.coin_multiplier *= multiplier,
+            }
+        }
+        effects
+    }
+
+    /// Checks that no accessory takes time or coins away, and that they don't add more
+    /// turns than the solver could ever search.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        if self.turns > MAX_TURNS {
+            return Err(format!("accessories can't add {} turns, at most {}", self.turns, MAX_TURNS));
+        }
+        if self.seconds.is_nan() || self.seconds < 0.0 {
+            return Err(format!("accessories can't take away {} seconds", -self.seconds));
+        }
+        if self.coin_multiplier.is_nan() || self.coin_multiplier < 0.0 {
+            return Err(format!("accessories can't multiply coins by {}", self.coin_multiplier));
+        }
+        Ok(())
+    }
+
+    /// `rules` with the extra turns.
+    pub fn rules(&self, rules: Rules) -> Rules {
+        let max_turns = rules.max_turns.saturating_add(self.turns).min(MAX_TURNS);
+        Rules { max_turns, ..rules }
+    }
+
+    /// `timer` with the extra seconds.
+    pub fn timer(&self, timer: Timer) -> Timer {
+        Timer { seconds: timer.seconds + self.seconds, ..timer }
+    }
+
+    /// `estimate` with the coins multiplied.
+    pub fn reward(&self, estimate: RewardEstimate) -> RewardEstimate {
+        let coins = (estimate.coins as f64 * self.coin_multiplier) as u32;
+        RewardEstimate { coins, ..estimate }
+    }
+
+    /// How many crumpled Toads are on `toads`, if an accessory alerts the player to them.
+    pub fn toad_alert(&self, toads: Option<Ring>) -> Option<u32> {
+        if !self.toad_alert {
+            return None;
+        }
+        Some(toads.unwrap_or_default().iter().map(|angles| angles.count_ones()).sum())
+    }
+}
@@ -0,0 +1,44 @@
+//! Solving without freezing the page: `solve_async` returns a Promise, and the search lets
+//! the event loop run every so often, so input and animations are handled while a hard
+//! board is solved on the UI thread.
+
+use crate::{finish_solution, input_from_js, solve_to_js, Result, Search, MAX_TURNS};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+/// How many boards `solve_async` visits between yields by default, which takes a few
+/// milliseconds.
+pub const YIELD_NODES: u32 = 100_000;
+
+/// Resolves once the event loop has had a chance to run, with a zero-delay timer so
+/// rendering and input aren't starved the way they are by microtasks.
+pub(crate) async fn yield_to_event_loop() {
+    let promise = js_sys::Promise::new(&mut |resolve, _reject| {
+        let scheduled = js_sys::Reflect::get(&js_sys::global(), &"setTimeout".into())
+            .and_then(|set_timeout| set_timeout.dyn_into::<js_sys::Function>())
+            .and_then(|set_timeout| set_timeout.call1(&JsValue::NULL, &resolve));
+        // Without a timer, carry on once the current microtasks are done instead.
+        if scheduled.is_err() {
+            let _ = resolve.call0(&JsValue::NULL);
+        }
+    });
+    // The promise is only ever resolved.
+    let _ = wasm_bindgen_futures::JsFuture::from(promise).await;
+}
+
+/// Like `solve`, but returns a Promise, and lets the event loop run after about every
+/// `yield_nodes` boards, or `YIELD_NODES` if not given. The search can only pause between
+/// first movements, so the pauses may be further apart on some boards.
+///
+/// The board, options, and solution are read and reported just as `solve` does.
+#[wasm_bindgen(skip_typescript)]
+pub async fn solve_async(ring: JsValue, options: JsValue, yield_nodes: Option<u32>) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let yield_nodes = yield_nodes.unwrap_or(YIELD_NODES).max(1);
+    let mut search = Search::new(&options).with_yielding(yield_nodes as u64);
+    let solution = match search.find_solutions_async(ring, MAX_TURNS, 1).await.pop() {
+        Some(solution) => Some(finish_solution(&mut search, solution, ring, MAX_TURNS, &options).await),
+        None => None,
+    };
+    solve_to_js(solution, &options)
+}
@@ -0,0 +1,363 @@
+//! Enemies that can't be hit by every weapon: spiked enemies can't be jumped on, flying
+//! enemies can't be hammered, and shielded enemies take two hits.
+//!
+//! ```json
+//! {"spiked": [0, 0, 0, 0], "flying": [0, 0, 8, 0], "shielded": [1, 0, 0, 0]}
+//! ```
+//!
+//! Each attribute is a board of the cells whose enemies have it, which move along with
+//! the enemies. A jump hits every enemy in the line outward from the innermost one left in
+//! its angle, and can't be made while that line has a spiked enemy. A hammer hits every
+//! enemy but flying ones in the inner two rings of its angle and the next.
+//!
+//! The attacks in a solution account for the attributes, but the counts of jumps and
+//! hammers still describe the board as if it had none.
+
+use crate::evaluator::Evaluator;
+use crate::preset::Rules;
+use crate::{
+    apply_movement, binding, check_ring, input_from_js, solve_to_js, without_yielding,
+    Attack, Result, Ring, RingMovement, Search, Solution, SolveOptions, MAX_TURNS, NUM_ANGLES,
+    NUM_RINGS,
+};
+use serde::Deserialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// The cells whose enemies have each attribute.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EnemyAttributes {
+    /// Enemies that can't be jumped on.
+    pub spiked: Ring,
+    /// Enemies that can't be hammered.
+    pub flying: Ring,
+    /// Enemies that take two hits.
+    pub shielded: Ring,
+}
+
+impl EnemyAttributes {
+    /// Checks that every attribute is on a cell that exists and has an enemy.
+    pub fn check(&self, ring: &Ring) -> std::result::Result<(), String> {
+        for (name, cells) in
+            [("spiked", &self.spiked), ("flying", &self.flying), ("shielded", &self.shielded)]
+        {
+            check_ring(cells)?;
+            if let Some(r) = (0..NUM_RINGS as usize).find(|&r| cells[r] & !ring[r] != 0) {
+                return Err(format!("ring {} has {} cells without an enemy", r, name));
+            }
+        }
+        Ok(())
+    }
+
+    /// The attributes once the enemies have made `moves`.
+    fn after(&self, moves: &[RingMovement]) -> Self {
+        let moved = |cells: Ring| moves.iter().fold(cells, apply_movement);
+        EnemyAttributes {
+            spiked: moved(self.spiked),
+            flying: moved(self.flying),
+            shielded: moved(self.shielded),
+        }
+    }
+
+    /// Everything about the cells at angle `th`: four bits for each ring, from the inside
+    /// out, which are whether there's an enemy and whether it's spiked, flying, or shielded.
+    fn angle_key(&self, ring: Ring, th: u16) -> u16 {
+        (0..NUM_RINGS as usize).fold(0, |key, r| {
+            let bit = |cells: &Ring| cells[r] >> th & 1;
+            let cell = bit(&ring)
+                | bit(&self.spiked) << 1
+                | bit(&self.flying) << 2
+                | bit(&self.shielded) << 3;
+            key | cell << (4 * r)
+        })
+    }
+}
+
+/// How far the weapons reach.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Reach {
+    /// The most enemies in a line a jump hits.
+    pub jump_depth: usize,
+    /// How many of the inner rings a hammer hits.
+    pub hammer_rings: usize,
+}
+
+impl Default for Reach {
+    fn default() -> Self {
+        Reach { jump_depth: NUM_RINGS as usize, hammer_rings: 2 }
+    }
+}
+
+/// The jumps made at an angle before every hammer is swung, and after.
+type AngleJumps = (u8, u8);
+
+/// Which attacks clear a board: the hammers aimed at each angle, and the jumps at each
+/// angle, or None for an angle that can't be cleared.
+struct AnglePlan {
+    hammers: [usize; NUM_ANGLES as usize],
+    jumps: [Option<AngleJumps>; NUM_ANGLES as usize],
+}
+
+impl AnglePlan {
+    fn attacks(&self) -> u32 {
+        let jumps = self.jumps.iter().flatten().map(|&(before, after)| (before + after) as u32);
+        self.hammers.iter().sum::<usize>() as u32 + jumps.sum::<u32>()
+    }
+
+    fn uncleared(&self) -> u32 {
+        self.jumps.iter().filter(|jumps| jumps.is_none()).count() as u32
+    }
+}
+
+/// Every enemy can be hit with the actions and weapons of `rules`, despite its attributes.
+/// The distance is how many more actions it would take, plus one for every angle with
+/// enemies that can't be cleared at all.
+///
+/// So that the attacks can always be made in order, some jumps are made first, then every
+/// hammer, then the rest of the jumps.
+pub struct AttributeGoal {
+    attributes: EnemyAttributes,
+    rules: Rules,
+    reach: Reach,
+    /// The fewest jumps that clear an angle, by its `angle_key` and then by how many times
+    /// it's hammered, or None if it can't be cleared.
+    angle_jumps: RefCell<HashMap<u16, [Option<AngleJumps>; 3]>>,
+}
+
+impl AttributeGoal {
+    pub fn new(attributes: EnemyAttributes, rules: Rules) -> Self {
+        AttributeGoal {
+            attributes,
+            rules,
+            reach: Reach::default(),
+            angle_jumps: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Uses weapons that reach as far as `reach` instead of the usual.
+    pub fn with_reach(mut self, reach: Reach) -> Self {
+        self.reach = reach;
+        self
+    }
+
+    /// The fewest jumps that clear the angle described by `key`, for each number of hammer
+    /// hits it takes.
+    fn jumps(&self, key: u16) -> [Option<AngleJumps>; 3] {
+        if let Some(&jumps) = self.angle_jumps.borrow().get(&key) {
+            return jumps;
+        }
+        let jumps = [0, 1, 2].map(|hammers| clear_angle(key, hammers, self.rules.jump, &self.reach));
+        self.angle_jumps.borrow_mut().insert(key, jumps);
+        jumps
+    }
+
+    /// The attacks that clear as much of `ring` as possible, with as few attacks as
+    /// possible.
+    fn plan(&self, ring: Ring, attributes: &EnemyAttributes) -> AnglePlan {
+        let jumps: [[Option<AngleJumps>; 3]; NUM_ANGLES as usize] =
+            std::array::from_fn(|th| self.jumps(attributes.angle_key(ring, th as u16)));
+        let cost = |jumps: Option<AngleJumps>| match jumps {
+            Some((before, after)) => (0, (before + after) as u32),
+            None => (1, 0),
+        };
+        // Each angle is hit by the hammers aimed at it and at the angle before it, so the
+        // hammers aimed at each angle are chosen around the circle, starting from those
+        // aimed at the last angle, which also hit the first. Aiming at an angle more than
+        // twice never helps, since no enemy takes more than two hits.
+        let max_hammers = if self.rules.hammer { 2 } else { 0 };
+        let mut best: Option<((u32, u32), AnglePlan)> = None;
+        for last in 0..=max_hammers {
+            // The best (uncleared angles, attacks) up to each angle, by the hammers aimed
+            // at it, and the hammers aimed at the angle before for that.
+            let mut costs = [[None::<((u32, u32), usize)>; 3]; NUM_ANGLES as usize];
+            for th in 0..NUM_ANGLES as usize {
+                for before in 0..=max_hammers {
+                    let so_far = match th {
+                        0 if before == last => (0, 0),
+                        0 => continue,
+                        _ => match costs[th - 1][before] {
+                            Some((so_far, _)) => so_far,
+                            None => continue,
+                        },
+                    };
+                    // The hammers aimed at the last angle were chosen first.
+                    let aimed =
+                        if th == NUM_ANGLES as usize - 1 { last..=last } else { 0..=max_hammers };
+                    for here in aimed {
+                        let (uncleared, attacks) = cost(jumps[th][(before + here).min(2)]);
+                        let total = (so_far.0 + uncleared, so_far.1 + attacks + here as u32);
+                        if costs[th][here].is_none_or(|(best, _)| total < best) {
+                            costs[th][here] = Some((total, before));
+                        }
+                    }
+                }
+            }
+            let total = match costs[NUM_ANGLES as usize - 1][last] {
+                Some((total, _)) => total,
+                None => continue,
+            };
+            if best.as_ref().is_some_and(|(best, _)| *best <= total) {
+                continue;
+            }
+            // Follow the choices back around the circle.
+            let mut hammers = [0; NUM_ANGLES as usize];
+            let mut here = last;
+            for th in (0..NUM_ANGLES as usize).rev() {
+                hammers[th] = here;
+                here = costs[th][here].map_or(0, |(_, before)| before);
+            }
+            let jumps = std::array::from_fn(|th| {
+                let before = hammers[(th + NUM_ANGLES as usize - 1) % NUM_ANGLES as usize];
+                jumps[th][(before + hammers[th]).min(2)]
+            });
+            best = Some((total, AnglePlan { hammers, jumps }));
+        }
+        best.expect("some number of hammers is always possible").1
+    }
+
+    /// The attacks that clear the board reached by `moves`, in the order to make them, or
+    /// None if it can't be cleared.
+    pub fn attacks_after(&self, ring: Ring, moves: &[RingMovement]) -> Option<Vec<Attack>> {
+        let plan = self.plan(ring, &self.attributes.after(moves));
+        if plan.uncleared() > 0 {
+            return None;
+        }
+        let mut attacks = Vec::new();
+        let jumps = |th: usize, count: u8| (0..count).map(move |_| Attack::Jump { th: th as u16 });
+        for (th, &jumps_at) in plan.jumps.iter().enumerate() {
+            attacks.extend(jumps(th, jumps_at.map_or(0, |(before, _)| before)));
+        }
+        for (th, &hammers) in plan.hammers.iter().enumerate() {
+            attacks.extend((0..hammers).map(|_| Attack::Hammer { th: th as u16 }));
+        }
+        for (th, &jumps_at) in plan.jumps.iter().enumerate() {
+            attacks.extend(jumps(th, jumps_at.map_or(0, |(_, after)| after)));
+        }
+        Some(attacks)
+    }
+}
+
+impl Evaluator for AttributeGoal {
+    fn distance(&self, ring: Ring) -> u32 {
+        self.distance_after(ring, &[])
+    }
+
+    fn distance_after(&self, ring: Ring, moves: &[RingMovement]) -> u32 {
+        let plan = self.plan(ring, &self.attributes.after(moves));
+        let actions = self.rules.actions_for(ring);
+        plan.attacks().saturating_sub(actions) + plan.uncleared()
+    }
+}
+
+/// The fewest jumps that clear an angle described by an `angle_key`, when it's also
+/// hammered `hammers` times, or None if it can't be cleared.
+///
+/// Each jump hits whatever line is left, so this only has to choose how many jumps to
+/// make before the hammers.
+fn clear_angle(key: u16, hammers: usize, jump: bool, reach: &Reach) -> Option<AngleJumps> {
+    let cell = |r: usize| key >> (4 * r) & 0xf;
+    let spiked = |r: usize| cell(r) & 0b10 != 0;
+    let flying = |r: usize| cell(r) & 0b100 != 0;
+    // How many hits each enemy still takes.
+    let mut hits = [0u8; NUM_RINGS as usize];
+    for (r, hits) in hits.iter_mut().enumerate() {
+        *hits = (cell(r) & 1 != 0) as u8 * (1 + (cell(r) & 0b1000 != 0) as u8);
+    }
+    // Jumps on the line left, returning false if it can't be jumped on.
+    let jump_once = |hits: &mut [u8; NUM_RINGS as usize]| {
+        let first = match hits.iter().position(|&hits| hits > 0) {
+            Some(first) if jump => first,
+            _ => return false,
+        };
+        let line = hits[first..].iter().take(reach.jump_depth).take_while(|&&hits| hits > 0);
+        let line = first..first + line.count();
+        if line.clone().any(spiked) {
+            return false;
+        }
+        for hits in &mut hits[line] {
+            *hits -= 1;
+        }
+        true
+    };
+    let cleared = |hits: &[u8; NUM_RINGS as usize]| hits.iter().all(|&hits| hits == 0);
+    let mut best: Option<AngleJumps> = None;
+    let mut before = 0;
+    loop {
+        let mut after = hits;
+        for _ in 0..hammers {
+            for (r, hits) in after.iter_mut().enumerate().take(reach.hammer_rings) {
+                if !flying(r) {
+                    *hits = hits.saturating_sub(1);
+                }
+            }
+        }
+        let mut jumps_after = 0;
+        while !cleared(&after) && jump_once(&mut after) {
+            jumps_after += 1;
+        }
+        if cleared(&after) && best.is_none_or(|(a, b)| before + jumps_after < a + b) {
+            best = Some((before, jumps_after));
+        }
+        // Every jump hits something, so this runs out of jumps to make.
+        if cleared(&hits) || !jump_once(&mut hits) {
+            return best;
+        }
+        before += 1;
+    }
+}
+
+/// Finds the best way to clear `ring` given the attributes of its enemies, with the same
+/// ranking as `solve`.
+pub fn find_solution(
+    ring: Ring,
+    attributes: EnemyAttributes,
+    options: &SolveOptions,
+) -> std::result::Result<Option<Solution>, String> {
+    attributes.check(&ring)?;
+    let rules = options.battle_rules()?;
+    let goal = Rc::new(AttributeGoal::new(attributes, rules));
+    Ok(find_solution_with(ring, goal, options))
+}
+
+/// Finds the best way to reach `goal` from `ring`, with the attacks it plans.
+pub(crate) fn find_solution_with(
+    ring: Ring,
+    goal: Rc<AttributeGoal>,
+    options: &SolveOptions,
+) -> Option<Solution> {
+    let mut search = Search::new(options).with_evaluator(goal.clone());
+    let mut solution = search.find_solution(ring, MAX_TURNS)?;
+    let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+    if let Some(attacks) = goal.attacks_after(solution.result, &moves) {
+        solution.attacks = attacks;
+    }
+    let hazards = binding::find_attribute_hazards(
+        &mut search,
+        ring,
+        &goal.attributes,
+        &goal.rules,
+        options,
+        &solution,
+    );
+    solution.hazards = without_yielding(hazards);
+    Some(solution)
+}
+
+/// Solve a board whose enemies have attributes, given as `{spiked, flying, shielded}`
+/// boards of the cells with each.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_with_attributes(ring: JsValue, attributes: JsValue, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let attributes: EnemyAttributes = serde_wasm_bindgen::from_value(attributes)?;
+    attributes.check(&options.orientation.board_out(ring))?;
+    let orientation = options.orientation;
+    let attributes = EnemyAttributes {
+        spiked: orientation.board_in(attributes.spiked),
+        flying: orientation.board_in(attributes.flying),
+        shielded: orientation.board_in(attributes.shielded),
+    };
+    solve_to_js(find_solution(ring, attributes, &options)?, &options)
+}
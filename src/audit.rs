@@ -0,0 +1,162 @@
+//! Static checks of a board and its options before solving, so front ends can point out
+//! mistakes in what was entered instead of just reporting that there's no solution.
+
+use crate::attributes::EnemyAttributes;
+use crate::evaluator::Evaluator;
+use crate::heuristic::occupancy_bound;
+use crate::{
+    check_movement, check_ring, hammer_block, Result, Ring, SolveOptions, NUM_ANGLES, NUM_RINGS,
+};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// How much a finding matters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Severity {
+    /// Solving can't work as configured.
+    Error,
+    /// Solving works, but probably not the way the user meant.
+    Warning,
+}
+
+/// Something wrong with a board or its options.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FindingKind {
+    /// The board has enemies at angles that don't exist.
+    InvalidBoard { reason: String },
+    /// The board has no enemies.
+    EmptyBoard,
+    /// The board can already be cleared without moving anything.
+    AlreadySolved,
+    /// The forced first movement refers to a ring or row that doesn't exist.
+    InvalidFirstMove { reason: String },
+    /// The preset and rules don't make a battle that can be played.
+    InvalidRules { reason: String },
+    /// The constraints don't allow any movement, and the board isn't already solved.
+    NoMovementsAllowed,
+    /// Enemies are spread so widely that no solution within the turn limit can exist.
+    TooSpread { min_turns: u16 },
+    /// The attributes or Toads don't match the board, e.g. a flying enemy on an empty
+    /// panel, or a Toad on the same panel as an enemy.
+    InconsistentLayers { reason: String },
+    /// The enemy at angle `th` of ring `r` can't be hit by any attack the player has,
+    /// e.g. because it's both spiked and flying. Only a partner could still hit it.
+    UnreachableEnemy { r: u16, th: u16 },
+    /// The enemy at angle `th` of ring `r` can only be hammered, and every hammer that
+    /// would hit it lands on a Toad as the board stands, so the rings have to move them
+    /// apart first.
+    ToadsInEveryHammerBlock { r: u16, th: u16 },
+}
+
+/// A single problem found by `audit`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Finding {
+    pub severity: Severity,
+    pub kind: FindingKind,
+}
+
+/// Checks a board, the attributes of its enemies, and its options for contradictions,
+/// without searching.
+pub fn audit(ring: Ring, attributes: &EnemyAttributes, options: &SolveOptions) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut add = |severity, kind| findings.push(Finding { severity, kind });
+    if let Some(first_move) = &options.first_move {
+        if let Err(reason) = check_movement(first_move) {
+            add(Severity::Error, FindingKind::InvalidFirstMove { reason });
+        }
+    }
+    let rules = match options.battle_rules() {
+        Ok(rules) => rules,
+        Err(reason) => {
+            add(Severity::Error, FindingKind::InvalidRules { reason });
+            Default::default()
+        }
+    };
+    if let Err(reason) = check_ring(&ring) {
+        // The rest of the checks would only be confused by a board that doesn't exist.
+        add(Severity::Error, FindingKind::InvalidBoard { reason });
+        return findings;
+    }
+    let toads = options.toads.unwrap_or_default();
+    if let Err(reason) = attributes.check(&ring).and_then(|()| check_toads(&ring, &toads)) {
+        // Which enemies have which attributes can't be trusted either.
+        add(Severity::Error, FindingKind::InconsistentLayers { reason });
+        return findings;
+    }
+    if ring.iter().all(|&subring| subring == 0) {
+        add(Severity::Warning, FindingKind::EmptyBoard);
+        return findings;
+    }
+    // A partner attacks on its own, so it may still hit the enemies the player can't.
+    let unreachable = if options.partner.is_some() { Severity::Warning } else { Severity::Error };
+    let mut all_hittable = true;
+    for r in 0..NUM_RINGS {
+        for th in (0..NUM_ANGLES).filter(|&th| ring[r as usize] & (1 << th) != 0) {
+            let has = |cells: &Ring| cells[r as usize] & (1 << th) != 0;
+            let can_jump = rules.jump && !has(&attributes.spiked);
+            let can_hammer = rules.hammer && !has(&attributes.flying);
+            if !can_jump && !can_hammer {
+                all_hittable = false;
+                add(unreachable, FindingKind::UnreachableEnemy { r, th });
+            } else if !can_jump && r < 2 && toads_in_every_hammer_block(&toads, th) {
+                all_hittable = false;
+                add(Severity::Warning, FindingKind::ToadsInEveryHammerBlock { r, th });
+            }
+        }
+    }
+    if rules.distance(ring) == 0 && all_hittable {
+        add(Severity::Warning, FindingKind::AlreadySolved);
+        return findings;
+    }
+    let constraints = &options.constraints;
+    let can_rotate = (0..NUM_RINGS).any(|r| constraints.allows_ring(r)) && constraints.max_rotation > 0;
+    let can_shift =
+        (0..NUM_ANGLES / 2).any(|th| constraints.allows_row(th)) && constraints.max_shift > 0;
+    if !can_rotate && !can_shift && options.first_move.is_none() {
+        add(Severity::Error, FindingKind::NoMovementsAllowed);
+    }
+    let min_turns = occupancy_bound(ring);
+    if min_turns > rules.max_turns {
+        add(Severity::Error, FindingKind::TooSpread { min_turns });
+    }
+    findings
+}
+
+/// Checks that no Toad is on a panel that has an enemy.
+fn check_toads(ring: &Ring, toads: &Ring) -> std::result::Result<(), String> {
+    match (0..NUM_RINGS as usize).find(|&r| ring[r] & toads[r] != 0) {
+        Some(r) => Err(format!("ring {} has Toads on panels with an enemy", r)),
+        None => Ok(()),
+    }
+}
+
+/// Whether both hammers that hit the inner two rings at angle `th`, the one aimed at it
+/// and the one aimed at the angle before, land on a Toad.
+fn toads_in_every_hammer_block(toads: &Ring, th: u16) -> bool {
+    [th, (th + NUM_ANGLES - 1) % NUM_ANGLES].iter().all(|&aim| {
+        let block = hammer_block(aim);
+        block.iter().zip(toads).any(|(block, toads)| block & toads != 0)
+    })
+}
+
+/// Check a board and options for contradictions before solving, returning a list of
+/// `Finding`s. `attributes` are the `EnemyAttributes` of its enemies, if any.
+/// Unlike `solve`, invalid input is reported here rather than thrown.
+#[wasm_bindgen(js_name = audit, skip_typescript)]
+pub fn audit_js(ring: JsValue, options: JsValue, attributes: JsValue) -> Result<JsValue> {
+    let ring: Ring = serde_wasm_bindgen::from_value(ring)?;
+    let options = if options.is_undefined() || options.is_null() {
+        SolveOptions::default()
+    } else {
+        serde_wasm_bindgen::from_value(options)?
+    };
+    let attributes = if attributes.is_undefined() || attributes.is_null() {
+        EnemyAttributes::default()
+    } else {
+        serde_wasm_bindgen::from_value(attributes)?
+    };
+    Ok(serde_wasm_bindgen::to_value(&audit(ring, &attributes, &options))?)
+}
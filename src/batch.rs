@@ -0,0 +1,52 @@
+//! Solving many boards at once across every core, for large studies.
+//!
+//! This is only in the native library; the web front end solves one board at a time in
+//! a worker.
+
+use crate::{find_solution, Ring, Solution, SolveOptions, MAX_TURNS};
+use rayon::prelude::*;
+use std::sync::mpsc;
+
+/// The result of solving one board of a batch.
+#[derive(Clone, Debug)]
+pub struct BoardAnalysis {
+    /// Where the board was in the batch; results arrive in the order they finish.
+    pub index: usize,
+    pub board: Ring,
+    /// The best solution, or the closest board found if the search ran out of budget.
+    pub solution: Option<Solution>,
+}
+
+impl BoardAnalysis {
+    /// The fewest turns the board needs, if it can be solved.
+    pub fn min_turns(&self) -> Option<u16> {
+        self.solution
+            .as_ref()
+            .filter(|solution| solution.perfect && !solution.truncated)
+            .map(|solution| solution.moves.len() as u16)
+    }
+}
+
+/// Solves every board on rayon's thread pool, calling `on_result` as each one finishes.
+/// `on_result` is called from the pool's threads, so it may be called concurrently.
+pub fn analyze_batch<F>(boards: &[Ring], options: &SolveOptions, on_result: F)
+where
+    F: Fn(BoardAnalysis) + Sync,
+{
+    boards.par_iter().enumerate().for_each(|(index, &board)| {
+        on_result(BoardAnalysis { index, board, solution: find_solution(board, MAX_TURNS, options) });
+    });
+}
+
+/// Solves every board in the background, streaming results through the returned channel
+/// as they finish. The channel closes once every board is done.
+pub fn analyze_batch_channel(boards: Vec<Ring>, options: SolveOptions) -> mpsc::Receiver<BoardAnalysis> {
+    let (sender, receiver) = mpsc::channel();
+    rayon::spawn(move || {
+        // If the receiver is dropped, the rest of the results just go nowhere.
+        analyze_batch(&boards, &options, |analysis| {
+            let _ = sender.send(analysis);
+        });
+    });
+    receiver
+}
@@ -0,0 +1,261 @@
+//! Battle math: how much damage the attacks on a solved board deal with the player's
+//! weapons, and whether that defeats every enemy this round.
+//!
+//! ```json
+//! {"battle": {"boots": "shiny", "hammer": "normal", "excellent": true}}
+//! ```
+//!
+//! The attacks are the ones in `Solution::attacks`, up to the actions the player gets.
+//! Every enemy an attack hits takes the full power of its weapon. Given each enemy's
+//! attack, the report also estimates the damage the player takes from the enemies left
+//! standing.
+//!
+//! `simulate_battle` steps through the same attacks one action at a time, for front ends
+//! that replay the whole turn.
+
+use crate::hp::{moved_hp, HpGrid, MAX_HP};
+use crate::priority::prioritize;
+use crate::{
+    apply_movement, attack_plan, check_ring, evaluate, Attack, Result, Ring, RingMovement,
+    NUM_ANGLES, NUM_RINGS,
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// How upgraded a weapon is. The power of each tier is typical of the weapons in it; the
+/// exact numbers vary from weapon to weapon.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Tier {
+    #[default]
+    Normal,
+    Shiny,
+    Flashy,
+}
+
+impl Tier {
+    /// The damage boots of this tier deal to each enemy a jump hits.
+    pub fn boots_power(self) -> u32 {
+        match self {
+            Tier::Normal => 3,
+            Tier::Shiny => 5,
+            Tier::Flashy => 7,
+        }
+    }
+
+    /// The damage a hammer of this tier deals to each enemy it hits.
+    pub fn hammer_power(self) -> u32 {
+        match self {
+            Tier::Normal => 4,
+            Tier::Shiny => 6,
+            Tier::Flashy => 9,
+        }
+    }
+}
+
+/// The weapons the player has equipped, and how well they attack.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BattleSetup {
+    pub boots: Tier,
+    pub hammer: Tier,
+    /// Whether every action command is hit "Excellent", which deals half again as much
+    /// damage, rounded down.
+    pub excellent: bool,
+    /// The HP of the enemy on each panel of the board being solved, by ring and then
+    /// angle, if known.
+    pub hp: Option<HpGrid>,
+    /// The attack of the enemy on each panel, in the same form as `hp`, if known.
+    pub attack: Option<HpGrid>,
+    /// The player's defense, taken off each enemy's attack.
+    pub defense: u16,
+    /// The panels with priority targets, like healers and buffers, whose attacks are made
+    /// first.
+    pub priority: Option<Ring>,
+}
+
+impl BattleSetup {
+    /// Checks that no enemy has more than `MAX_HP`, or an attack above it, and the
+    /// priority targets are on panels that exist.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        if let Some(priority) = &self.priority {
+            check_ring(priority)?;
+        }
+        match &self.hp {
+            Some(hp) if hp.iter().flatten().any(|&hp| hp > MAX_HP) => {
+                Err(format!("enemies can have at most {} HP", MAX_HP))
+            }
+            _ => match &self.attack {
+                Some(attack) if attack.iter().flatten().any(|&attack| attack > MAX_HP) => {
+                    Err(format!("enemies can have at most {} attack", MAX_HP))
+                }
+                _ => Ok(()),
+            },
+        }
+    }
+
+    fn power(&self, attack: &Attack) -> u32 {
+        let power = match attack {
+            Attack::Jump { .. } => self.boots.boots_power(),
+            Attack::Hammer { .. } => self.hammer.hammer_power(),
+        };
+        if self.excellent {
+            power * 3 / 2
+        } else {
+            power
+        }
+    }
+}
+
+/// The damage a solution's attacks deal.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DamageReport {
+    /// The damage dealt by each attack made, in order. Damage beyond an enemy's HP
+    /// isn't counted, when its HP is known.
+    pub attacks: Vec<u32>,
+    pub total_damage: u32,
+    /// Whether every enemy is defeated this round, or None if their HP isn't known.
+    pub all_defeated: Option<bool>,
+    /// How many enemies are defeated this round. Without their HP, every enemy hit is
+    /// taken to be defeated.
+    pub defeated: u32,
+    /// The damage the player takes from the enemies left standing, each attacking once,
+    /// or None if their attack isn't known.
+    pub counterattack: Option<u32>,
+}
+
+/// One action of the attack phase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BattleAction {
+    pub attack: Attack,
+    /// The damage dealt. Damage beyond an enemy's HP isn't counted, when its HP is known.
+    pub damage: u32,
+    /// The panels with an enemy the attack hit.
+    pub hit: Ring,
+    /// The panels whose enemy this attack defeated.
+    pub defeated: Ring,
+}
+
+/// The attack phase on a lined-up board, action by action.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BattleSimulation {
+    /// The actions the player makes, in order.
+    pub actions: Vec<BattleAction>,
+    /// The panels with an enemy still standing once every action is made.
+    pub standing: Ring,
+    /// The HP those enemies have left, if their HP is known.
+    pub hp: Option<HpGrid>,
+    /// Whether every enemy is defeated this round, or None if their HP isn't known.
+    pub all_defeated: Option<bool>,
+    /// The damage the player takes from the enemies left standing, each attacking once,
+    /// or None if their attack isn't known.
+    pub counterattack: Option<u32>,
+}
+
+/// Steps through the attacks on `result`, the board once the rings are lined up, up to
+/// the actions the player gets. The HP, attack, and priority targets in `setup` are for
+/// `result`.
+pub fn simulate(result: Ring, setup: &BattleSetup) -> BattleSimulation {
+    // Without HP, every enemy is taken to fall to the first hit, as the attacks assume.
+    let known = setup.hp.is_some();
+    let mut hp = [[0u32; NUM_ANGLES as usize]; NUM_RINGS as usize];
+    for (r, hps) in hp.iter_mut().enumerate() {
+        for (th, hp) in hps.iter_mut().enumerate() {
+            *hp = match &setup.hp {
+                Some(known) => known[r][th] as u32,
+                None => (result[r] >> th & 1) as u32,
+            };
+        }
+    }
+    let actions = evaluate(result).actions as usize;
+    let attacks = match setup.priority {
+        Some(priority) => prioritize(&attack_plan(result), result, priority),
+        None => attack_plan(result),
+    };
+    let mut steps = Vec::new();
+    for &attack in attacks.iter().take(actions) {
+        let power = setup.power(&attack);
+        let cells: Vec<(usize, usize)> = match attack {
+            // A jump hits the line outward from the innermost enemy standing.
+            Attack::Jump { th } => (0..NUM_RINGS as usize)
+                .skip_while(|&r| hp[r][th as usize] == 0)
+                .take_while(|&r| hp[r][th as usize] > 0)
+                .map(|r| (r, th as usize))
+                .collect(),
+            Attack::Hammer { th } => {
+                let next = (th as usize + 1) % NUM_ANGLES as usize;
+                vec![(0, th as usize), (1, th as usize), (0, next), (1, next)]
+            }
+        };
+        let mut step = BattleAction { attack, damage: 0, hit: [0; 4], defeated: [0; 4] };
+        for (r, th) in cells {
+            let left = &mut hp[r][th];
+            if *left > 0 {
+                step.damage += if known { power.min(*left) } else { power };
+                *left = if known { left.saturating_sub(power) } else { 0 };
+                step.hit[r] |= 1 << th;
+                if *left == 0 {
+                    step.defeated[r] |= 1 << th;
+                }
+            }
+        }
+        steps.push(step);
+    }
+    let mut standing = [0; NUM_RINGS as usize];
+    for (r, hps) in hp.iter().enumerate() {
+        for (th, &hp) in hps.iter().enumerate() {
+            standing[r] |= ((hp > 0) as u16) << th;
+        }
+    }
+    let counterattack = setup.attack.map(|attack| {
+        let hits = hp.iter().flatten().zip(attack.iter().flatten());
+        let hits = hits.filter(|&(&hp, _)| hp > 0);
+        hits.map(|(_, &attack)| attack.saturating_sub(setup.defense) as u32).sum()
+    });
+    BattleSimulation {
+        actions: steps,
+        standing,
+        hp: known.then(|| hp.map(|hps| hps.map(|hp| hp as u16))),
+        all_defeated: known.then(|| standing == [0; NUM_RINGS as usize]),
+        counterattack,
+    }
+}
+
+/// The damage dealt once `moves` have been made, reaching `result`. The HP, attack, and
+/// priority targets in `setup` are from before the moves.
+pub fn damage_report(moves: &[RingMovement], result: Ring, setup: &BattleSetup) -> DamageReport {
+    let aligned = BattleSetup {
+        hp: setup.hp.map(|hp| moved_hp(&hp, moves)),
+        attack: setup.attack.map(|attack| moved_hp(&attack, moves)),
+        priority: setup.priority.map(|priority| moves.iter().fold(priority, apply_movement)),
+        ..*setup
+    };
+    let simulation = simulate(result, &aligned);
+    let count = |ring: Ring| ring.iter().map(|subring| subring.count_ones()).sum::<u32>();
+    let attacks: Vec<u32> = simulation.actions.iter().map(|action| action.damage).collect();
+    DamageReport {
+        total_damage: attacks.iter().sum(),
+        attacks,
+        all_defeated: simulation.all_defeated,
+        defeated: count(result).saturating_sub(count(simulation.standing)),
+        counterattack: simulation.counterattack,
+    }
+}
+
+/// Simulate the attack phase on `result`, a board that's already lined up, with the
+/// weapons, HP, and attack of `setup` given for that board.
+#[wasm_bindgen(skip_typescript)]
+pub fn simulate_battle(result: JsValue, setup: JsValue) -> Result<JsValue> {
+    let result: Ring = serde_wasm_bindgen::from_value(result)?;
+    check_ring(&result)?;
+    let setup: BattleSetup = if setup.is_undefined() || setup.is_null() {
+        BattleSetup::default()
+    } else {
+        serde_wasm_bindgen::from_value(setup)?
+    };
+    setup.check()?;
+    Ok(serde_wasm_bindgen::to_value(&simulate(result, &setup))?)
+}
@@ -0,0 +1,73 @@
+//! Micro-benchmarks that run on the user's device, so front ends can tune engine settings
+//! to how fast it actually is.
+
+use crate::{
+    evaluate, now_millis, options_from_js, Result, Ring, Search, SolveOptions, MAX_TURNS, NUM_RINGS,
+};
+use serde::Serialize;
+use std::hint::black_box;
+use wasm_bindgen::prelude::*;
+
+/// Splits a packed batch of boards, `NUM_RINGS` subrings each, into rings.
+pub(crate) fn unpack_boards(packed: &[u16]) -> std::result::Result<Vec<Ring>, String> {
+    let chunks = packed.chunks_exact(NUM_RINGS as usize);
+    if !chunks.remainder().is_empty() {
+        return Err(format!("board batch length {} isn't a multiple of {}", packed.len(), NUM_RINGS));
+    }
+    Ok(chunks.map(|chunk| [chunk[0], chunk[1], chunk[2], chunk[3]]).collect())
+}
+
+/// Times the goal test over every board in `boards`, `iterations` times, and returns
+/// how many boards it evaluates per second.
+pub fn measure_goal_test(boards: &[Ring], iterations: u32) -> f64 {
+    let start = now_millis();
+    for _ in 0..iterations {
+        for &board in boards {
+            black_box(evaluate(black_box(board)).is_perfect());
+        }
+    }
+    let elapsed = now_millis() - start;
+    let evaluated = boards.len() as f64 * iterations as f64;
+    if elapsed > 0.0 {
+        evaluated / elapsed * 1000.0
+    } else {
+        f64::INFINITY
+    }
+}
+
+/// Measure how many boards per second this device can run the goal test on.
+/// `boards` is a packed batch of boards (e.g. a `Uint16Array`), four subrings each.
+#[wasm_bindgen]
+pub fn benchmark_goal_test(boards: &[u16], iterations: u32) -> Result<f64> {
+    let boards = unpack_boards(boards)?;
+    Ok(measure_goal_test(&boards, iterations))
+}
+
+/// How long solving a batch of boards took.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchMeasurement {
+    /// The boards visited across every search.
+    pub nodes: u64,
+    pub millis: f64,
+}
+
+/// Solves every board in `boards` with `options`, e.g. to compare move orders.
+pub fn measure_search(boards: &[Ring], options: &SolveOptions) -> SearchMeasurement {
+    let start = now_millis();
+    let mut nodes = 0;
+    for &board in boards {
+        let mut search = Search::new(options);
+        black_box(search.find_solution(board, MAX_TURNS));
+        nodes += search.budget.nodes;
+    }
+    SearchMeasurement { nodes, millis: now_millis() - start }
+}
+
+/// Measure how long solving a packed batch of boards takes with the given options.
+#[wasm_bindgen(skip_typescript)]
+pub fn benchmark_search(boards: &[u16], options: JsValue) -> Result<JsValue> {
+    let boards = unpack_boards(boards)?;
+    let options = options_from_js(options)?;
+    Ok(serde_wasm_bindgen::to_value(&measure_search(&boards, &options))?)
+}
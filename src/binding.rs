@@ -0,0 +1,221 @@
+//! Explains which constraints stopped the solver from suggesting a better line,
+//! e.g. "a shorter solution rotates ring 0, which is forbidden", and which hazards on the
+//! board did, e.g. "a shorter solution jumps at angle 7, onto a spiked enemy".
+
+use crate::attributes::EnemyAttributes;
+use crate::preset::Rules;
+use crate::{
+    apply_movement, Attack, MoveConstraints, Ring, RingMovement, Search, Solution, SolveOptions,
+};
+use serde::{Deserialize, Serialize};
+
+/// Why a movement in a better line isn't allowed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BindingReason {
+    /// The options force a different first move.
+    NotFirstMove,
+    /// Rotations are forbidden.
+    RotationsForbidden,
+    /// Shifts are forbidden.
+    ShiftsForbidden,
+    /// Rotating this ring is forbidden.
+    RingForbidden,
+    /// Shifting this row is forbidden.
+    RowForbidden,
+    /// The rotation is larger than `maxRotation`.
+    RotationTooLarge,
+    /// The shift is larger than `maxShift`.
+    ShiftTooLarge,
+}
+
+/// A constraint that ruled out a better solution.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BindingConstraint {
+    /// The movement in the better solution that isn't allowed.
+    pub movement: RingMovement,
+    pub reason: BindingReason,
+    /// How many fewer turns the better solution takes. 0 if it only drags fewer notches.
+    pub turns_saved: u16,
+}
+
+/// Something on the board that a better line would run into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Hazard {
+    /// An attack would land on a crumpled Toad.
+    Toad,
+    /// A jump would land on a spiked enemy.
+    Spiked,
+    /// A hammer would be swung at a flying enemy.
+    Flying,
+    /// A row would be shifted through a blocked panel.
+    Blocked,
+}
+
+/// A hazard that ruled out a better solution.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AvoidedHazard {
+    pub hazard: Hazard,
+    /// The attack in the better solution that runs into the hazard, if it's an attack.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attack: Option<Attack>,
+    /// The movement in the better solution that runs into the hazard, if it's a movement.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub movement: Option<RingMovement>,
+    /// How many fewer turns the better solution takes. 0 if it only drags fewer notches.
+    pub turns_saved: u16,
+}
+
+impl MoveConstraints {
+    /// Why these constraints don't allow `movement`, if they don't.
+    pub fn violation(&self, movement: &RingMovement) -> Option<BindingReason> {
+        match *movement {
+            RingMovement::Ring { .. } if !self.rotations => Some(BindingReason::RotationsForbidden),
+            RingMovement::Row { .. } if !self.shifts => Some(BindingReason::ShiftsForbidden),
+            RingMovement::Ring { r, .. } if !self.allows_ring(r) => Some(BindingReason::RingForbidden),
+            RingMovement::Row { th, .. } if !self.allows_row(th) => Some(BindingReason::RowForbidden),
+            RingMovement::Ring { .. } if movement.distance() > self.max_rotation as u32 => {
+                Some(BindingReason::RotationTooLarge)
+            }
+            RingMovement::Row { .. } if movement.distance() > self.max_shift as u32 => {
+                Some(BindingReason::ShiftTooLarge)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Finds the constraints in `options` that stopped a better solution than `solution`
+/// from being chosen, by searching again without them in what's left of the budget of
+/// the `search` that found it.
+///
+/// Returns nothing if the options don't constrain the search, or if the constraints
+/// didn't matter.
+pub(crate) async fn find_binding(
+    search: &mut Search<'_>,
+    ring: Ring,
+    max_turns: u16,
+    options: &SolveOptions,
+    solution: &Solution,
+) -> Vec<BindingConstraint> {
+    // Locked rings are part of the encounter, not the player's choice, so they stay.
+    let locked = MoveConstraints { locked: options.constraints.locked, ..Default::default() };
+    let constrained = options.first_move.is_some() || options.constraints != locked;
+    if !constrained || !solution.perfect {
+        return Vec::new();
+    }
+    let unconstrained = SolveOptions { first_move: None, constraints: locked, ..options.clone() };
+    let turns = solution.moves.len() as u16;
+    let better = match search.search_again(&unconstrained, ring, max_turns.min(turns), 1).await.pop() {
+        Some(better) if better.perfect => better,
+        _ => return Vec::new(),
+    };
+    let turns_saved = match turns_saved(&better, solution) {
+        Some(turns_saved) => turns_saved,
+        None => return Vec::new(),
+    };
+    let mut binding = Vec::new();
+    if let (Some(first_move), Some(&better_first)) = (options.first_move, better.moves.front()) {
+        if first_move != better_first {
+            binding.push(BindingConstraint {
+                movement: better_first,
+                reason: BindingReason::NotFirstMove,
+                turns_saved,
+            });
+        }
+    }
+    binding.extend(better.moves.iter().filter_map(|&movement| {
+        options.constraints.violation(&movement).map(|reason| BindingConstraint {
+            movement,
+            reason,
+            turns_saved,
+        })
+    }));
+    binding
+}
+
+/// How many fewer turns `better` takes than `solution`, if it's ranked ahead of it.
+pub(crate) fn turns_saved(better: &Solution, solution: &Solution) -> Option<u16> {
+    let key = |s: &Solution| (s.moves.len(), s.rank());
+    (key(better) < key(solution)).then(|| (solution.moves.len() - better.moves.len()) as u16)
+}
+
+/// The attacks of `better` that land on `cells`, which have been moved along with it.
+fn attacks_on(
+    hazard: Hazard,
+    attacks: impl IntoIterator<Item = Attack>,
+    cells: Ring,
+    turns_saved: u16,
+) -> impl Iterator<Item = AvoidedHazard> {
+    attacks.into_iter().filter_map(move |attack| {
+        let hits = attack.footprint().iter().zip(&cells).any(|(hit, cells)| hit & cells != 0);
+        hits.then_some(AvoidedHazard { hazard, attack: Some(attack), movement: None, turns_saved })
+    })
+}
+
+/// Finds the attacks on Toads that stopped a better solution than `solution` from being
+/// chosen, by searching again as if there were none, in what's left of the budget of the
+/// `search` that found it.
+pub(crate) async fn find_toad_hazards(
+    search: &mut Search<'_>,
+    ring: Ring,
+    max_turns: u16,
+    options: &SolveOptions,
+    solution: &Solution,
+) -> Vec<AvoidedHazard> {
+    let toads = match options.toads {
+        Some(toads) if toads != Ring::default() && solution.perfect => toads,
+        _ => return Vec::new(),
+    };
+    let unprotected = SolveOptions { toads: None, ..options.clone() };
+    let turns = solution.moves.len() as u16;
+    let better = match search.search_again(&unprotected, ring, max_turns.min(turns), 1).await.pop() {
+        Some(better) if better.perfect => better,
+        _ => return Vec::new(),
+    };
+    let turns_saved = match turns_saved(&better, solution) {
+        Some(turns_saved) => turns_saved,
+        None => return Vec::new(),
+    };
+    // The attacks the Toads are checked against, as `toads::ToadGoal` does.
+    let rules = options.battle_rules().unwrap_or_default();
+    let toads = better.moves.iter().fold(toads, apply_movement);
+    attacks_on(Hazard::Toad, rules.attack_plan(better.result), toads, turns_saved).collect()
+}
+
+/// Finds the jumps on spiked enemies and hammers at flying ones that stopped a better
+/// solution than `solution` from being chosen, by searching again as if no enemy had an
+/// attribute, in what's left of the budget of the `search` that found it.
+pub(crate) async fn find_attribute_hazards(
+    search: &mut Search<'_>,
+    ring: Ring,
+    attributes: &EnemyAttributes,
+    rules: &Rules,
+    options: &SolveOptions,
+    solution: &Solution,
+) -> Vec<AvoidedHazard> {
+    let EnemyAttributes { spiked, flying, .. } = *attributes;
+    if spiked == Ring::default() && flying == Ring::default() || !solution.perfect {
+        return Vec::new();
+    }
+    let turns = solution.moves.len() as u16;
+    let better = match search.search_again(options, ring, turns, 1).await.pop() {
+        Some(better) if better.perfect => better,
+        _ => return Vec::new(),
+    };
+    let turns_saved = match turns_saved(&better, solution) {
+        Some(turns_saved) => turns_saved,
+        None => return Vec::new(),
+    };
+    let moved = |cells: Ring| better.moves.iter().fold(cells, apply_movement);
+    let attacks = rules.attack_plan(better.result);
+    let jumps = attacks.iter().copied().filter(|attack| matches!(attack, Attack::Jump { .. }));
+    let hammers = attacks.iter().copied().filter(|attack| matches!(attack, Attack::Hammer { .. }));
+    let mut hazards: Vec<AvoidedHazard> =
+        attacks_on(Hazard::Spiked, jumps, moved(spiked), turns_saved).collect();
+    hazards.extend(attacks_on(Hazard::Flying, hammers, moved(flying), turns_saved));
+    hazards
+}
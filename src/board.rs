@@ -0,0 +1,154 @@
+//! A board kept in Rust between calls, so a frontend moving the rings or editing the board
+//! one cell at a time doesn't pass the whole board back and forth on every change.
+//!
+//! The movements made on it are kept too, so they can be undone and redone the same way
+//! in every frontend.
+
+use crate::validation::ring_from_js;
+use crate::{
+    apply_movement, check_movement, find_solution, oriented_options_from_js, solve_to_js,
+    Result, Ring, RingMovement, Solution, SolveOptions, MAX_TURNS, NUM_ANGLES, NUM_RINGS,
+};
+use wasm_bindgen::prelude::*;
+
+/// The current board, the movements made on it, and the options to solve it with.
+#[wasm_bindgen]
+pub struct RingBoard {
+    ring: Ring,
+    options: SolveOptions,
+    /// Each movement made that can be undone, with the board before it, oldest first.
+    past: Vec<(RingMovement, Ring)>,
+    /// The movements undone that can be made again, the next to redo last.
+    future: Vec<RingMovement>,
+}
+
+impl RingBoard {
+    /// Holds `ring`, to be solved with `options`.
+    pub fn with_options(ring: Ring, options: SolveOptions) -> Self {
+        RingBoard { ring, options, past: Vec::new(), future: Vec::new() }
+    }
+
+    /// The current board.
+    pub fn ring(&self) -> Ring {
+        self.ring
+    }
+
+    /// Makes `movement` on the board, which can then be undone. Anything undone can no
+    /// longer be redone.
+    pub fn make(&mut self, movement: &RingMovement) -> std::result::Result<(), String> {
+        check_movement(movement)?;
+        self.past.push((*movement, self.ring));
+        self.future.clear();
+        self.ring = apply_movement(self.ring, movement);
+        Ok(())
+    }
+
+    /// Takes back the last movement made, returning it, or None if there's none to undo.
+    pub fn undo(&mut self) -> Option<RingMovement> {
+        let (movement, ring) = self.past.pop()?;
+        self.ring = ring;
+        self.future.push(movement);
+        Some(movement)
+    }
+
+    /// Makes the last movement undone again, returning it, or None if there's none to redo.
+    pub fn redo(&mut self) -> Option<RingMovement> {
+        let movement = self.future.pop()?;
+        self.past.push((movement, self.ring));
+        self.ring = apply_movement(self.ring, &movement);
+        Some(movement)
+    }
+
+    /// The movements made on the board that can be undone, oldest first.
+    pub fn history(&self) -> Vec<RingMovement> {
+        self.past.iter().map(|&(movement, _)| movement).collect()
+    }
+
+    /// Adds an enemy at angle `th` of ring `r`, or removes the one there, returning whether
+    /// there's one there now. This makes a new board, so the movements made before can't
+    /// be undone or redone.
+    pub fn toggle(&mut self, r: u16, th: u16) -> std::result::Result<bool, String> {
+        if r >= NUM_RINGS {
+            return Err(format!("ring index {} out of range", r));
+        }
+        if th >= NUM_ANGLES {
+            return Err(format!("angle {} out of range", th));
+        }
+        self.ring[r as usize] ^= 1 << th;
+        self.past.clear();
+        self.future.clear();
+        Ok(self.ring[r as usize] & (1 << th) != 0)
+    }
+
+    /// Solves the current board, like `find_solution`. The board and its movements are in
+    /// the orientation of the options, and the solution is in the solver's.
+    pub fn find_solution(&self) -> Option<Solution> {
+        let ring = self.options.orientation.board_in(self.ring);
+        find_solution(ring, MAX_TURNS, &self.options)
+    }
+}
+
+#[wasm_bindgen]
+impl RingBoard {
+    /// Hold a board, to be solved with `options`. The board, and the movements made on
+    /// it, are in the orientation of `options`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(ring: JsValue, options: JsValue) -> Result<RingBoard> {
+        Ok(RingBoard::with_options(ring_from_js(ring)?, oriented_options_from_js(options)?))
+    }
+
+    /// The current board.
+    #[wasm_bindgen(js_name = board, skip_typescript)]
+    pub fn board_js(&self) -> Result<JsValue> {
+        Ok(serde_wasm_bindgen::to_value(&self.ring)?)
+    }
+
+    /// Rotate ring `r` by `amount` angles.
+    #[wasm_bindgen(js_name = rotateRing)]
+    pub fn rotate_ring(&mut self, r: u16, amount: i16, clockwise: bool) -> Result<()> {
+        Ok(self.make(&RingMovement::Ring { r, amount, clockwise })?)
+    }
+
+    /// Shift the row at angle `th` by `amount` cells.
+    #[wasm_bindgen(js_name = shiftRow)]
+    pub fn shift_row(&mut self, th: u16, amount: i16, outward: bool) -> Result<()> {
+        Ok(self.make(&RingMovement::Row { th, amount, outward })?)
+    }
+
+    /// Add or remove the enemy at angle `th` of ring `r`, returning whether there's one
+    /// there now.
+    #[wasm_bindgen(js_name = toggleCell)]
+    pub fn toggle_cell(&mut self, r: u16, th: u16) -> Result<bool> {
+        Ok(self.toggle(r, th)?)
+    }
+
+    /// Take back the last movement, returning it, or null if there's none to undo.
+    #[wasm_bindgen(js_name = undo, skip_typescript)]
+    pub fn undo_js(&mut self) -> Result<JsValue> {
+        Ok(serde_wasm_bindgen::to_value(&self.undo())?)
+    }
+
+    /// Make the last movement undone again, returning it, or null if there's none to redo.
+    #[wasm_bindgen(js_name = redo, skip_typescript)]
+    pub fn redo_js(&mut self) -> Result<JsValue> {
+        Ok(serde_wasm_bindgen::to_value(&self.redo())?)
+    }
+
+    /// The movements that can be undone, oldest first.
+    #[wasm_bindgen(js_name = history, skip_typescript)]
+    pub fn history_js(&self) -> Result<JsValue> {
+        Ok(serde_wasm_bindgen::to_value(&self.history())?)
+    }
+
+    /// Solve the current board, like the free `solve` function.
+    #[wasm_bindgen(js_name = solve, skip_typescript)]
+    pub fn solve_js(&self) -> Result<JsValue> {
+        solve_to_js(self.find_solution(), &self.options)
+    }
+
+    /// The current board as JSON, the same array `solve` takes.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(&self.ring).unwrap_or_default()
+    }
+}
@@ -0,0 +1,686 @@
+//! Boss battles, where the rings hold panels instead of enemies. After the player moves
+//! the rings, Mario walks in from the outer edge, following arrow panels, and has to reach
+//! the attack panel to hit the boss. Action panels he walks over on the way each give him
+//! something to use in the attack.
+//!
+//! ```json
+//! {"panels": [[{"type": "empty"}, {"type": "arrow", "direction": "in"}, ...], ...]}
+//! ```
+//!
+//! Mario keeps walking the way he's going over empty and action panels, turns at each
+//! arrow, and stops at the attack panel. A route that walks into the boss in the middle,
+//! off the outer edge, or around in circles doesn't reach it.
+//!
+//! Some arenas also have ON/OFF switches. Each switch panel flips every switch of its group
+//! when Mario crosses it, which raises or lowers the bridges of that group and turns its
+//! switch arrows. Walking onto a lowered bridge drops Mario off the route.
+//!
+//! Treasure chests and hearts along the way are picked up the first time Mario crosses
+//! them. Of the routes that reach the attack panel, the best scores the most by the
+//! arena's `weights`, so players can go for the most damage or the most healing.
+//!
+//! Bosses can also cover panels with hazards, like flooded tiles, spikes, or flames. What
+//! each kind of hazard does is given with the arena, so a new boss's hazards are just
+//! more data: either Mario can't walk onto them, or they hurt him each time he does. Of
+//! routes that score the same, the one that hurts him least is best.
+//!
+//! Magic circles grant Mario a special action the first time he crosses them, like the
+//! 1000-Fold Arms. They score like any other pickup, but they're often off the shortest
+//! way in: with a `turn` weight, the search also tries making more turns, and takes them
+//! when the detour scores more than the turns it costs.
+//!
+//! A whole fight can also be planned ahead: given what the boss does after each of
+//! Mario's attacks, `plan_fight` chooses each turn's movements for the best fight
+//! overall, rather than the best turn at a time.
+
+use crate::exhaustive::{Exhaustive, Leaves, Moves};
+use crate::hp::{moved_hp, HpGrid};
+use crate::{
+    check_movement, iterate_movements, options_from_js, total_distance, MoveConstraints,
+    Result, RingMovement, SolveOptions, MAX_TURNS, NUM_ANGLES, NUM_RINGS,
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// Which way an arrow panel sends Mario.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Direction {
+    In,
+    Out,
+    Clockwise,
+    Counterclockwise,
+}
+
+/// What's on a panel of a boss arena.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Panel {
+    #[default]
+    Empty,
+    Arrow { direction: Direction },
+    Action,
+    Attack,
+    /// Flips the switches of `group` when crossed.
+    Switch { group: u8 },
+    /// Only there to walk on while the switches of `group` are `on`.
+    Bridge { group: u8, on: bool },
+    /// An arrow that points one way while the switches of `group` are on, and another
+    /// while they're off.
+    SwitchArrow { group: u8, on: Direction, off: Direction },
+    /// A treasure chest.
+    Chest,
+    /// A heart, which heals Mario.
+    Heart,
+    /// A hazard, which does what the arena's hazard `kind` does.
+    Hazard { kind: u8 },
+    /// A magic circle, which grants `spell`.
+    MagicCircle { spell: Spell },
+}
+
+/// The special action a magic circle grants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Spell {
+    /// Lets Mario use the 1000-Fold Arms on the boss.
+    ThousandFoldArms,
+    /// Powers up Mario's attack.
+    Attack,
+    /// Heals Mario.
+    Heal,
+}
+
+const SPELLS: [Spell; 3] = [Spell::ThousandFoldArms, Spell::Attack, Spell::Heal];
+
+/// How many kinds of hazard an arena can have.
+pub const HAZARD_KINDS: u8 = 16;
+
+/// What walking onto a kind of hazard does to Mario.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum HazardEffect {
+    /// He can't walk onto it, so routes over it don't reach the attack panel.
+    Block,
+    /// He takes `damage` each time he walks onto it.
+    Damage { damage: u32 },
+}
+
+/// A kind of hazard, e.g. `{"name": "flames", "effect": {"type": "damage", "damage": 2}}`.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Hazard {
+    pub name: String,
+    pub effect: HazardEffect,
+}
+
+/// How many groups of switches an arena can have.
+pub const SWITCH_GROUPS: u8 = 4;
+
+const DIRECTIONS: [Direction; 4] =
+    [Direction::In, Direction::Out, Direction::Clockwise, Direction::Counterclockwise];
+
+impl Panel {
+    /// A number for the panel, so the panels can be moved like HP.
+    pub(crate) fn code(self) -> u16 {
+        match self {
+            Panel::Empty => 0,
+            Panel::Attack => 1,
+            Panel::Action => 2,
+            Panel::Arrow { direction } => 3 + direction as u16,
+            Panel::Switch { group } => 7 + group as u16,
+            Panel::Bridge { group, on } => 11 + group as u16 * 2 + on as u16,
+            Panel::SwitchArrow { group, on, off } => {
+                19 + group as u16 * 16 + on as u16 * 4 + off as u16
+            }
+            Panel::Chest => 83,
+            Panel::Heart => 84,
+            Panel::Hazard { kind } => 85 + kind as u16,
+            Panel::MagicCircle { spell } => 101 + spell as u16,
+        }
+    }
+
+    /// The panel with `code`, or an error if there's none.
+    pub(crate) fn from_code(code: u16) -> std::result::Result<Self, String> {
+        Ok(match code {
+            0 => Panel::Empty,
+            1 => Panel::Attack,
+            2 => Panel::Action,
+            3..=6 => Panel::Arrow { direction: DIRECTIONS[code as usize - 3] },
+            7..=10 => Panel::Switch { group: (code - 7) as u8 },
+            11..=18 => {
+                let code = code - 11;
+                Panel::Bridge { group: (code / 2) as u8, on: code % 2 == 1 }
+            }
+            19..=82 => {
+                let code = code as usize - 19;
+                let (on, off) = (DIRECTIONS[code / 4 % 4], DIRECTIONS[code % 4]);
+                Panel::SwitchArrow { group: (code / 16) as u8, on, off }
+            }
+            83 => Panel::Chest,
+            84 => Panel::Heart,
+            85..=100 => Panel::Hazard { kind: (code - 85) as u8 },
+            101..=103 => Panel::MagicCircle { spell: SPELLS[code as usize - 101] },
+            code => return Err(format!("there's no panel with code {}", code)),
+        })
+    }
+
+    /// The switch group this panel belongs to, if any.
+    fn group(self) -> Option<u8> {
+        match self {
+            Panel::Switch { group }
+            | Panel::Bridge { group, .. }
+            | Panel::SwitchArrow { group, .. } => Some(group),
+            _ => None,
+        }
+    }
+}
+
+/// The panels of a boss arena, by ring and then angle, like `Ring`.
+pub type PanelGrid = [[Panel; NUM_ANGLES as usize]; NUM_RINGS as usize];
+
+/// What a route is worth for each panel it picks up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RouteWeights {
+    pub action: u32,
+    pub chest: u32,
+    pub heart: u32,
+    pub thousand_fold_arms: u32,
+    pub attack_circle: u32,
+    pub heal_circle: u32,
+    /// What each turn costs, to weigh against the score of the routes more turns allow.
+    /// If None, the fewest turns always come first.
+    pub turn: Option<u32>,
+}
+
+impl Default for RouteWeights {
+    /// The most damage: only action panels and the circles that help attack count.
+    fn default() -> Self {
+        RouteWeights {
+            action: 1,
+            chest: 0,
+            heart: 0,
+            thousand_fold_arms: 3,
+            attack_circle: 2,
+            heal_circle: 0,
+            turn: None,
+        }
+    }
+}
+
+impl RouteWeights {
+    pub fn score(&self, route: &Route) -> u32 {
+        let spells = route.spells.iter().map(|spell| match spell {
+            Spell::ThousandFoldArms => self.thousand_fold_arms,
+            Spell::Attack => self.attack_circle,
+            Spell::Heal => self.heal_circle,
+        });
+        route.actions * self.action
+            + route.chests * self.chest
+            + route.hearts * self.heart
+            + spells.sum::<u32>()
+    }
+
+    /// The score of `route` less the cost of the `turns` it takes to make.
+    pub fn value(&self, route: &Route, turns: usize) -> i64 {
+        self.score(route) as i64 - self.turn.unwrap_or(0) as i64 * turns as i64
+    }
+}
+
+/// A boss arena to solve.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BossBoard {
+    pub panels: PanelGrid,
+    /// The groups of switches that start on, as bits from group 0 up.
+    pub switches: u8,
+    /// How to choose between the routes to the attack panel.
+    pub weights: RouteWeights,
+    /// What each kind of hazard does, by `kind`.
+    pub hazards: Vec<Hazard>,
+}
+
+impl BossBoard {
+    /// Checks that there's an attack panel to reach, and every switch group and kind of
+    /// hazard exists.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        if !has_attack_panel(&self.panels) {
+            return Err("the arena has no attack panel".to_string());
+        }
+        if self.switches >> SWITCH_GROUPS != 0 {
+            return Err(format!("arenas can only have {} switch groups", SWITCH_GROUPS));
+        }
+        if self.hazards.len() > HAZARD_KINDS as usize {
+            return Err(format!("arenas can only have {} kinds of hazard", HAZARD_KINDS));
+        }
+        self.panels.iter().flatten().try_for_each(|&panel| self.check_panel(panel))
+    }
+
+    /// Checks that the switch group or kind of hazard of `panel` exists in this arena.
+    fn check_panel(&self, panel: Panel) -> std::result::Result<(), String> {
+        if panel.group().is_some_and(|group| group >= SWITCH_GROUPS) {
+            return Err(format!("arenas can only have {} switch groups", SWITCH_GROUPS));
+        }
+        match panel {
+            Panel::Hazard { kind } if kind as usize >= self.hazards.len() => {
+                Err(format!("hazard kind {} isn't defined", kind))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The arena after `movement`.
+    pub fn moved(&self, movement: &RingMovement) -> std::result::Result<Self, String> {
+        Ok(BossBoard { panels: moved_panels(&self.panels, movement)?, ..self.clone() })
+    }
+
+    /// The route Mario takes entering at angle `th`, or None if it doesn't reach the attack
+    /// panel.
+    pub fn walk(&self, th: u16) -> Option<Route> {
+        self.walk_on(&self.panels, th)
+    }
+
+    /// Like `walk`, but with the panels moved to `panels`.
+    fn walk_on(&self, panels: &PanelGrid, th: u16) -> Option<Route> {
+        let (mut r, mut th, mut direction) = (NUM_RINGS - 1, th, Direction::In);
+        let mut switches = self.switches;
+        let mut path = Vec::new();
+        let (mut actions, mut chests, mut hearts, mut damage) = (0, 0, 0, 0);
+        let mut spells = Vec::new();
+        // Each panel can only be crossed once each way with the switches set the same way
+        // before Mario is walking in circles.
+        let mut crossed = [[0u64; NUM_ANGLES as usize]; NUM_RINGS as usize];
+        loop {
+            let seen = &mut crossed[r as usize][th as usize];
+            let first = *seen == 0;
+            let state = 1u64 << (switches as u32 * 4 + direction as u32);
+            if *seen & state != 0 {
+                return None;
+            }
+            *seen |= state;
+            path.push(PanelPosition { r, th });
+            let is_on = |group: u8| switches & 1 << group != 0;
+            match panels[r as usize][th as usize] {
+                Panel::Attack => {
+                    let start = path[0].th;
+                    return Some(Route { start, path, actions, chests, hearts, spells, damage });
+                }
+                Panel::Action => actions += 1,
+                Panel::Arrow { direction: turn } => direction = turn,
+                Panel::Switch { group } => switches ^= 1 << group,
+                Panel::Bridge { group, on } if is_on(group) != on => return None,
+                Panel::SwitchArrow { group, on, off } => {
+                    direction = if is_on(group) { on } else { off };
+                }
+                Panel::Chest if first => chests += 1,
+                Panel::Heart if first => hearts += 1,
+                Panel::MagicCircle { spell } if first => spells.push(spell),
+                Panel::Hazard { kind } => match self.hazards[kind as usize].effect {
+                    HazardEffect::Block => return None,
+                    HazardEffect::Damage { damage: hurt } => damage += hurt,
+                },
+                Panel::Empty
+                | Panel::Bridge { .. }
+                | Panel::Chest
+                | Panel::Heart
+                | Panel::MagicCircle { .. } => {}
+            }
+            match direction {
+                Direction::In if r == 0 => return None,
+                Direction::In => r -= 1,
+                Direction::Out if r == NUM_RINGS - 1 => return None,
+                Direction::Out => r += 1,
+                Direction::Clockwise => th = (th + 1) % NUM_ANGLES,
+                Direction::Counterclockwise => th = (th + NUM_ANGLES - 1) % NUM_ANGLES,
+            }
+        }
+    }
+
+    /// The best route into the arena as it is: the highest score, then the least damage,
+    /// then the shortest.
+    pub fn best_route(&self) -> Option<Route> {
+        self.best_route_on(&self.panels)
+    }
+
+    /// Like `best_route`, but with the panels moved to `panels`.
+    fn best_route_on(&self, panels: &PanelGrid) -> Option<Route> {
+        (0..NUM_ANGLES).filter_map(|th| self.walk_on(panels, th)).min_by_key(|route| {
+            (std::cmp::Reverse(self.weights.score(route)), route.damage, route.path.len())
+        })
+    }
+}
+
+/// Whether `panels` have an attack panel for Mario to reach.
+fn has_attack_panel(panels: &PanelGrid) -> bool {
+    panels.iter().flatten().any(|&panel| panel == Panel::Attack)
+}
+
+/// The panels after `movement`, moved like HP by their codes.
+fn moved_panels(
+    panels: &PanelGrid,
+    movement: &RingMovement,
+) -> std::result::Result<PanelGrid, String> {
+    let codes: HpGrid = panels.map(|panels| panels.map(Panel::code));
+    let codes = moved_hp(&codes, std::slice::from_ref(movement));
+    let mut moved = PanelGrid::default();
+    for (panel, &code) in moved.iter_mut().flatten().zip(codes.iter().flatten()) {
+        *panel = Panel::from_code(code)?;
+    }
+    Ok(moved)
+}
+
+/// A panel Mario walks over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PanelPosition {
+    pub r: u16,
+    pub th: u16,
+}
+
+/// Where Mario walks from the outer edge to the attack panel.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Route {
+    /// The angle he enters at.
+    pub start: u16,
+    /// Every panel he walks over, ending at the attack panel.
+    pub path: Vec<PanelPosition>,
+    /// How many action panels he walks over.
+    pub actions: u32,
+    /// How many chests he picks up.
+    pub chests: u32,
+    /// How many hearts he picks up.
+    pub hearts: u32,
+    /// The spells of the magic circles he crosses, in order.
+    pub spells: Vec<Spell>,
+    /// The damage he takes from hazards.
+    pub damage: u32,
+}
+
+/// The movements to make before Mario walks, and the route he takes.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BossSolution {
+    pub moves: Vec<RingMovement>,
+    /// The arena after the movements.
+    pub board: BossBoard,
+    pub route: Route,
+    /// Whether the search ran out of budget, so fewer turns might have been enough.
+    pub truncated: bool,
+}
+
+impl BossSolution {
+    /// How this ranks against other solutions, best first.
+    fn rank(&self) -> (std::cmp::Reverse<i64>, u32, usize, u32) {
+        rank(&self.board.weights, &self.moves, &self.route)
+    }
+}
+
+/// How the solution that makes `moves` for `route` ranks against others, best first.
+fn rank(
+    weights: &RouteWeights,
+    moves: &[RingMovement],
+    route: &Route,
+) -> (std::cmp::Reverse<i64>, u32, usize, u32) {
+    let value = weights.value(route, moves.len());
+    (std::cmp::Reverse(value), route.damage, moves.len(), total_distance(moves))
+}
+
+/// Moves the panels of an arena. Every ring and row has panels to move, whatever's on
+/// them.
+struct ArenaMoves;
+
+impl Moves for ArenaMoves {
+    type Board = PanelGrid;
+    type Error = String;
+
+    fn each_movement(
+        &self,
+        panels: &PanelGrid,
+        _turns: usize,
+        constraints: &MoveConstraints,
+        mut visit: impl FnMut(RingMovement, PanelGrid) -> std::result::Result<(), String>,
+    ) -> std::result::Result<(), String> {
+        let full = [(1 << NUM_ANGLES) - 1; NUM_RINGS as usize];
+        let error = iterate_movements(full, constraints, |movement, _| {
+            moved_panels(panels, &movement).and_then(|moved| visit(movement, moved)).err()
+        });
+        error.map_or(Ok(()), Err)
+    }
+
+    fn first_moved(
+        &self,
+        panels: &PanelGrid,
+        movement: &RingMovement,
+    ) -> std::result::Result<Option<PanelGrid>, String> {
+        moved_panels(panels, movement).map(Some)
+    }
+}
+
+/// Keeps the arenas with the best scoring routes, then the smallest total distance.
+struct BossLeaves<'a> {
+    /// The arena as given. Only its panels move during the search.
+    arena: &'a BossBoard,
+    /// The best solutions found so far, best first, each leaving a different arena.
+    found: Vec<BossSolution>,
+    /// How many solutions to keep.
+    keep: usize,
+}
+
+impl Leaves<PanelGrid> for BossLeaves<'_> {
+    /// Keeps the path to `panels` if it's among the best, and the best way to those
+    /// panels.
+    fn leaf(&mut self, &panels: &PanelGrid, path: &[RingMovement]) {
+        let route = match self.arena.best_route_on(&panels) {
+            Some(route) => route,
+            None => return,
+        };
+        let rank = rank(&self.arena.weights, path, &route);
+        if let Some(same) = self.found.iter().position(|found| found.board.panels == panels) {
+            if self.found[same].rank() <= rank {
+                return;
+            }
+            self.found.remove(same);
+        }
+        let at = self.found.partition_point(|found| found.rank() <= rank);
+        if at < self.keep {
+            let board = BossBoard { panels, ..self.arena.clone() };
+            let moves = path.to_vec();
+            self.found.insert(at, BossSolution { moves, board, route, truncated: false });
+            self.found.truncate(self.keep);
+        }
+    }
+}
+
+/// The best `keep` solutions for `board` with the fewest turns, each leaving a different
+/// arena, and whether the search ran out of budget. The arena has to be checked first,
+/// but it may have no attack panel to reach.
+fn solutions(
+    board: &BossBoard,
+    options: &SolveOptions,
+    keep: usize,
+) -> std::result::Result<(Vec<BossSolution>, bool), String> {
+    let rules = options.battle_rules()?;
+    if !has_attack_panel(&board.panels) {
+        return Ok((Vec::new(), false));
+    }
+    let mut leaves = BossLeaves { arena: board, found: Vec::new(), keep };
+    let mut search = Exhaustive::new(&ArenaMoves, options);
+    let max_turns = rules.max_turns.min(MAX_TURNS);
+    // With a cost for each turn, more turns can still be worth it.
+    search.search_depths(&board.panels, max_turns, &mut leaves, |leaves| {
+        !leaves.found.is_empty() && board.weights.turn.is_none()
+    })?;
+    let truncated = search.budget.truncated;
+    for solution in &mut leaves.found {
+        solution.truncated = truncated;
+    }
+    Ok((leaves.found, truncated))
+}
+
+/// Finds the fewest turns that give Mario a route to the attack panel of `board`, then
+/// the highest score for it, then the smallest total distance. With a `turn` weight,
+/// it's the highest score less the turns instead. Returns None if no
+/// route can be made within the turn limit.
+pub fn find_solution(
+    board: &BossBoard,
+    options: &SolveOptions,
+) -> std::result::Result<Option<BossSolution>, String> {
+    board.check()?;
+    Ok(solutions(board, options, 1)?.0.pop())
+}
+
+/// How many of the best fights so far `plan_fight` carries on from each turn, and how
+/// many ways to play each turn it tries for each of them.
+pub const PLAN_WIDTH: usize = 8;
+
+/// A panel the boss puts down, replacing whatever was there.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlacedPanel {
+    pub r: u16,
+    pub th: u16,
+    pub panel: Panel,
+}
+
+/// What the boss does to the arena on its turn, after Mario attacks: first its movements,
+/// then the panels it puts down.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BossTurn {
+    pub moves: Vec<RingMovement>,
+    pub panels: Vec<PlacedPanel>,
+}
+
+/// What the boss does after each of Mario's turns, in order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BossPattern {
+    pub turns: Vec<BossTurn>,
+    /// Whether the pattern starts over once it runs out. Otherwise the boss leaves the
+    /// arena alone after it.
+    pub repeat: bool,
+}
+
+impl BossPattern {
+    /// Checks that every movement and panel the boss makes is on `arena`, and every panel
+    /// it puts down could be there.
+    pub fn check(&self, arena: &BossBoard) -> std::result::Result<(), String> {
+        for turn in &self.turns {
+            turn.moves.iter().try_for_each(check_movement)?;
+            let off_arena = |placed: &&PlacedPanel| placed.r >= NUM_RINGS || placed.th >= NUM_ANGLES;
+            if let Some(placed) = turn.panels.iter().find(off_arena) {
+                return Err(format!("there's no ring {} angle {}", placed.r, placed.th));
+            }
+            turn.panels.iter().try_for_each(|placed| arena.check_panel(placed.panel))?;
+        }
+        Ok(())
+    }
+
+    /// The arena after the boss's turn following Mario's turn `turn`, counting from 0.
+    pub fn after(
+        &self,
+        turn: usize,
+        board: &BossBoard,
+    ) -> std::result::Result<BossBoard, String> {
+        let len = self.turns.len();
+        let index = if self.repeat && len > 0 { turn % len } else { turn };
+        let Some(boss) = self.turns.get(index) else { return Ok(board.clone()) };
+        let mut board = board.clone();
+        for movement in &boss.moves {
+            board.panels = moved_panels(&board.panels, movement)?;
+        }
+        for placed in &boss.panels {
+            board.panels[placed.r as usize][placed.th as usize] = placed.panel;
+        }
+        Ok(board)
+    }
+}
+
+/// A turn-by-turn plan for a fight.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FightPlan {
+    /// The solution for each of Mario's turns. It stops early if there's a turn he can't
+    /// reach the attack panel on.
+    pub turns: Vec<BossSolution>,
+    /// Whether any search ran out of budget.
+    pub truncated: bool,
+}
+
+impl FightPlan {
+    /// How this ranks against other plans as long, best first: the highest total score
+    /// less any cost of the turns, then the least damage, then the fewest movements, then
+    /// the smallest total distance.
+    fn rank(&self) -> (std::cmp::Reverse<i64>, u32, usize, u32) {
+        let value = |turn: &BossSolution| turn.board.weights.value(&turn.route, turn.moves.len());
+        let score = self.turns.iter().map(value).sum();
+        let damage = self.turns.iter().map(|turn| turn.route.damage).sum();
+        let moves = self.turns.iter().map(|turn| turn.moves.len()).sum();
+        let distance = self.turns.iter().map(|turn| total_distance(&turn.moves)).sum();
+        (std::cmp::Reverse(score), damage, moves, distance)
+    }
+}
+
+/// Plans `turns` of Mario's turns against a boss that follows `pattern`, keeping the
+/// arena as each turn leaves it. This carries on the best `PLAN_WIDTH` fights each turn
+/// instead of every one, so the plan isn't always the very best.
+pub fn plan_fight(
+    board: &BossBoard,
+    pattern: &BossPattern,
+    turns: usize,
+    options: &SolveOptions,
+) -> std::result::Result<FightPlan, String> {
+    board.check()?;
+    pattern.check(board)?;
+    let mut plans = vec![FightPlan { turns: Vec::new(), truncated: false }];
+    for turn in 0..turns {
+        let mut next = Vec::new();
+        for plan in &plans {
+            let board = match plan.turns.last() {
+                Some(last) => pattern.after(turn - 1, &last.board)?,
+                None => board.clone(),
+            };
+            let (solutions, truncated) = solutions(&board, options, PLAN_WIDTH)?;
+            next.extend(solutions.into_iter().map(|solution| {
+                let mut turns = plan.turns.clone();
+                turns.push(solution);
+                FightPlan { turns, truncated: plan.truncated || truncated }
+            }));
+        }
+        if next.is_empty() {
+            break;
+        }
+        next.sort_by_key(FightPlan::rank);
+        next.truncate(PLAN_WIDTH);
+        plans = next;
+    }
+    Ok(plans.swap_remove(0))
+}
+
+/// Solve a boss arena given as `{panels}`, returning the movements and Mario's route to
+/// the attack panel, or `null` if there's none within the turn limit.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_boss(board: JsValue, options: JsValue) -> Result<JsValue> {
+    let board: BossBoard = serde_wasm_bindgen::from_value(board)?;
+    let options = options_from_js(options)?;
+    Ok(match find_solution(&board, &options)? {
+        Some(solution) => serde_wasm_bindgen::to_value(&solution)?,
+        None => JsValue::null(),
+    })
+}
+
+/// Plan `turns` turns of a boss fight in the arena `board`, given what the boss does after
+/// each attack as `{turns: [{moves, panels}], repeat}`.
+#[wasm_bindgen(skip_typescript)]
+pub fn plan_boss_fight(
+    board: JsValue,
+    pattern: JsValue,
+    turns: usize,
+    options: JsValue,
+) -> Result<JsValue> {
+    let board: BossBoard = serde_wasm_bindgen::from_value(board)?;
+    let pattern: BossPattern = serde_wasm_bindgen::from_value(pattern)?;
+    let options = options_from_js(options)?;
+    Ok(serde_wasm_bindgen::to_value(&plan_fight(&board, &pattern, turns, &options)?)?)
+}
@@ -0,0 +1,70 @@
+//! Aborting a solve that's no longer wanted, e.g. one started for a board the user has
+//! since edited, so it stops using the CPU promptly.
+//!
+//! A running search checks its token every `CLOCK_INTERVAL` boards. A solve running on a
+//! Web Worker keeps that worker busy until it returns, so the page can't call `cancel` on
+//! the worker's token in the meantime. Instead, share an `Int32Array` over a
+//! `SharedArrayBuffer` with the worker, wrap it with `CancelToken.fromShared`, and set
+//! its first element to anything but 0 from the page to cancel.
+
+use crate::{find_solution_with, input_from_js, solve_to_js, Result, Search, MAX_TURNS};
+use std::cell::Cell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// Cancels the solves it's passed to. Clones share the same state.
+#[wasm_bindgen]
+#[derive(Clone, Default)]
+pub struct CancelToken {
+    /// Set by `cancel`.
+    cancelled: Rc<Cell<bool>>,
+    /// A flag in shared memory that's set to cancel, if any.
+    shared: Option<js_sys::Int32Array>,
+}
+
+impl CancelToken {
+    /// Whether the solves using this token should stop.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
+            || self
+                .shared
+                .as_ref()
+                .is_some_and(|flag| js_sys::Atomics::load(flag, 0).is_ok_and(|value| value != 0))
+    }
+}
+
+#[wasm_bindgen]
+impl CancelToken {
+    /// Create a token that isn't cancelled yet.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Create a token that's cancelled once `flag[0]` is set to anything but 0, e.g. by
+    /// another thread through a `SharedArrayBuffer`.
+    #[wasm_bindgen(js_name = fromShared)]
+    pub fn from_shared(flag: js_sys::Int32Array) -> CancelToken {
+        CancelToken { shared: Some(flag), ..CancelToken::default() }
+    }
+
+    /// Stop every solve using this token at its next check.
+    pub fn cancel(&self) {
+        self.cancelled.set(true);
+    }
+
+    /// Whether the token has been cancelled.
+    #[wasm_bindgen(getter)]
+    pub fn cancelled(&self) -> bool {
+        self.is_cancelled()
+    }
+}
+
+/// Like `solve`, but stops early once `token` is cancelled. A cancelled solve returns
+/// the closest board found so far with `truncated` set, like one out of budget.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_cancellable(ring: JsValue, options: JsValue, token: &CancelToken) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let search = Search::new(&options).with_cancel(token.clone());
+    solve_to_js(find_solution_with(search, ring, MAX_TURNS, &options), &options)
+}
@@ -0,0 +1,153 @@
+//! An interactive coach that walks a player through a solution, re-planning whenever
+//! they make a different move than the one suggested.
+
+use crate::evaluator::Evaluator;
+use crate::{
+    apply_movement, check_movement, evaluate, goal, options_from_js, validation, Result, Ring,
+    RingMovement, Search, SolveOptions,
+};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// What happened after the player made a move.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoachUpdate {
+    /// Whether the move had the same effect as the suggested one.
+    pub followed: bool,
+    /// Whether a new plan had to be found.
+    pub replanned: bool,
+    /// How many more turns the player now needs in total than the plan before this move.
+    /// None if the board can no longer be solved in the turns left.
+    pub extra_turns: Option<u16>,
+    /// The next move to make, or None if the board is solved or can't be solved.
+    pub next: Option<RingMovement>,
+    /// Whether the board now reaches the goal of the options.
+    pub solved: bool,
+}
+
+/// Holds the optimal plan for a board and updates it as the player makes moves.
+#[wasm_bindgen]
+pub struct Coach {
+    board: Ring,
+    options: SolveOptions,
+    /// The goal of the options, or None if it's the usual perfect clear.
+    goal: Option<Rc<dyn Evaluator>>,
+    /// Every move the player has made, for goals that follow more than the board.
+    made: Vec<RingMovement>,
+    turns_left: u16,
+    /// The remaining moves of the current plan, or None if no plan solves the board.
+    plan: Option<VecDeque<RingMovement>>,
+}
+
+impl Coach {
+    /// Starts coaching `board` with every turn the rules of `options` give.
+    pub fn start(board: Ring, options: SolveOptions) -> std::result::Result<Self, String> {
+        let rules = options.battle_rules()?;
+        let goal = goal(&options, rules);
+        let turns_left = rules.max_turns;
+        let mut coach = Coach { board, options, goal, made: Vec::new(), turns_left, plan: None };
+        coach.replan();
+        Ok(coach)
+    }
+
+    /// The current board, after every move the player has made.
+    pub fn board(&self) -> Ring {
+        self.board
+    }
+
+    /// The move the player should make next, if any.
+    pub fn next_move(&self) -> Option<RingMovement> {
+        self.plan.as_ref().and_then(|plan| plan.front().copied())
+    }
+
+    /// Finds a new optimal plan for the current board with the turns left.
+    fn replan(&mut self) {
+        self.plan = Search::new(&self.options)
+            .find_solution(self.board, self.turns_left)
+            .filter(|solution| solution.perfect)
+            .map(|solution| solution.moves);
+    }
+
+    /// Records the move the player actually made, re-planning if it deviated.
+    pub fn play(&mut self, movement: &RingMovement) -> std::result::Result<CoachUpdate, String> {
+        check_movement(movement)?;
+        if self.turns_left == 0 {
+            return Err("no turns left".to_string());
+        }
+        let planned_turns = self.plan.as_ref().map(VecDeque::len);
+        let expected = self.next_move().map(|planned| apply_movement(self.board, &planned));
+        self.board = apply_movement(self.board, movement);
+        self.made.push(*movement);
+        self.turns_left -= 1;
+
+        let followed = expected == Some(self.board);
+        if followed {
+            if let Some(plan) = self.plan.as_mut() {
+                plan.pop_front();
+            }
+        } else {
+            self.replan();
+        }
+        let extra_turns = match (planned_turns, &self.plan) {
+            (Some(planned), Some(plan)) => Some((1 + plan.len()).saturating_sub(planned) as u16),
+            // The player found a way where the coach couldn't.
+            (None, Some(_)) => Some(0),
+            (_, None) => None,
+        };
+        Ok(CoachUpdate {
+            followed,
+            replanned: !followed,
+            extra_turns,
+            next: self.next_move(),
+            solved: self.solved(),
+        })
+    }
+
+    /// Whether the board reaches the goal of the options, as the search checks it.
+    fn solved(&self) -> bool {
+        let excess = match &self.goal {
+            Some(goal) => goal.distance_after(self.board, &self.made),
+            None => evaluate(self.board).excess_actions(),
+        };
+        excess == 0
+    }
+}
+
+#[wasm_bindgen]
+impl Coach {
+    /// Start coaching a board, finding the optimal plan.
+    #[wasm_bindgen(constructor)]
+    pub fn new(ring: JsValue, options: JsValue) -> Result<Coach> {
+        let ring = validation::ring_from_js(ring)?;
+        Ok(Coach::start(ring, options_from_js(options)?)?)
+    }
+
+    /// The next instruction for the player, or null if there is none.
+    #[wasm_bindgen(js_name = nextMove, skip_typescript)]
+    pub fn next_move_js(&self) -> Result<JsValue> {
+        Ok(serde_wasm_bindgen::to_value(&self.next_move())?)
+    }
+
+    /// Record the move the player made, returning a `CoachUpdate`.
+    #[wasm_bindgen(js_name = play, skip_typescript)]
+    pub fn play_js(&mut self, movement: JsValue) -> Result<JsValue> {
+        let movement: RingMovement = serde_wasm_bindgen::from_value(movement)?;
+        let update = self.play(&movement)?;
+        Ok(serde_wasm_bindgen::to_value(&update)?)
+    }
+
+    /// The current board.
+    #[wasm_bindgen(js_name = board, skip_typescript)]
+    pub fn board_js(&self) -> Result<JsValue> {
+        Ok(serde_wasm_bindgen::to_value(&self.board)?)
+    }
+
+    /// How many turns the player has left.
+    #[wasm_bindgen(getter, js_name = turnsLeft)]
+    pub fn turns_left(&self) -> u16 {
+        self.turns_left
+    }
+}
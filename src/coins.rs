@@ -0,0 +1,108 @@
+//! The coins the player can pay the Toads in the stands during a battle, for more time to
+//! line up the enemies or for their help attacking. When a board can't be cleared under
+//! the rules, this finds the cheapest help that makes it winnable.
+//!
+//! ```json
+//! {"turn": 50, "action": 100, "maxActions": 2}
+//! ```
+//!
+//! More time counts as an extra turn, up to the most turns the solver searches.
+
+use crate::accessories::Accessory;
+use crate::preset::{Preset, Rules};
+use crate::{
+    find_solution, input_from_js, with_spoilers, Result, Ring, Solution, SolveOptions, MAX_TURNS,
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// What the Toads charge for their help, or None for help they won't give.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CoinCosts {
+    /// The coins for enough time to make another turn.
+    pub turn: Option<u32>,
+    /// The coins for the Toads to make another action's worth of attacks.
+    pub action: Option<u32>,
+    /// The most extra actions the Toads will make in a battle.
+    pub max_actions: u32,
+}
+
+/// The help to pay for, and the solution it makes possible.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoinPlan {
+    pub coins: u32,
+    pub extra_turns: u16,
+    pub extra_actions: u32,
+    pub solution: Solution,
+}
+
+/// Finds the cheapest help that lets `ring` be cleared, with the solution it allows, or
+/// None if no help for sale is enough. Free help is tried first, so a board that can
+/// already be cleared costs nothing. Fails with `spoiler_free`, since the plan has the moves.
+pub fn cheapest_plan(
+    ring: Ring,
+    costs: &CoinCosts,
+    options: &SolveOptions,
+) -> std::result::Result<Option<CoinPlan>, String> {
+    with_spoilers(options)?;
+    let rules = options.battle_rules()?;
+    let max_turns = if costs.turn.is_some() { MAX_TURNS - rules.max_turns } else { 0 };
+    let max_actions = if costs.action.is_some() { costs.max_actions } else { 0 };
+    let mut choices: Vec<(u32, u16, u32)> = (0..=max_turns)
+        .flat_map(|turns| (0..=max_actions).map(move |actions| (turns, actions)))
+        .map(|(turns, actions)| {
+            let coins = turns as u32 * costs.turn.unwrap_or(0)
+                + actions * costs.action.unwrap_or(0);
+            (coins, turns, actions)
+        })
+        .collect();
+    // Ties go to the least help, so the plan is no harder to play than it needs to be.
+    choices.sort_by_key(|&(coins, turns, actions)| (coins, turns as u32 + actions));
+    // `rules` already has the accessories' extra turns, so they mustn't be added again.
+    let accessories: Vec<Accessory> = options
+        .accessories
+        .iter()
+        .filter(|accessory| !matches!(accessory, Accessory::TurnPlus { .. }))
+        .copied()
+        .collect();
+    for (coins, extra_turns, extra_actions) in choices {
+        let helped = SolveOptions {
+            preset: Preset::Custom,
+            rules: Some(Rules {
+                max_turns: rules.max_turns + extra_turns,
+                actions: match extra_actions {
+                    0 => rules.actions,
+                    extra => Some(rules.actions_for(ring) + extra),
+                },
+                ..rules
+            }),
+            actions: None,
+            accessories: accessories.clone(),
+            ..options.clone()
+        };
+        if let Some(solution) = find_solution(ring, MAX_TURNS, &helped) {
+            if solution.perfect {
+                return Ok(Some(CoinPlan { coins, extra_turns, extra_actions, solution }));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Find the fewest coins to pay the Toads so `ring` can be cleared, given what they
+/// charge as `{turn, action, maxActions}`, and the solution it allows. Returns `null` if
+/// no help is enough.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_with_coins(ring: JsValue, costs: JsValue, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let costs: CoinCosts = serde_wasm_bindgen::from_value(costs)?;
+    Ok(match cheapest_plan(ring, &costs, &options)? {
+        Some(plan) => {
+            let solution = options.orientation.solution_out(plan.solution);
+            serde_wasm_bindgen::to_value(&CoinPlan { solution, ..plan })?
+        }
+        None => JsValue::null(),
+    })
+}
@@ -0,0 +1,56 @@
+//! The puzzle timer: how long a player takes to make a solution's movements, and whether
+//! that fits in the seconds left on the clock.
+//!
+//! ```json
+//! {"timer": {"seconds": 20, "perMove": 1.5, "perNotch": 0.4}}
+//! ```
+//!
+//! When the best solution takes too long, the other solutions with as many turns are
+//! tried, and the best one that fits in time is chosen instead. If none does, the
+//! solution is kept and flagged.
+
+use crate::RingMovement;
+use serde::{Deserialize, Serialize};
+
+/// How many solutions with as many turns to try when the best doesn't fit in time.
+pub const CANDIDATES: usize = 16;
+
+/// The time left, and how long a player takes to make movements.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Timer {
+    /// The seconds left on the clock.
+    pub seconds: f64,
+    /// The seconds to pick up a ring or row and let it go.
+    pub per_move: f64,
+    /// The seconds to drag it each notch.
+    pub per_notch: f64,
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Timer { seconds: 60.0, per_move: 1.5, per_notch: 0.4 }
+    }
+}
+
+impl Timer {
+    /// How long making `moves` takes.
+    pub fn seconds_for<'m>(&self, moves: impl IntoIterator<Item = &'m RingMovement>) -> f64 {
+        moves.into_iter().map(|movement| self.per_move + self.per_notch * movement.distance() as f64).sum()
+    }
+
+    /// How long making `moves` takes, and whether it's done before time runs out.
+    pub fn timing<'m>(&self, moves: impl IntoIterator<Item = &'m RingMovement>) -> Timing {
+        let seconds = self.seconds_for(moves);
+        Timing { seconds, in_time: seconds <= self.seconds }
+    }
+}
+
+/// How long a solution takes to make.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Timing {
+    pub seconds: f64,
+    /// Whether it's done before time runs out.
+    pub in_time: bool,
+}
@@ -0,0 +1,120 @@
+//! Difficulty ratings for boards, and histograms over sets of them for curating packs.
+
+use crate::generator::BoardSource;
+use crate::{Result, Ring, Search, SolveOptions, MAX_TURNS};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// How hard a board is for a player to solve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Difficulty {
+    /// Already solved as given.
+    Solved,
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    /// Can't be solved within `MAX_TURNS`.
+    Unsolvable,
+}
+
+impl Difficulty {
+    const ALL: [Difficulty; 6] = [
+        Difficulty::Solved,
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+        Difficulty::Unsolvable,
+    ];
+}
+
+/// A board's difficulty and the turns it needs.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rating {
+    pub difficulty: Difficulty,
+    pub min_turns: Option<u16>,
+}
+
+/// Rates a board by the turns it needs, one step harder if there's only one way to do it.
+pub fn rate(ring: Ring) -> Rating {
+    let search = || Search::new(&SolveOptions::default());
+    let min_turns = search()
+        .find_solutions(ring, MAX_TURNS, 1)
+        .first()
+        .map(|solution| solution.moves.len() as u16);
+    // Only solutions with the fewest turns count, since any solution can be made longer by
+    // splitting one of its movements in two.
+    let unique = min_turns.is_some_and(|turns| {
+        let solutions = search().find_solutions(ring, turns, 2);
+        solutions.iter().filter(|solution| solution.moves.len() == turns as usize).count() == 1
+    });
+    let difficulty = match min_turns {
+        None => Difficulty::Unsolvable,
+        Some(0) => Difficulty::Solved,
+        Some(1) if unique => Difficulty::Medium,
+        Some(1) => Difficulty::Easy,
+        Some(2) if unique => Difficulty::Hard,
+        Some(2) => Difficulty::Medium,
+        Some(3) if unique => Difficulty::Expert,
+        Some(3) => Difficulty::Hard,
+        Some(_) => Difficulty::Expert,
+    };
+    Rating { difficulty, min_turns }
+}
+
+/// The number of boards with a given difficulty.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DifficultyBucket {
+    pub difficulty: Difficulty,
+    pub count: usize,
+}
+
+/// The number of boards needing a given number of turns, or None for unsolvable boards.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnsBucket {
+    pub turns: Option<u16>,
+    pub count: usize,
+}
+
+/// How a set of boards is spread across difficulties and turn counts.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DifficultyHistogram {
+    pub boards: usize,
+    pub by_difficulty: Vec<DifficultyBucket>,
+    pub by_turns: Vec<TurnsBucket>,
+}
+
+/// Rates every board and counts them by difficulty and by turns needed.
+pub fn histogram(boards: &[Ring]) -> DifficultyHistogram {
+    let ratings: Vec<Rating> = boards.iter().map(|&board| rate(board)).collect();
+    let by_difficulty = Difficulty::ALL
+        .iter()
+        .map(|&difficulty| DifficultyBucket {
+            difficulty,
+            count: ratings.iter().filter(|rating| rating.difficulty == difficulty).count(),
+        })
+        .collect();
+    let by_turns = (0..=MAX_TURNS)
+        .map(Some)
+        .chain(std::iter::once(None))
+        .map(|turns| TurnsBucket {
+            turns,
+            count: ratings.iter().filter(|rating| rating.min_turns == turns).count(),
+        })
+        .collect();
+    DifficultyHistogram { boards: boards.len(), by_difficulty, by_turns }
+}
+
+/// Count boards by rated difficulty and by minimal turns, so pack curators can check the
+/// balance of a set at a glance. Accepts either an array of boards or a `GeneratorSpec`.
+#[wasm_bindgen(skip_typescript)]
+pub fn difficulty_histogram(input: JsValue) -> Result<JsValue> {
+    let source: BoardSource = serde_wasm_bindgen::from_value(input)?;
+    Ok(serde_wasm_bindgen::to_value(&histogram(&source.into_boards()?))?)
+}
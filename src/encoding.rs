@@ -0,0 +1,196 @@
+//! A compact binary encoding of solutions, for QR codes, URLs, and save files.
+//!
+//! An encoded solution is:
+//! - a header byte: bits 0-3 are the number of moves, bit 4 is set if the solution was
+//!   truncated, and bits 5-7 are the encoding version;
+//! - one byte per move, see `encode_movement`;
+//! - the resulting board, 12 bits per ring from inner to outer, packed little-endian
+//!   into 6 bytes.
+//!
+//! A solution with `n` moves always takes `n + 7` bytes. The result board is stored so a
+//! solution can be shown without the original board; everything else about it is
+//! recomputed when decoding, except which constraints were binding.
+
+use crate::{check_movement, check_ring, evaluate, Result, Ring, RingMovement, Solution, NUM_ANGLES, NUM_RINGS};
+use serde::Deserialize;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// The encoding version written in the header.
+pub const ENCODING_VERSION: u8 = 1;
+
+/// The most moves a header can count.
+const MAX_MOVES: usize = 0xF;
+const TRUNCATED: u8 = 1 << 4;
+const VERSION_SHIFT: u8 = 5;
+/// The number of bytes taken by the packed result board.
+pub(crate) const BOARD_BYTES: usize = (NUM_RINGS * NUM_ANGLES / 8) as usize;
+
+/// Why a solution couldn't be encoded or decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EncodingError {
+    /// The solution has more moves than fit in the header.
+    TooManyMoves(usize),
+    /// The data was written by a different version of the encoding.
+    UnsupportedVersion(u8),
+    /// The data is shorter or longer than its header says.
+    WrongLength { expected: usize, actual: usize },
+    /// A move or the board refers to something that doesn't exist.
+    Invalid(String),
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EncodingError::TooManyMoves(n) => {
+                write!(f, "{} moves is more than the {} that can be encoded", n, MAX_MOVES)
+            }
+            EncodingError::UnsupportedVersion(v) => write!(
+                f,
+                "solution encoding version {} isn't the supported version {}",
+                v, ENCODING_VERSION
+            ),
+            EncodingError::WrongLength { expected, actual } => {
+                write!(f, "encoded solution is {} bytes, expected {}", actual, expected)
+            }
+            EncodingError::Invalid(reason) => write!(f, "invalid encoded solution: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for EncodingError {}
+
+/// Encodes a movement as a single byte.
+///
+/// Bit 7 is set for a row shift. For a ring rotation, bits 5-6 are the ring, bit 4 is set
+/// if clockwise, and bits 0-3 are the amount. For a row shift, bits 4-6 are the row's
+/// angle below 6, bit 3 is set if outward, and bits 0-2 are the amount.
+/// Amounts are reduced to less than a full turn, and rows at 6 or above are flipped to
+/// their opposite angle, so the movement has the same effect but may be written differently.
+pub fn encode_movement(movement: &RingMovement) -> u8 {
+    match *movement {
+        RingMovement::Ring { r, amount, clockwise } => {
+            let amount = amount.rem_euclid(NUM_ANGLES as i16) as u8;
+            ((r as u8) << 5) | ((clockwise as u8) << 4) | amount
+        }
+        RingMovement::Row { th, amount, outward } => {
+            let (th, outward) = if th >= NUM_ANGLES / 2 {
+                (th - NUM_ANGLES / 2, !outward)
+            } else {
+                (th, outward)
+            };
+            let amount = amount.rem_euclid((NUM_RINGS * 2) as i16) as u8;
+            0x80 | ((th as u8) << 4) | ((outward as u8) << 3) | amount
+        }
+    }
+}
+
+/// Decodes a movement written by `encode_movement`.
+pub fn decode_movement(byte: u8) -> std::result::Result<RingMovement, EncodingError> {
+    if byte & 0x80 == 0 {
+        let amount = (byte & 0xF) as i16;
+        if amount >= NUM_ANGLES as i16 {
+            return Err(EncodingError::Invalid(format!("rotation amount {} too large", amount)));
+        }
+        Ok(RingMovement::Ring { r: (byte >> 5 & 0b11) as u16, amount, clockwise: byte & 0x10 != 0 })
+    } else {
+        let th = (byte >> 4 & 0b111) as u16;
+        if th >= NUM_ANGLES / 2 {
+            return Err(EncodingError::Invalid(format!("row angle {} out of range", th)));
+        }
+        Ok(RingMovement::Row { th, amount: (byte & 0b111) as i16, outward: byte & 0x08 != 0 })
+    }
+}
+
+/// Packs a board into 6 bytes, 12 bits per ring.
+pub(crate) fn encode_board(ring: &Ring) -> [u8; BOARD_BYTES] {
+    let bits = ring
+        .iter()
+        .enumerate()
+        .fold(0u64, |bits, (r, &subring)| bits | (subring as u64) << (r as u16 * NUM_ANGLES));
+    let mut bytes = [0; BOARD_BYTES];
+    bytes.copy_from_slice(&bits.to_le_bytes()[..BOARD_BYTES]);
+    bytes
+}
+
+/// Unpacks a board written by `encode_board`.
+pub(crate) fn decode_board(bytes: &[u8]) -> Ring {
+    let mut buf = [0; 8];
+    buf[..BOARD_BYTES].copy_from_slice(bytes);
+    let bits = u64::from_le_bytes(buf);
+    let mut ring: Ring = [0; NUM_RINGS as usize];
+    for (r, subring) in ring.iter_mut().enumerate() {
+        *subring = (bits >> (r as u16 * NUM_ANGLES)) as u16 & ((1 << NUM_ANGLES) - 1);
+    }
+    ring
+}
+
+/// Encodes the moves and result of a solution.
+pub fn encode(
+    moves: &[RingMovement],
+    result: &Ring,
+    truncated: bool,
+) -> std::result::Result<Vec<u8>, EncodingError> {
+    if moves.len() > MAX_MOVES {
+        return Err(EncodingError::TooManyMoves(moves.len()));
+    }
+    for movement in moves {
+        check_movement(movement).map_err(EncodingError::Invalid)?;
+    }
+    check_ring(result).map_err(EncodingError::Invalid)?;
+    let header = (ENCODING_VERSION << VERSION_SHIFT) | if truncated { TRUNCATED } else { 0 };
+    let mut bytes = Vec::with_capacity(1 + moves.len() + BOARD_BYTES);
+    bytes.push(header | moves.len() as u8);
+    bytes.extend(moves.iter().map(encode_movement));
+    bytes.extend_from_slice(&encode_board(result));
+    Ok(bytes)
+}
+
+/// Encodes a solution.
+pub fn solution_to_bytes(solution: &Solution) -> std::result::Result<Vec<u8>, EncodingError> {
+    let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+    encode(&moves, &solution.result, solution.truncated)
+}
+
+/// Decodes a solution, re-evaluating its result board.
+pub fn solution_from_bytes(bytes: &[u8]) -> std::result::Result<Solution, EncodingError> {
+    let header = *bytes.first().ok_or(EncodingError::WrongLength { expected: 1, actual: 0 })?;
+    let version = header >> VERSION_SHIFT;
+    if version != ENCODING_VERSION {
+        return Err(EncodingError::UnsupportedVersion(version));
+    }
+    let count = (header & MAX_MOVES as u8) as usize;
+    let expected = 1 + count + BOARD_BYTES;
+    if bytes.len() != expected {
+        return Err(EncodingError::WrongLength { expected, actual: bytes.len() });
+    }
+    let moves = bytes[1..=count]
+        .iter()
+        .map(|&byte| decode_movement(byte))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let result = decode_board(&bytes[1 + count..]);
+    Ok(Solution::new(moves, evaluate(result), header & TRUNCATED != 0))
+}
+
+/// The parts of a solution from JavaScript that get encoded.
+#[derive(Deserialize)]
+struct EncodedParts {
+    moves: Vec<RingMovement>,
+    result: Ring,
+    #[serde(default)]
+    truncated: bool,
+}
+
+/// Encode a solution to a few bytes per move.
+#[wasm_bindgen(skip_typescript)]
+pub fn encode_solution(solution: JsValue) -> Result<Vec<u8>> {
+    let parts: EncodedParts = serde_wasm_bindgen::from_value(solution)?;
+    Ok(encode(&parts.moves, &parts.result, parts.truncated).map_err(|e| e.to_string())?)
+}
+
+/// Decode a solution written by `encode_solution`.
+#[wasm_bindgen(skip_typescript)]
+pub fn decode_solution(bytes: &[u8]) -> Result<JsValue> {
+    let solution = solution_from_bytes(bytes).map_err(|e| e.to_string())?;
+    Ok(serde_wasm_bindgen::to_value(&solution)?)
+}
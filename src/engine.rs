@@ -0,0 +1,265 @@
+//! A long-lived solver for embedders, holding its options, a cache of solved boards,
+//! and scratch memory between solves.
+//!
+//! The free `solve` functions are conveniences that build a fresh solver each call.
+//! Applications that solve many boards can instead keep a `Solver` around, run several
+//! differently configured ones side by side, and decide exactly when their memory is
+//! released with `reset()` and `free()`.
+
+use crate::evaluator::Evaluator;
+use crate::{
+    apply_movement, evaluate, finish_solution, goal, iterate_movements, oriented_options_from_js,
+    solve_to_js, validation, with_spoilers, without_yielding, Result, Ring, RingMovement, Search,
+    Solution, SolveOptions, MAX_TURNS,
+};
+use std::collections::HashMap;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// How many solved boards to remember before the cache is cleared.
+const CACHE_CAPACITY: usize = 256;
+
+/// A configured solver that reuses its memory across solves.
+#[wasm_bindgen]
+pub struct Solver {
+    options: SolveOptions,
+    /// Finished searches, by board. Truncated results aren't cached, since a retry could
+    /// do better, and neither are reused or patched solutions, which may not be the best.
+    cache: HashMap<Ring, Option<Solution>>,
+    /// The path buffer lent to each search.
+    scratch: Vec<RingMovement>,
+    /// The last board solved with a perfect solution, for incremental solves, whether it
+    /// was searched, reused, or patched.
+    last: Option<(Ring, Solution)>,
+    /// The goal of `options`, or None for the usual perfect clear, and its turn limit, for
+    /// checking reused and patched solutions the way a search would.
+    goal: Option<Rc<dyn Evaluator>>,
+    max_turns: u16,
+}
+
+/// How `Solver::find_solution_incremental` found its solution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reuse {
+    /// The board was the same as the last one solved.
+    Cached,
+    /// The board had one cell toggled, and the last solution still solves it.
+    Reused,
+    /// The board had one cell toggled, and changing or adding one movement of the last
+    /// solution solves it.
+    Patched,
+    /// A full search was needed.
+    Searched,
+}
+
+/// Whether two boards differ in exactly one cell.
+fn one_cell_toggled(a: &Ring, b: &Ring) -> bool {
+    a.iter().zip(b).map(|(a, b)| (a ^ b).count_ones()).sum::<u32>() == 1
+}
+
+impl Solver {
+    /// Creates a solver that always solves with `options`.
+    pub fn with_options(options: SolveOptions) -> Self {
+        // Options from JavaScript are checked as they're read.
+        let rules = options.battle_rules().unwrap_or_default();
+        Solver {
+            goal: goal(&options, rules),
+            max_turns: rules.max_turns,
+            options,
+            cache: HashMap::new(),
+            scratch: Vec::new(),
+            last: None,
+        }
+    }
+
+    /// The options every solve uses.
+    pub fn options(&self) -> &SolveOptions {
+        &self.options
+    }
+
+    /// Reads a board from JavaScript, turned to the solver's orientation.
+    fn ring_from_js(&self, ring: JsValue) -> Result<Ring> {
+        Ok(self.options.orientation.board_in(validation::ring_from_js(ring)?))
+    }
+
+    /// Finds the best solution for `ring`, reusing an earlier result if there is one.
+    pub fn find_solution(&mut self, ring: Ring) -> Option<Solution> {
+        if let Some(solution) = self.cache.get(&ring) {
+            return solution.clone();
+        }
+        let mut search = Search::new(&self.options).with_path(std::mem::take(&mut self.scratch));
+        let solution = search.find_solution(ring, MAX_TURNS).map(|solution| {
+            let finished = finish_solution(&mut search, solution, ring, MAX_TURNS, &self.options);
+            without_yielding(finished)
+        });
+        self.scratch = search.into_path();
+        self.remember(ring, &solution);
+        solution
+    }
+
+    /// Caches a finished search of `ring`, and keeps a perfect solution for incremental
+    /// solves.
+    fn remember(&mut self, ring: Ring, solution: &Option<Solution>) {
+        if !solution.as_ref().is_some_and(|solution| solution.truncated) {
+            if self.cache.len() >= CACHE_CAPACITY {
+                self.cache.clear();
+            }
+            self.cache.insert(ring, solution.clone());
+        }
+        if let Some(solution) = solution.as_ref().filter(|solution| solution.perfect) {
+            self.last = Some((ring, solution.clone()));
+        }
+    }
+
+    /// Whether `result`, reached by `moves`, reaches the goal of the options, as the
+    /// search checks it.
+    fn reaches_goal(&self, result: Ring, moves: &[RingMovement]) -> bool {
+        let excess = match &self.goal {
+            Some(goal) => goal.distance_after(result, moves),
+            None => evaluate(result).excess_actions(),
+        };
+        excess == 0
+    }
+
+    /// The solution that makes `moves` on `ring`, with everything `find_solution` reports
+    /// about it, once they're known to reach the goal.
+    fn finish(&self, ring: Ring, moves: Vec<RingMovement>) -> Solution {
+        let result = moves.iter().fold(ring, apply_movement);
+        let mut solution = Solution::new(moves, evaluate(result), false);
+        solution.perfect = true;
+        let mut search = Search::new(&self.options);
+        without_yielding(finish_solution(&mut search, solution, ring, MAX_TURNS, &self.options))
+    }
+
+    /// Like `find_solution`, but when `ring` is the last solved board with one cell
+    /// toggled, as happens while correcting a board entered by hand, first tries to reuse
+    /// or patch the last solution before searching.
+    ///
+    /// A reused or patched solution always solves the board within the turn limit, but
+    /// may not be the one with the fewest turns, so it's only kept for further incremental
+    /// solves: `find_solution` still searches the board.
+    pub fn find_solution_incremental(&mut self, ring: Ring) -> (Option<Solution>, Reuse) {
+        if let Some(solution) = self.cache.get(&ring) {
+            return (solution.clone(), Reuse::Cached);
+        }
+        if let Some((_, solution)) = self.last.as_ref().filter(|(board, _)| *board == ring) {
+            return (Some(solution.clone()), Reuse::Cached);
+        }
+        let last = self.last.as_ref().filter(|(board, _)| one_cell_toggled(board, &ring));
+        if let Some((_, solution)) = last {
+            let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+            let result = moves.iter().fold(ring, apply_movement);
+            let reused = if self.reaches_goal(result, &moves) {
+                Some((moves, Reuse::Reused))
+            } else {
+                self.patch(ring, &moves).map(|patched| (patched, Reuse::Patched))
+            };
+            if let Some((moves, reuse)) = reused {
+                let solution = self.finish(ring, moves);
+                // Further corrections can build on this one.
+                self.last = Some((ring, solution.clone()));
+                return (Some(solution), reuse);
+            }
+        }
+        (self.find_solution(ring), Reuse::Searched)
+    }
+
+    /// Tries replacing each movement of `moves` with any other allowed movement, then
+    /// adding one more movement at the end if there are turns left, returning the first
+    /// movements found that reach the goal.
+    fn patch(&self, ring: Ring, moves: &[RingMovement]) -> Option<Vec<RingMovement>> {
+        let constraints = self.options.constraints;
+        let mut before = ring;
+        for (i, original) in moves.iter().enumerate() {
+            // A forced first movement can't be changed.
+            let fixed = i == 0 && self.options.first_move.is_some();
+            let rest = &moves[i + 1..];
+            let found = iterate_movements(before, &constraints, |movement, moved| {
+                if fixed || movement == *original {
+                    return None;
+                }
+                let mut patched = moves.to_vec();
+                patched[i] = movement;
+                let result = rest.iter().fold(moved, apply_movement);
+                self.reaches_goal(result, &patched).then_some(patched)
+            });
+            if found.is_some() {
+                return found;
+            }
+            before = apply_movement(before, original);
+        }
+        if moves.len() >= self.max_turns as usize {
+            return None;
+        }
+        iterate_movements(before, &constraints, |movement, moved| {
+            let mut patched = moves.to_vec();
+            patched.push(movement);
+            self.reaches_goal(moved, &patched).then_some(patched)
+        })
+    }
+
+    /// Finds up to `count` distinct solutions for `ring`. These aren't cached.
+    pub fn find_solutions(&mut self, ring: Ring, count: usize) -> Vec<Solution> {
+        let mut search = Search::new(&self.options).with_path(std::mem::take(&mut self.scratch));
+        let solutions = search.find_solutions(ring, MAX_TURNS, count);
+        self.scratch = search.into_path();
+        solutions
+    }
+
+    /// Forgets every cached solve and releases the scratch memory.
+    pub fn clear(&mut self) {
+        self.cache = HashMap::new();
+        self.scratch = Vec::new();
+        self.last = None;
+    }
+}
+
+#[wasm_bindgen]
+impl Solver {
+    /// Create a solver that uses `options` for every solve, with every board and solution
+    /// in their orientation.
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: JsValue) -> Result<Solver> {
+        Ok(Solver::with_options(oriented_options_from_js(options)?))
+    }
+
+    /// Solve a board, like the free `solve` function.
+    #[wasm_bindgen(js_name = solve, skip_typescript)]
+    pub fn solve_js(&mut self, ring: JsValue) -> Result<JsValue> {
+        let ring = self.ring_from_js(ring)?;
+        solve_to_js(self.find_solution(ring), &self.options)
+    }
+
+    /// Solve a board that may be the last one solved with a single cell toggled, reusing
+    /// or patching the last solution if possible. See `find_solution_incremental`.
+    #[wasm_bindgen(js_name = solveIncremental, skip_typescript)]
+    pub fn solve_incremental_js(&mut self, ring: JsValue) -> Result<JsValue> {
+        let ring = self.ring_from_js(ring)?;
+        solve_to_js(self.find_solution_incremental(ring).0, &self.options)
+    }
+
+    /// Find up to `count` distinct solutions, like the free `solve_top_k` function.
+    #[wasm_bindgen(js_name = solveTopK, skip_typescript)]
+    pub fn solve_top_k_js(&mut self, ring: JsValue, count: usize) -> Result<JsValue> {
+        with_spoilers(&self.options)?;
+        let ring = self.ring_from_js(ring)?;
+        let orientation = self.options.orientation;
+        let solutions: Vec<Solution> = self
+            .find_solutions(ring, count)
+            .into_iter()
+            .map(|solution| orientation.solution_out(solution))
+            .collect();
+        Ok(serde_wasm_bindgen::to_value(&solutions)?)
+    }
+
+    /// Drop cached solves and scratch memory, keeping the options.
+    /// Call `free()` instead once the solver is no longer needed at all.
+    pub fn reset(&mut self) {
+        self.clear();
+    }
+
+    /// How many solved boards are cached.
+    #[wasm_bindgen(getter, js_name = cachedBoards)]
+    pub fn cached_boards(&self) -> usize {
+        self.cache.len()
+    }
+}
@@ -0,0 +1,169 @@
+//! Goals for the search other than the usual perfect clear.
+//!
+//! The search doesn't care what a goal means: it only needs to know how far each board is
+//! from it, so any `Evaluator` can reuse the same turn-limited search, ranking, and
+//! best-effort results.
+
+use crate::{
+    check_ring, custom_goal, evaluate, input_from_js, orientation, solve_to_js, Result, Ring,
+    RingMovement, Search, Solution, SolveOptions, MAX_TURNS, NUM_ANGLES,
+};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// Decides which boards reach the goal.
+pub trait Evaluator {
+    /// How far `ring` is from the goal: 0 if the goal is reached, and otherwise lower for
+    /// boards that are closer, which are kept as best-effort results.
+    fn distance(&self, ring: Ring) -> u32;
+
+    /// How far the board reached from the start by `moves` is from the goal, for goals
+    /// that follow more than where the enemies are. Defaults to `distance`.
+    fn distance_after(&self, ring: Ring, moves: &[RingMovement]) -> u32 {
+        let _ = moves;
+        self.distance(ring)
+    }
+
+    /// Whether `moves` can be made from the start at all, for goals that rule out some
+    /// movements. The search skips every board under movements that aren't allowed.
+    /// Defaults to allowing everything.
+    fn allows(&self, moves: &[RingMovement]) -> bool {
+        let _ = moves;
+        true
+    }
+}
+
+/// Any function from a board to a distance is an evaluator.
+impl<F: Fn(Ring) -> u32> Evaluator for F {
+    fn distance(&self, ring: Ring) -> u32 {
+        self(ring)
+    }
+}
+
+/// The default goal: every enemy can be hit with the actions available.
+/// The distance is how many more actions it would take.
+pub struct PerfectClear;
+
+impl Evaluator for PerfectClear {
+    fn distance(&self, ring: Ring) -> u32 {
+        evaluate(ring).excess_actions()
+    }
+}
+
+/// Gathers every enemy into at most `angles` angles, e.g. so a single kind of attack can
+/// reach them all. The distance is how many extra angles are occupied.
+pub struct ClusterEnemies {
+    pub angles: u32,
+}
+
+impl Evaluator for ClusterEnemies {
+    fn distance(&self, ring: Ring) -> u32 {
+        let occupied = ring.iter().fold(0, |angles, &subring| angles | subring);
+        debug_assert!(occupied >> NUM_ANGLES == 0);
+        occupied.count_ones().saturating_sub(self.angles)
+    }
+}
+
+/// Recreates a formation: every enemy has to end up exactly where it is in `goal`.
+/// The distance is how many cells differ.
+pub struct MatchBoard {
+    pub goal: Ring,
+}
+
+impl Evaluator for MatchBoard {
+    fn distance(&self, ring: Ring) -> u32 {
+        ring.iter().zip(&self.goal).map(|(a, b)| (a ^ b).count_ones()).sum()
+    }
+}
+
+/// Finds the best way to reach the goal of `evaluator`, with the same ranking as `solve`,
+/// keeping the Toads in `options` safe and going for its priority targets. A partner or a
+/// tolerance only help clear the board, so this fails if `options` has either.
+///
+/// The search can only use its heuristic backends for the default goal, so this always
+/// searches every board at each depth.
+pub fn find_solution_with(
+    ring: Ring,
+    options: &SolveOptions,
+    evaluator: Rc<dyn Evaluator>,
+) -> std::result::Result<Option<Solution>, String> {
+    if options.tolerance.is_some_and(|tolerance| tolerance > 0) {
+        return Err("a tolerance only applies to clearing the board".to_string());
+    }
+    let goal = custom_goal(options, options.battle_rules()?, evaluator)?;
+    Ok(Search::new(options).with_evaluator(goal).find_solution(ring, MAX_TURNS))
+}
+
+/// Finds the fewest movements that turn `start` into `goal`, within the usual turn limit.
+/// Movements never add or remove enemies, so both boards must have as many enemies.
+pub fn find_transformation(
+    start: Ring,
+    goal: Ring,
+    options: &SolveOptions,
+) -> std::result::Result<Option<Solution>, String> {
+    check_ring(&goal)?;
+    let enemies = |ring: &Ring| ring.iter().map(|subring| subring.count_ones()).sum::<u32>();
+    if enemies(&start) != enemies(&goal) {
+        return Err(format!(
+            "the start has {} enemies but the goal has {}",
+            enemies(&start),
+            enemies(&goal)
+        ));
+    }
+    find_solution_with(start, options, Rc::new(MatchBoard { goal }))
+}
+
+/// A goal decided by a JavaScript predicate over the board.
+struct JsGoal {
+    predicate: js_sys::Function,
+    /// The orientation the predicate takes boards in.
+    orientation: orientation::Orientation,
+    /// The first error thrown by the predicate, reported once the search is done.
+    error: RefCell<Option<JsValue>>,
+}
+
+impl Evaluator for JsGoal {
+    fn distance(&self, ring: Ring) -> u32 {
+        if self.error.borrow().is_some() {
+            return 1;
+        }
+        let result = serde_wasm_bindgen::to_value(&self.orientation.board_out(ring))
+            .map_err(JsValue::from)
+            .and_then(|ring| self.predicate.call1(&JsValue::NULL, &ring));
+        match result {
+            Ok(reached) => !reached.is_truthy() as u32,
+            Err(e) => {
+                *self.error.borrow_mut() = Some(e);
+                1
+            }
+        }
+    }
+}
+
+/// Solve for a custom goal: `goal` is called with each candidate board and returns whether
+/// it's acceptable. This lets front ends try house rules without a new release, but calling
+/// into JavaScript for every board is much slower than the built-in goal. `goal` is given
+/// boards in the orientation of `options`, like the solution.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_with_goal(ring: JsValue, goal: js_sys::Function, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let orientation = options.orientation;
+    let goal = Rc::new(JsGoal { predicate: goal, orientation, error: RefCell::new(None) });
+    let solution = find_solution_with(ring, &options, goal.clone())?;
+    if let Some(e) = goal.error.take() {
+        return Err(e);
+    }
+    solve_to_js(solution, &options)
+}
+
+/// Find the fewest movements that turn the `start` board into the `goal` board,
+/// e.g. to practice recreating a formation.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_to_target(start: JsValue, goal: JsValue, options: JsValue) -> Result<JsValue> {
+    let (start, options) = input_from_js(start, options)?;
+    let goal: Ring = serde_wasm_bindgen::from_value(goal)?;
+    check_ring(&goal)?;
+    let goal = options.orientation.board_in(goal);
+    solve_to_js(find_transformation(start, goal, &options)?, &options)
+}
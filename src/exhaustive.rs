@@ -0,0 +1,194 @@
+//! The exhaustive search behind the solvers for boards `Search` can't move: boards with
+//! blocked panels, boards the rings move on their own between turns, boards of other
+//! sizes, and boss arenas, as well as the Pareto front. It tries every sequence of
+//! movements one depth at a time, pruning only what the caller asks it to.
+//!
+//! Each kind of board says how it moves with `Moves`, and each search says what it's
+//! looking for with `Leaves`, so the budget, the forced first movement, and the depths are
+//! handled the same way for all of them.
+
+use crate::{
+    apply_movement, iterate_movements, total_distance, Budget, MoveConstraints, Ring,
+    RingMovement, SolveOptions,
+};
+use std::convert::Infallible;
+
+/// How a kind of board moves.
+pub(crate) trait Moves {
+    type Board;
+    /// Why a movement couldn't be made, or `Infallible`.
+    type Error;
+
+    /// Calls `visit` with each movement allowed by `constraints` from `board`, reached by
+    /// `turns` movements so far, and the board it leads to, stopping at the first error.
+    fn each_movement(
+        &self,
+        board: &Self::Board,
+        turns: usize,
+        constraints: &MoveConstraints,
+        visit: impl FnMut(RingMovement, Self::Board) -> Result<(), Self::Error>,
+    ) -> Result<(), Self::Error>;
+
+    /// The board after the forced first `movement`, or None if it can't be made.
+    fn first_moved(
+        &self,
+        board: &Self::Board,
+        movement: &RingMovement,
+    ) -> Result<Option<Self::Board>, Self::Error>;
+}
+
+/// What an exhaustive search is looking for.
+pub(crate) trait Leaves<B> {
+    /// Looks at `board`, reached by `path` once every turn is made.
+    fn leaf(&mut self, board: &B, path: &[RingMovement]);
+
+    /// Whether nothing `turn` more movements after `path` is worth looking at.
+    fn prune(&self, path: &[RingMovement], turn: u16) -> bool {
+        let _ = (path, turn);
+        false
+    }
+}
+
+/// The game's board, moved as `Search` moves it.
+pub(crate) struct RingMoves;
+
+impl Moves for RingMoves {
+    type Board = Ring;
+    type Error = Infallible;
+
+    fn each_movement(
+        &self,
+        ring: &Ring,
+        _turns: usize,
+        constraints: &MoveConstraints,
+        mut visit: impl FnMut(RingMovement, Ring) -> Result<(), Infallible>,
+    ) -> Result<(), Infallible> {
+        iterate_movements(*ring, constraints, |movement, moved| visit(movement, moved).err());
+        Ok(())
+    }
+
+    fn first_moved(
+        &self,
+        ring: &Ring,
+        movement: &RingMovement,
+    ) -> Result<Option<Ring>, Infallible> {
+        Ok(Some(apply_movement(*ring, movement)))
+    }
+}
+
+/// Keeps the board with the smallest total distance of those `reached` accepts.
+pub(crate) struct Shortest<B, F> {
+    reached: F,
+    best: Option<(u32, Vec<RingMovement>, B)>,
+}
+
+impl<B: Clone, F: FnMut(&B, &[RingMovement]) -> bool> Leaves<B> for Shortest<B, F> {
+    fn leaf(&mut self, board: &B, path: &[RingMovement]) {
+        let distance = total_distance(path);
+        let shorter = self.best.as_ref().is_none_or(|(best, _, _)| distance < *best);
+        if shorter && (self.reached)(board, path) {
+            self.best = Some((distance, path.to_vec(), board.clone()));
+        }
+    }
+}
+
+/// The movements to a board an exhaustive search found, and the board.
+pub(crate) type Found<B> = (Vec<RingMovement>, B);
+
+/// The state of an exhaustive search of the boards `moves` makes.
+pub(crate) struct Exhaustive<'a, M> {
+    moves: &'a M,
+    first_move: Option<RingMovement>,
+    constraints: MoveConstraints,
+    /// The boards visited so far, and what's left of the budget.
+    pub(crate) budget: Budget,
+    path: Vec<RingMovement>,
+}
+
+impl<'a, M: Moves> Exhaustive<'a, M> {
+    /// Searches with the budget, forced first movement, and constraints of `options`.
+    pub(crate) fn new(moves: &'a M, options: &SolveOptions) -> Self {
+        Exhaustive {
+            moves,
+            first_move: options.first_move,
+            constraints: options.constraints,
+            budget: Budget::new(options),
+            path: Vec::new(),
+        }
+    }
+
+    /// Searches every sequence of exactly `turn` more movements from `board`, showing
+    /// `leaves` each board they reach.
+    pub(crate) fn search(
+        &mut self,
+        board: &M::Board,
+        turn: u16,
+        leaves: &mut impl Leaves<M::Board>,
+    ) -> Result<(), M::Error> {
+        if self.budget.spent() || leaves.prune(&self.path, turn) {
+            return Ok(());
+        }
+        self.budget.nodes += 1;
+        if turn == 0 {
+            leaves.leaf(board, &self.path);
+            return Ok(());
+        }
+        if let Some(movement) = self.first_move.filter(|_| self.path.is_empty()) {
+            if let Some(moved) = self.moves.first_moved(board, &movement)? {
+                self.path.push(movement);
+                let searched = self.search(&moved, turn - 1, leaves);
+                self.path.pop();
+                searched?;
+            }
+            return Ok(());
+        }
+        let moves = self.moves;
+        let constraints = self.constraints;
+        moves.each_movement(board, self.path.len(), &constraints, |movement, moved| {
+            self.path.push(movement);
+            let searched = self.search(&moved, turn - 1, leaves);
+            self.path.pop();
+            searched
+        })
+    }
+
+    /// Searches one depth at a time, from the fewest turns up to `max_turns`, until the
+    /// budget runs out or `done` says `leaves` has found enough after a depth.
+    pub(crate) fn search_depths<L: Leaves<M::Board>>(
+        &mut self,
+        board: &M::Board,
+        max_turns: u16,
+        leaves: &mut L,
+        mut done: impl FnMut(&L) -> bool,
+    ) -> Result<(), M::Error> {
+        // With a forced first move, the board as given doesn't count.
+        let first_turn = self.first_move.is_some() as u16;
+        for turn in first_turn..=max_turns {
+            self.search(board, turn, leaves)?;
+            if self.budget.truncated || done(leaves) {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// The fewest turns that reach a board `reached` accepts, then the smallest total
+    /// distance, as the moves and the board they reach. None if nothing within `max_turns`
+    /// does, or the budget ran out first.
+    pub(crate) fn find_shortest(
+        &mut self,
+        board: &M::Board,
+        max_turns: u16,
+        reached: impl FnMut(&M::Board, &[RingMovement]) -> bool,
+    ) -> Result<Option<Found<M::Board>>, M::Error>
+    where
+        M::Board: Clone,
+    {
+        let mut shortest = Shortest { reached, best: None };
+        self.search_depths(board, max_turns, &mut shortest, |shortest| shortest.best.is_some())?;
+        if self.budget.truncated {
+            return Ok(None);
+        }
+        Ok(shortest.best.map(|(_, moves, board)| (moves, board)))
+    }
+}
@@ -0,0 +1,76 @@
+//! First strikes: hitting an enemy in the overworld before the battle starts, which either
+//! defeats it outright or takes away some of its HP. Applying the strike first makes the
+//! board solved match what the player sees once the battle begins.
+//!
+//! ```json
+//! {"type": "remove", "r": 0, "th": 3}
+//! {"type": "damage", "r": 2, "th": 7, "damage": 3}
+//! ```
+
+use crate::hp::HpGrid;
+use crate::{check_ring, Result, Ring, NUM_ANGLES, NUM_RINGS};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// What a first strike does to the enemy at ring `r` and angle `th`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FirstStrike {
+    /// Defeats the enemy.
+    Remove { r: u16, th: u16 },
+    /// Takes `damage` HP from the enemy, defeating it if that's all it had.
+    Damage { r: u16, th: u16, damage: u16 },
+}
+
+/// A board, and the HP of its enemies if known, as in `hp::EnemyHp::hp`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Board {
+    pub ring: Ring,
+    #[serde(default)]
+    pub hp: Option<HpGrid>,
+}
+
+impl Board {
+    /// Applies `strike`, checking that it hits an enemy.
+    pub fn strike(&mut self, strike: FirstStrike) -> std::result::Result<(), String> {
+        check_ring(&self.ring)?;
+        let (r, th, damage) = match strike {
+            FirstStrike::Remove { r, th } => (r, th, None),
+            FirstStrike::Damage { r, th, damage } => (r, th, Some(damage)),
+        };
+        if r >= NUM_RINGS || th >= NUM_ANGLES {
+            return Err(format!("there's no ring {} angle {}", r, th));
+        }
+        let (r, th) = (r as usize, th as usize);
+        if self.ring[r] & (1 << th) == 0 {
+            return Err(format!("ring {} angle {} has no enemy to strike", r, th));
+        }
+        let defeated = match (damage, &mut self.hp) {
+            (None, Some(hp)) => {
+                hp[r][th] = 0;
+                true
+            }
+            (None, None) => true,
+            (Some(damage), Some(hp)) => {
+                hp[r][th] = hp[r][th].saturating_sub(damage);
+                hp[r][th] == 0
+            }
+            (Some(_), None) => return Err("damaging an enemy needs its HP".to_string()),
+        };
+        if defeated {
+            self.ring[r] &= !(1 << th);
+        }
+        Ok(())
+    }
+}
+
+/// Apply a first strike to `board`, given as `{ring, hp}` where `hp` is optional,
+/// returning the board the battle starts with in the same form.
+#[wasm_bindgen(skip_typescript)]
+pub fn apply_first_strike(board: JsValue, strike: JsValue) -> Result<JsValue> {
+    let mut board: Board = serde_wasm_bindgen::from_value(board)?;
+    let strike: FirstStrike = serde_wasm_bindgen::from_value(strike)?;
+    board.strike(strike)?;
+    Ok(serde_wasm_bindgen::to_value(&board)?)
+}
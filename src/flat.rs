@@ -0,0 +1,79 @@
+//! Solving boards passed as typed arrays, for batch tools solving thousands of boards that
+//! don't want to pay for converting every board and solution to and from JavaScript objects.
+//!
+//! A board is a `Uint16Array` of one number per ring, as `solve` takes. `solve_flat` gives
+//! the solution back as an `Int16Array`, empty if there's none:
+//! - the number of moves;
+//! - 1 if the solution is perfect, plus 2 if the search was truncated;
+//! - the resulting board, one number per ring;
+//! - three numbers per move: 0 for a ring rotation or 1 for a row shift, the ring or the
+//!   row's angle, and the amount, positive for clockwise or outward.
+//!
+//! With `spoilerFree`, only the first two numbers are given, like the `MoveCount` `solve`
+//! gives then.
+
+use crate::{
+    check_ring, find_solution, oriented_options_from_js, solve_to_js, Result, Ring,
+    RingMovement, Solution, SolveOptions, MAX_TURNS, NUM_RINGS,
+};
+use std::convert::TryInto;
+use wasm_bindgen::prelude::*;
+
+/// Reads a board from the number for each ring.
+pub fn ring_from_slice(ring: &[u16]) -> std::result::Result<Ring, String> {
+    let ring: Ring = ring.try_into().map_err(|_| {
+        format!("the board has {} rings, expected {}", ring.len(), NUM_RINGS)
+    })?;
+    check_ring(&ring)?;
+    Ok(ring)
+}
+
+/// The three numbers `solve_flat` writes for `movement`.
+fn flat_movement(movement: &RingMovement) -> [i16; 3] {
+    match *movement {
+        RingMovement::Ring { r, amount, clockwise } => {
+            [0, r as i16, if clockwise { amount } else { -amount }]
+        }
+        RingMovement::Row { th, amount, outward } => {
+            [1, th as i16, if outward { amount } else { -amount }]
+        }
+    }
+}
+
+/// `solution` written as `solve_flat` gives it.
+pub fn flatten(solution: &Solution) -> Vec<i16> {
+    let mut flat = Vec::with_capacity(2 + NUM_RINGS as usize + 3 * solution.moves.len());
+    flat.push(solution.moves.len() as i16);
+    flat.push(solution.perfect as i16 | (solution.truncated as i16) << 1);
+    flat.extend(solution.result.iter().map(|&subring| subring as i16));
+    flat.extend(solution.moves.iter().flat_map(flat_movement));
+    flat
+}
+
+/// Solves `ring` with `options`, as `solve` does without a progress callback, returning
+/// the options read to turn the solution to the frontend's orientation with.
+fn solve_slice(ring: &[u16], options: JsValue) -> Result<(Option<Solution>, SolveOptions)> {
+    let ring = ring_from_slice(ring)?;
+    let options = oriented_options_from_js(options)?;
+    let solution = find_solution(options.orientation.board_in(ring), MAX_TURNS, &options);
+    Ok((solution, options))
+}
+
+/// Solve a board given as a `Uint16Array`, returning the solution like `solve`.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_typed(ring: &[u16], options: JsValue) -> Result<JsValue> {
+    let (solution, options) = solve_slice(ring, options)?;
+    solve_to_js(solution, &options)
+}
+
+/// Solve a board given as a `Uint16Array`, returning the solution as a flat `Int16Array`.
+#[wasm_bindgen]
+pub fn solve_flat(ring: &[u16], options: JsValue) -> Result<Vec<i16>> {
+    let (solution, options) = solve_slice(ring, options)?;
+    let solution = solution.map(|solution| options.orientation.solution_out(solution));
+    let mut flat = solution.as_ref().map(flatten).unwrap_or_default();
+    if options.spoiler_free {
+        flat.truncate(2);
+    }
+    Ok(flat)
+}
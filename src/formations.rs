@@ -0,0 +1,82 @@
+//! A library of common enemy formations, so generators, tests, and tutorials can refer to
+//! them by name.
+
+use crate::symmetry::rotate_board;
+use crate::{Result, Ring};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// A named enemy formation, at rotation 0.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Formation {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub board: Ring,
+}
+
+/// Every formation in the library.
+pub const FORMATIONS: &[Formation] = &[
+    Formation {
+        name: "column",
+        description: "Four enemies lined up in one angle, cleared with a single jump.",
+        board: [0b1, 0b1, 0b1, 0b1],
+    },
+    Formation {
+        name: "block",
+        description: "A 2×2 block in the inner rings, cleared with a single hammer.",
+        board: [0b11, 0b11, 0, 0],
+    },
+    Formation {
+        name: "four-corners",
+        description: "Four enemies spread evenly around the outer ring, like the classic Goomba opener.",
+        board: [0, 0, 0, 0b001001001001],
+    },
+    Formation {
+        name: "double-column",
+        description: "Two full columns side by side.",
+        board: [0b11, 0b11, 0b11, 0b11],
+    },
+    Formation {
+        name: "opposite-columns",
+        description: "Two full columns on opposite sides of the ring.",
+        board: [0b1000001, 0b1000001, 0b1000001, 0b1000001],
+    },
+    Formation {
+        name: "staggered",
+        description: "Enemies alternating between the two inner rings, like a line of Shy Guys.",
+        board: [0b0101, 0b1010, 0, 0],
+    },
+    Formation {
+        name: "diagonal",
+        description: "One enemy in each ring, each one angle further clockwise.",
+        board: [0b0001, 0b0010, 0b0100, 0b1000],
+    },
+    Formation {
+        name: "inner-circle",
+        description: "Six enemies every other angle around the innermost ring.",
+        board: [0b010101010101, 0, 0, 0],
+    },
+];
+
+/// Finds a formation by name, rotated clockwise by `rotation` angles.
+pub fn find_formation(name: &str, rotation: u16) -> Option<Ring> {
+    FORMATIONS
+        .iter()
+        .find(|formation| formation.name == name)
+        .map(|formation| rotate_board(formation.board, rotation))
+}
+
+/// Get the board for a named formation, rotated clockwise by `rotation` angles.
+#[wasm_bindgen(skip_typescript)]
+pub fn formation(name: &str, rotation: u16) -> Result<JsValue> {
+    let board = find_formation(name, rotation)
+        .ok_or_else(|| format!("unknown formation {:?}", name))?;
+    Ok(serde_wasm_bindgen::to_value(&board)?)
+}
+
+/// List every formation in the library, with its name, description, and board.
+#[wasm_bindgen(skip_typescript)]
+pub fn formations() -> Result<JsValue> {
+    Ok(serde_wasm_bindgen::to_value(&FORMATIONS)?)
+}
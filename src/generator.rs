@@ -0,0 +1,262 @@
+//! Random board generation, for puzzle packs, practice, and analysis.
+//!
+//! Designers can describe the boards they want with `DesignConstraints`, e.g.:
+//! ```json
+//! {
+//!   "count": 5, "enemies": 8, "seed": 42,
+//!   "constraints": {"turns": 2, "rotatesRing": 3, "unique": true, "mirrored": true}
+//! }
+//! ```
+//! Random boards are generated until one meets every constraint, so strict constraints can
+//! make generation slow, or produce fewer boards than asked for.
+
+use crate::symmetry::{find_symmetries, mirror_board, rotate_board};
+use crate::{Result, Ring, RingMovement, Search, SolveOptions, MAX_TURNS, NUM_ANGLES, NUM_RINGS};
+use serde::Deserialize;
+use std::ops::Range;
+use wasm_bindgen::prelude::*;
+
+/// Describes a batch of random boards to generate.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct GeneratorSpec {
+    /// How many boards to generate.
+    pub count: usize,
+    /// How many enemies to place on each board.
+    pub enemies: u16,
+    /// The seed for the random number generator; the same spec always makes the same boards.
+    pub seed: u64,
+    /// What the boards have to look like, and how they have to be solved.
+    pub constraints: DesignConstraints,
+}
+
+impl Default for GeneratorSpec {
+    fn default() -> Self {
+        GeneratorSpec { count: 1, enemies: 8, seed: 0, constraints: DesignConstraints::default() }
+    }
+}
+
+impl GeneratorSpec {
+    /// Checks that the constraints can be met at all.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        let constraints = &self.constraints;
+        if let Some(order) = constraints.rotational_order {
+            if order == 0 || !NUM_ANGLES.is_multiple_of(order) {
+                return Err(format!("rotational order {} doesn't divide {}", order, NUM_ANGLES));
+            }
+        }
+        if let Some(counts) = &constraints.per_ring {
+            if let Some(r) = counts.iter().position(|&count| count > NUM_ANGLES) {
+                return Err(format!("ring {} can't hold {} enemies", r, counts[r]));
+            }
+        }
+        if constraints.turns.is_some_and(|turns| turns > MAX_TURNS) {
+            return Err(format!("boards can't need more than {} turns", MAX_TURNS));
+        }
+        if constraints.rotates_ring.is_some_and(|r| r >= NUM_RINGS) {
+            return Err(format!("there are only {} rings", NUM_RINGS));
+        }
+        Ok(())
+    }
+}
+
+/// Requirements a designer has for generated boards. Everything is optional.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DesignConstraints {
+    /// Exactly this many enemies in each ring, from inner to outer, instead of `enemies`
+    /// anywhere on the board.
+    pub per_ring: Option<[u16; NUM_RINGS as usize]>,
+    /// Cells that must stay empty, in the same layout as a board.
+    pub forbidden: Ring,
+    /// The fewest turns the board must need.
+    pub turns: Option<u16>,
+    /// A ring the best solution must rotate.
+    pub rotates_ring: Option<u16>,
+    /// Whether the best solution has to be the only one with as few turns, not counting
+    /// reordering movements that commute.
+    pub unique: bool,
+    /// The board must look the same after rotating by `12 / rotational_order` angles.
+    pub rotational_order: Option<u16>,
+    /// Whether the board must be mirror symmetric across some axis.
+    pub mirrored: bool,
+    /// How many random boards to try for each board generated before giving up.
+    pub max_attempts: u32,
+}
+
+impl Default for DesignConstraints {
+    fn default() -> Self {
+        DesignConstraints {
+            per_ring: None,
+            forbidden: [0; NUM_RINGS as usize],
+            turns: None,
+            rotates_ring: None,
+            unique: false,
+            rotational_order: None,
+            mirrored: false,
+            max_attempts: 1000,
+        }
+    }
+}
+
+impl DesignConstraints {
+    /// Whether any constraint depends on how the board is solved.
+    fn needs_solve(&self) -> bool {
+        self.turns.is_some() || self.rotates_ring.is_some() || self.unique
+    }
+
+    /// Whether `board` meets every constraint, given the enemies it should have.
+    fn accepts(&self, board: &Ring, enemies: u16) -> bool {
+        let counts = board.map(|subring| subring.count_ones() as u16);
+        let counts_match = match &self.per_ring {
+            Some(per_ring) => counts == *per_ring,
+            None => counts.iter().sum::<u16>() == enemies,
+        };
+        if !counts_match || board.iter().zip(&self.forbidden).any(|(b, f)| b & f != 0) {
+            return false;
+        }
+        if self.rotational_order.is_some() || self.mirrored {
+            let symmetries = find_symmetries(*board);
+            let order = self.rotational_order.unwrap_or(1);
+            if !symmetries.rotational_order.is_multiple_of(order) || self.mirrored && symmetries.mirrors.is_empty() {
+                return false;
+            }
+        }
+        if !self.needs_solve() {
+            return true;
+        }
+        // Searching deeper than the required turns can only find boards that get rejected.
+        let max_turns = self.turns.unwrap_or(MAX_TURNS);
+        let wanted = if self.unique { 2 } else { 1 };
+        let solutions = Search::new(&SolveOptions::default()).find_solutions(*board, max_turns, wanted);
+        let best = match solutions.first() {
+            Some(best) if best.perfect => best,
+            _ => return false,
+        };
+        let turns = best.moves.len();
+        let rotates = |r| {
+            best.moves.iter().any(|movement| matches!(*movement, RingMovement::Ring { r: moved, .. } if moved == r))
+        };
+        self.turns.is_none_or(|wanted| wanted as usize == turns)
+            && self.rotates_ring.is_none_or(rotates)
+            && (!self.unique || solutions.iter().filter(|s| s.moves.len() == turns).count() == 1)
+    }
+}
+
+/// A small, fast, deterministic random number generator (xorshift64*).
+/// Boards don't need cryptographic randomness, just the same results on every platform.
+pub(crate) struct XorShift(u64);
+
+impl XorShift {
+    const MIX: u64 = 0x9E37_79B9_7F4A_7C15;
+
+    pub(crate) fn new(seed: u64) -> Self {
+        // The state must never be zero, or every number after it would be zero too. The
+        // one seed that mixes to zero gets the same numbers as seed 0 instead.
+        match seed ^ Self::MIX {
+            0 => XorShift(Self::MIX),
+            state => XorShift(state),
+        }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// A random number in `0..n`.
+    pub(crate) fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n
+    }
+}
+
+/// Places up to `enemies` more enemies on distinct random empty cells of `rings`,
+/// skipping cells set in `forbidden`.
+fn place_random(rng: &mut XorShift, ring: &mut Ring, rings: Range<u16>, enemies: u16, forbidden: &Ring) {
+    let open = |ring: &Ring, r: u16| (!(ring[r as usize] | forbidden[r as usize]) & 0xFFF).count_ones() as u16;
+    let cells = rings.len() as u16 * NUM_ANGLES;
+    let mut left = enemies.min(rings.clone().map(|r| open(ring, r)).sum());
+    while left > 0 {
+        let cell = rng.below(cells as u64) as u16;
+        let (r, th) = ((rings.start + cell / NUM_ANGLES) as usize, cell % NUM_ANGLES);
+        if (ring[r] | forbidden[r]) & (1 << th) == 0 {
+            ring[r] |= 1 << th;
+            left -= 1;
+        }
+    }
+}
+
+/// Makes a random board that's likely to meet the symmetry and count constraints:
+/// enough enemies are placed that copying them to every symmetric cell gives about
+/// the right number.
+fn candidate_board(rng: &mut XorShift, enemies: u16, constraints: &DesignConstraints) -> Ring {
+    let order = constraints.rotational_order.unwrap_or(1);
+    let copies = order * if constraints.mirrored { 2 } else { 1 };
+    let mut board: Ring = [0; NUM_RINGS as usize];
+    let forbidden = &constraints.forbidden;
+    match &constraints.per_ring {
+        Some(per_ring) => {
+            for (r, &count) in (0..NUM_RINGS).zip(per_ring) {
+                place_random(rng, &mut board, r..r + 1, count.div_ceil(copies), forbidden);
+            }
+        }
+        None => place_random(rng, &mut board, 0..NUM_RINGS, enemies.div_ceil(copies), forbidden),
+    }
+    if order > 1 {
+        let step = NUM_ANGLES / order;
+        let fundamental = board;
+        for i in 1..order {
+            let rotated = rotate_board(fundamental, step * i);
+            board.iter_mut().zip(&rotated).for_each(|(b, r)| *b |= r);
+        }
+    }
+    if constraints.mirrored {
+        let mirrored = mirror_board(board, rng.below(NUM_ANGLES as u64) as u16);
+        board.iter_mut().zip(&mirrored).for_each(|(b, m)| *b |= m);
+    }
+    board
+}
+
+/// Generates the boards described by `spec`. If a board meeting the constraints isn't
+/// found within `max_attempts`, it's left out, so fewer than `count` boards may be returned.
+pub fn generate_boards(spec: &GeneratorSpec) -> Vec<Ring> {
+    let mut rng = XorShift::new(spec.seed);
+    let constraints = &spec.constraints;
+    (0..spec.count)
+        .filter_map(|_| {
+            (0..constraints.max_attempts.max(1))
+                .map(|_| candidate_board(&mut rng, spec.enemies, constraints))
+                .find(|board| constraints.accepts(board, spec.enemies))
+        })
+        .collect()
+}
+
+/// Boards given to an analysis from JavaScript: either listed directly, or generated.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(crate) enum BoardSource {
+    Boards(Vec<Ring>),
+    Generator(GeneratorSpec),
+}
+
+impl BoardSource {
+    pub(crate) fn into_boards(self) -> std::result::Result<Vec<Ring>, String> {
+        match self {
+            BoardSource::Boards(boards) => Ok(boards),
+            BoardSource::Generator(spec) => {
+                spec.check()?;
+                Ok(generate_boards(&spec))
+            }
+        }
+    }
+}
+
+/// Generate random boards from a `GeneratorSpec`, including any designer constraints.
+#[wasm_bindgen(skip_typescript)]
+pub fn generate(spec: JsValue) -> Result<JsValue> {
+    let spec: GeneratorSpec = serde_wasm_bindgen::from_value(spec)?;
+    spec.check()?;
+    Ok(serde_wasm_bindgen::to_value(&generate_boards(&spec))?)
+}
@@ -0,0 +1,129 @@
+//! Admissible estimates of how many turns a board needs, for pruning the search, and a
+//! report on how good they are.
+
+use crate::generator::BoardSource;
+use crate::{check_ring, evaluate, Result, Ring, Search, SolveOptions, MAX_TURNS};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// A lower bound on the turns needed to solve `ring`, from how its enemies are spread
+/// across the rings.
+///
+/// With `A` actions, a perfect board needs `jumps + hammers <= A`. Every hammer covers
+/// two angles, so counting occupied angles gives `jump angles + all angles <= 2A`,
+/// and each of those is at least the most enemies in any one (outer) ring.
+/// Rotations never change how many enemies a ring has, and a shift moves at most two
+/// enemies in or out of each ring, so each turn can lower that total by at most four.
+pub fn occupancy_bound(ring: Ring) -> u16 {
+    let evaluation = evaluate(ring);
+    if evaluation.is_perfect() {
+        return 0;
+    }
+    let counts = ring.map(u16::count_ones);
+    let most = *counts.iter().max().unwrap();
+    let most_outer = counts[2].max(counts[3]);
+    let over = (most + most_outer).saturating_sub(2 * evaluation.actions);
+    // Every imperfect board needs at least one turn.
+    over.div_ceil(4).max(1) as u16
+}
+
+/// A trivial bound: no turns for a perfect board, and one otherwise.
+/// Useful as a baseline to compare other heuristics against.
+pub fn unit_bound(ring: Ring) -> u16 {
+    !evaluate(ring).is_perfect() as u16
+}
+
+/// How many turns `lower_bound` searches every board for.
+/// Each extra turn multiplies the work by the ~90 possible movements.
+const EXACT_TURNS: u16 = 2;
+
+/// The best lower bound on the turns needed to solve `ring` that's still quick to find:
+/// exact for boards that need at most two turns, since every board that close can be
+/// tried, and otherwise the larger of three and `occupancy_bound`.
+pub fn lower_bound(ring: Ring) -> u16 {
+    let estimate = occupancy_bound(ring);
+    if estimate > EXACT_TURNS {
+        return estimate;
+    }
+    match Search::new(&SolveOptions::default()).find_solution(ring, EXACT_TURNS) {
+        Some(solution) if solution.perfect => solution.moves.len() as u16,
+        _ => estimate.max(EXACT_TURNS + 1),
+    }
+}
+
+/// A lower bound on the turns a board needs.
+pub type Heuristic = fn(Ring) -> u16;
+
+/// Every heuristic, by name, for comparing them in a `HeuristicReport`.
+pub const HEURISTICS: &[(&str, Heuristic)] =
+    &[("unit", unit_bound), ("occupancy", occupancy_bound), ("lowerBound", lower_bound)];
+
+/// How one heuristic's estimates compare to the true turns needed over a sample.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeuristicQuality {
+    pub name: &'static str,
+    /// Boards where the estimate was more than the true turns, so it isn't admissible.
+    pub violations: usize,
+    /// Boards where the estimate was exactly right.
+    pub exact: usize,
+    /// The average of true turns minus the estimate; lower is a tighter bound.
+    pub average_slack: f64,
+}
+
+/// How well each heuristic estimates the turns needed for a sample of boards.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HeuristicReport {
+    /// Boards that could be solved, which the report covers.
+    pub solvable: usize,
+    /// Boards that can't be solved within `MAX_TURNS`, so have no true turns to compare to.
+    pub unsolvable: usize,
+    pub heuristics: Vec<HeuristicQuality>,
+}
+
+/// Compares every heuristic against the true minimal turns of each board.
+pub fn report(boards: &[Ring]) -> HeuristicReport {
+    let solved: Vec<(Ring, u16)> = boards
+        .iter()
+        .filter_map(|&board| {
+            let solution = Search::new(&SolveOptions::default()).find_solution(board, MAX_TURNS)?;
+            solution.perfect.then_some((board, solution.moves.len() as u16))
+        })
+        .collect();
+    let heuristics = HEURISTICS
+        .iter()
+        .map(|&(name, heuristic)| {
+            let mut quality = HeuristicQuality { name, violations: 0, exact: 0, average_slack: 0.0 };
+            let mut slack = 0;
+            for &(board, turns) in &solved {
+                let estimate = heuristic(board);
+                quality.violations += (estimate > turns) as usize;
+                quality.exact += (estimate == turns) as usize;
+                slack += turns as i64 - estimate as i64;
+            }
+            if !solved.is_empty() {
+                quality.average_slack = slack as f64 / solved.len() as f64;
+            }
+            quality
+        })
+        .collect();
+    HeuristicReport { solvable: solved.len(), unsolvable: boards.len() - solved.len(), heuristics }
+}
+
+/// Compare each heuristic's estimates against the true minimal turns for a sample of boards,
+/// given as an array of boards or a `GeneratorSpec`.
+#[wasm_bindgen(skip_typescript)]
+pub fn heuristic_report(input: JsValue) -> Result<JsValue> {
+    let source: BoardSource = serde_wasm_bindgen::from_value(input)?;
+    Ok(serde_wasm_bindgen::to_value(&report(&source.into_boards()?))?)
+}
+
+/// Quickly find a lower bound on the turns `ring` needs, e.g. to show "at least 3 moves
+/// needed" while the full search runs. See `lower_bound`.
+#[wasm_bindgen(js_name = lower_bound, skip_typescript)]
+pub fn lower_bound_js(ring: JsValue) -> Result<u16> {
+    let ring: Ring = serde_wasm_bindgen::from_value(ring)?;
+    check_ring(&ring)?;
+    Ok(lower_bound(ring))
+}
@@ -0,0 +1,202 @@
+//! Enemy HP, so that when a board can't be cleared, the solver still finds the alignment
+//! that deals the most damage instead of treating every imperfect board the same.
+//!
+//! ```json
+//! {"hp": [[3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], ...], "damage": 2}
+//! ```
+
+use crate::evaluator::Evaluator;
+use crate::preset::Rules;
+use crate::{
+    apply_movement, custom_goal, finish_solution, hammer_block, input_from_js, panels_of,
+    solve_to_js, without_yielding, Result, Ring, RingMovement, Search, Solution, SolveOptions,
+    MAX_TURNS, NUM_ANGLES, NUM_RINGS,
+};
+use serde::Deserialize;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// The most HP an enemy can have.
+pub const MAX_HP: u16 = 255;
+
+/// The HP of each enemy, by ring and then angle.
+pub type HpGrid = [[u16; NUM_ANGLES as usize]; NUM_RINGS as usize];
+
+/// The HP of every enemy on a board, and the damage each hit deals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct EnemyHp {
+    /// The HP of the enemy on each panel, by ring and then angle, and 0 where there's none.
+    pub hp: HpGrid,
+    /// The damage every enemy a jump or hammer hits takes.
+    pub damage: u16,
+}
+
+impl Default for EnemyHp {
+    fn default() -> Self {
+        EnemyHp { hp: [[0; NUM_ANGLES as usize]; NUM_RINGS as usize], damage: 1 }
+    }
+}
+
+impl EnemyHp {
+    /// Checks that exactly the panels with enemies have HP, and no more than `MAX_HP`.
+    pub fn check(&self, ring: &Ring) -> std::result::Result<(), String> {
+        if self.damage == 0 {
+            return Err("attacks must deal some damage".to_string());
+        }
+        for (r, hps) in self.hp.iter().enumerate() {
+            for (th, &hp) in hps.iter().enumerate() {
+                let enemy = ring[r] & (1 << th) != 0;
+                if hp > 0 && !enemy {
+                    return Err(format!("ring {} angle {} has HP but no enemy", r, th));
+                }
+                if hp == 0 && enemy {
+                    return Err(format!("ring {} angle {} has an enemy without HP", r, th));
+                }
+                if hp > MAX_HP {
+                    return Err(format!("ring {} angle {} has more than {} HP", r, th, MAX_HP));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The bits of each enemy's HP, one board for each bit, so they move with the enemies.
+type HpPlanes = [Ring; MAX_HP.count_ones() as usize];
+
+fn to_planes(hp: &HpGrid) -> HpPlanes {
+    let mut planes = HpPlanes::default();
+    for (r, hps) in hp.iter().enumerate() {
+        for (th, &hp) in hps.iter().enumerate() {
+            for (bit, plane) in planes.iter_mut().enumerate() {
+                plane[r] |= (hp >> bit & 1) << th;
+            }
+        }
+    }
+    planes
+}
+
+fn from_planes(planes: &HpPlanes) -> HpGrid {
+    let mut hp = [[0; NUM_ANGLES as usize]; NUM_RINGS as usize];
+    for (r, hps) in hp.iter_mut().enumerate() {
+        for (th, hp) in hps.iter_mut().enumerate() {
+            for (bit, plane) in planes.iter().enumerate() {
+                *hp |= (plane[r] >> th & 1) << bit;
+            }
+        }
+    }
+    hp
+}
+
+/// The HP of each enemy once `moves` have been made.
+pub(crate) fn moved_hp(hp: &HpGrid, moves: &[RingMovement]) -> HpGrid {
+    from_planes(&to_planes(hp).map(|plane| moves.iter().fold(plane, apply_movement)))
+}
+
+/// The most damage `attacks` jumps and hammers can deal, choosing each as the one that
+/// deals the most damage to the enemies still standing. This isn't always the best
+/// possible, but it's close, and it's quick enough to run on every board searched.
+fn damage_dealt(mut hp: HpGrid, damage: u16, attacks: u32, rules: &Rules) -> u32 {
+    let dealt_to = |hp: &HpGrid, cells: Ring| -> u32 {
+        panels_of(cells).map(|(r, th)| hp[r][th].min(damage) as u32).sum()
+    };
+    let mut dealt = 0;
+    for _ in 0..attacks {
+        // The cells the best attack hits, and the damage it deals.
+        let mut best: (u32, Ring) = (0, [0; NUM_RINGS as usize]);
+        for th in 0..NUM_ANGLES as usize {
+            let mut candidates = [None; 2];
+            if rules.jump {
+                // A jump hits the line outward from the innermost enemy standing.
+                let mut line = [0; NUM_RINGS as usize];
+                (0..NUM_RINGS as usize)
+                    .skip_while(|&r| hp[r][th] == 0)
+                    .take_while(|&r| hp[r][th] > 0)
+                    .for_each(|r| line[r] = 1 << th);
+                candidates[0] = Some(line);
+            }
+            if rules.hammer {
+                candidates[1] = Some(hammer_block(th as u16));
+            }
+            for &cells in candidates.iter().flatten() {
+                let dealt = dealt_to(&hp, cells);
+                if dealt > best.0 {
+                    best = (dealt, cells);
+                }
+            }
+        }
+        if best.0 == 0 {
+            break;
+        }
+        dealt += best.0;
+        for (r, th) in panels_of(best.1) {
+            hp[r][th] = hp[r][th].saturating_sub(damage);
+        }
+    }
+    dealt
+}
+
+/// Every enemy can be hit with the actions and weapons of `rules`. When they can't, boards
+/// where the actions deal more damage are closer: the distance is 1 more than the HP that
+/// would be left.
+pub struct HpGoal {
+    planes: HpPlanes,
+    damage: u16,
+    rules: Rules,
+}
+
+impl HpGoal {
+    pub fn new(hp: &EnemyHp, rules: Rules) -> Self {
+        HpGoal { planes: to_planes(&hp.hp), damage: hp.damage, rules }
+    }
+}
+
+impl Evaluator for HpGoal {
+    fn distance(&self, ring: Ring) -> u32 {
+        self.distance_after(ring, &[])
+    }
+
+    fn distance_after(&self, ring: Ring, moves: &[RingMovement]) -> u32 {
+        if self.rules.distance(ring) == 0 {
+            return 0;
+        }
+        let hp = from_planes(&self.planes.map(|plane| moves.iter().fold(plane, apply_movement)));
+        let total: u32 = hp.iter().flatten().map(|&hp| hp as u32).sum();
+        let actions = self.rules.actions_for(ring);
+        1 + total - damage_dealt(hp, self.damage, actions, &self.rules)
+    }
+}
+
+/// Finds the best way to clear `ring`, with the same ranking and everything else `solve`
+/// reports. If it can't be cleared, this returns the board that deals the most damage as a
+/// best-effort solution. Fails if `options` has a partner.
+pub fn find_solution(
+    ring: Ring,
+    hp: &EnemyHp,
+    options: &SolveOptions,
+) -> std::result::Result<Option<Solution>, String> {
+    hp.check(&ring)?;
+    let rules = options.battle_rules()?;
+    let goal = custom_goal(options, rules, Rc::new(HpGoal::new(hp, rules)))?;
+    let mut search = Search::new(options).with_evaluator(goal);
+    let solution = match search.find_solution(ring, MAX_TURNS) {
+        Some(solution) => solution,
+        None => match search.best_solution(false) {
+            Some(solution) => solution,
+            None => return Ok(None),
+        },
+    };
+    let finished = finish_solution(&mut search, solution, ring, MAX_TURNS, options);
+    Ok(Some(without_yielding(finished)))
+}
+
+/// Solve a board whose enemies have HP, given as `{hp, damage}`. If it can't be cleared,
+/// the best-effort solution deals the most damage.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_with_hp(ring: JsValue, hp: JsValue, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let hp: EnemyHp = serde_wasm_bindgen::from_value(hp)?;
+    let hp = EnemyHp { hp: options.orientation.grid_in(hp.hp), ..hp };
+    solve_to_js(find_solution(ring, &hp, &options)?, &options)
+}
@@ -0,0 +1,225 @@
+//! An integer linear programming model of the ring puzzle, solved with `microlp`.
+//!
+//! This is far slower than the hand-written search, and is only built with the `ilp`
+//! feature. It's meant as an independent oracle to cross-check the search against, and
+//! as a starting point for researching variants that are easier to state as constraints
+//! than to search.
+//!
+//! For `T` turns, the model has:
+//! - a binary `y[t][m]` for making movement `m` on turn `t`, exactly one per turn;
+//! - a continuous `x[t][c]` in `[0, 1]` for whether cell `c` has an enemy after turn `t`.
+//!   When a movement is made, it forces each cell it touches to equal its source cell,
+//!   and when no movement touching a cell is made, the cell keeps its value. This keeps
+//!   `x` integral without declaring it so;
+//! - a binary `jump[th]` and `hammer[th]` for each angle, where a hammer at `th` covers
+//!   the inner two rings at `th` and `th + 1`;
+//! - a binary `second_jump[th]` for each angle, needed when its enemies don't form one
+//!   line outward from the innermost, unless the inner two rings there are hammered.
+//!
+//! Every enemy left after the last turn has to be covered, with at most `ceil(enemies / 4)`
+//! jumps and hammers in total.
+
+use crate::{
+    apply_movement, evaluate, iterate_movements, MoveConstraints, Result, Ring, RingMovement,
+    Search, Solution, SolveOptions, MAX_TURNS, NUM_ANGLES, NUM_RINGS,
+};
+use microlp::{ComparisonOp, LinearExpr, OptimizationDirection, Problem, Variable};
+use wasm_bindgen::prelude::*;
+
+const NUM_CELLS: usize = (NUM_RINGS * NUM_ANGLES) as usize;
+
+/// The cell index of ring `r` at angle `th`.
+fn cell(r: u16, th: u16) -> usize {
+    (r * NUM_ANGLES + th) as usize
+}
+
+/// A movement and where it sends each cell it touches.
+struct MovementMap {
+    movement: RingMovement,
+    /// `(from, to)` for every cell the movement touches.
+    moves: Vec<(usize, usize)>,
+}
+
+/// Every movement allowed by `constraints`, with where it sends each cell.
+fn movement_maps(constraints: &MoveConstraints) -> Vec<MovementMap> {
+    let mut maps = Vec::new();
+    // Every ring and row has an enemy on a full board, so none of them are skipped.
+    let full = [(1 << NUM_ANGLES) - 1; NUM_RINGS as usize];
+    iterate_movements(full, constraints, |movement, _| {
+        let mut moves = Vec::new();
+        for r in 0..NUM_RINGS {
+            for th in 0..NUM_ANGLES {
+                let mut single = [0; NUM_RINGS as usize];
+                single[r as usize] = 1 << th;
+                let moved = apply_movement(single, &movement);
+                let to_r = moved.iter().position(|&subring| subring != 0).unwrap() as u16;
+                let to = cell(to_r, moved[to_r as usize].trailing_zeros() as u16);
+                let from = cell(r, th);
+                if from != to {
+                    moves.push((from, to));
+                }
+            }
+        }
+        maps.push(MovementMap { movement, moves });
+        None::<()>
+    });
+    maps
+}
+
+/// Finds a solution taking exactly `turns` turns, with the smallest total distance.
+fn solve_with_turns(ring: Ring, turns: usize, maps: &[MovementMap]) -> Option<Vec<RingMovement>> {
+    let mut problem = Problem::new(OptimizationDirection::Minimize);
+    let enemies: u32 = ring.iter().copied().map(u16::count_ones).sum();
+    let actions = enemies.div_ceil(4);
+
+    // x[0] is the given board, so it's a constant rather than a variable.
+    let mut cells: Vec<Vec<Variable>> = Vec::new();
+    let mut choices: Vec<Vec<Variable>> = Vec::new();
+    for t in 0..turns {
+        let chosen: Vec<Variable> = maps
+            .iter()
+            .map(|map| problem.add_binary_var(map.movement.distance() as f64))
+            .collect();
+        problem.add_constraint(chosen.iter().map(|&y| (y, 1.0)), ComparisonOp::Eq, 1.0);
+        let after: Vec<Variable> = (0..NUM_CELLS).map(|_| problem.add_var(0.0, (0.0, 1.0))).collect();
+
+        // `before[c]` is either a variable, or the constant value of the given board.
+        let before = |c: usize| -> (Option<Variable>, f64) {
+            match t {
+                0 => (None, (ring[c / NUM_ANGLES as usize] >> (c % NUM_ANGLES as usize) & 1) as f64),
+                _ => (Some(cells[t - 1][c]), 0.0),
+            }
+        };
+        // after[to] - before[from] <= 1 - y and before[from] - after[to] <= 1 - y.
+        let mut touching: Vec<Vec<Variable>> = vec![Vec::new(); NUM_CELLS];
+        for (map, &y) in maps.iter().zip(&chosen) {
+            for &(from, to) in &map.moves {
+                touching[from].push(y);
+                let (var, constant) = before(from);
+                for sign in [1.0, -1.0] {
+                    let mut expr = LinearExpr::empty();
+                    expr.add(after[to], sign);
+                    expr.add(y, 1.0);
+                    if let Some(var) = var {
+                        expr.add(var, -sign);
+                    }
+                    problem.add_constraint(expr, ComparisonOp::Le, 1.0 + sign * constant);
+                }
+            }
+        }
+        // after[c] - before[c] <= sum(touching y), and the same the other way.
+        for c in 0..NUM_CELLS {
+            let (var, constant) = before(c);
+            for sign in [1.0, -1.0] {
+                let mut expr = LinearExpr::empty();
+                expr.add(after[c], sign);
+                for &y in &touching[c] {
+                    expr.add(y, -1.0);
+                }
+                if let Some(var) = var {
+                    expr.add(var, -sign);
+                }
+                problem.add_constraint(expr, ComparisonOp::Le, sign * constant);
+            }
+        }
+        cells.push(after);
+        choices.push(chosen);
+    }
+
+    let jumps: Vec<Variable> = (0..NUM_ANGLES).map(|_| problem.add_binary_var(0.0)).collect();
+    let second_jumps: Vec<Variable> =
+        (0..NUM_ANGLES).map(|_| problem.add_binary_var(0.0)).collect();
+    let hammers: Vec<Variable> = (0..NUM_ANGLES).map(|_| problem.add_binary_var(0.0)).collect();
+    problem.add_constraint(
+        jumps.iter().chain(&second_jumps).chain(&hammers).map(|&v| (v, 1.0)),
+        ComparisonOp::Le,
+        actions as f64,
+    );
+    // Adds `sign * x[c]` after the last turn to `expr`, returning the part that's constant.
+    let add_final = |expr: &mut LinearExpr, c: usize, sign: f64| match cells.last() {
+        Some(last) => {
+            expr.add(last[c], sign);
+            0.0
+        }
+        None => sign * (ring[c / NUM_ANGLES as usize] >> (c % NUM_ANGLES as usize) & 1) as f64,
+    };
+    for th in 0..NUM_ANGLES {
+        let hammered = [th, (th + NUM_ANGLES - 1) % NUM_ANGLES].map(|h| hammers[h as usize]);
+        for r in 0..NUM_RINGS {
+            let mut expr = LinearExpr::empty();
+            let constant = add_final(&mut expr, cell(r, th), 1.0);
+            expr.add(jumps[th as usize], -1.0);
+            if r < 2 {
+                for &hammer in &hammered {
+                    expr.add(hammer, -1.0);
+                }
+            }
+            problem.add_constraint(expr, ComparisonOp::Le, -constant);
+        }
+        // An enemy, then a gap, then an enemy further out needs a second jump, unless
+        // the inner enemy is hammered; the gap is always outside it.
+        // x[i] - x[j] + x[k] - hammers - second_jump <= 1.
+        for i in 0..2 {
+            for j in i + 1..NUM_RINGS {
+                for k in j + 1..NUM_RINGS {
+                    let mut expr = LinearExpr::empty();
+                    let constant = add_final(&mut expr, cell(i, th), 1.0)
+                        + add_final(&mut expr, cell(j, th), -1.0)
+                        + add_final(&mut expr, cell(k, th), 1.0);
+                    for &hammer in &hammered {
+                        expr.add(hammer, -1.0);
+                    }
+                    expr.add(second_jumps[th as usize], -1.0);
+                    problem.add_constraint(expr, ComparisonOp::Le, 1.0 - constant);
+                }
+            }
+        }
+    }
+
+    let solution = problem.solve().ok()?;
+    choices
+        .iter()
+        .map(|chosen| {
+            let index = chosen.iter().position(|&y| solution.var_value_rounded(y) == 1.0)?;
+            Some(maps[index].movement)
+        })
+        .collect()
+}
+
+/// Finds a solution with the fewest turns by solving the model for each number of turns.
+pub fn find_solution(ring: Ring, max_turns: u16, constraints: &MoveConstraints) -> Option<Solution> {
+    let maps = movement_maps(constraints);
+    (0..=max_turns as usize).find_map(|turns| {
+        let moves = solve_with_turns(ring, turns, &maps)?;
+        let result = moves.iter().fold(ring, apply_movement);
+        Some(Solution::new(moves, evaluate(result), false))
+    })
+}
+
+/// Checks that the model and the search agree on how many turns `ring` needs, and that the
+/// model's solution really solves the board.
+pub fn cross_check(ring: Ring, options: &SolveOptions) -> std::result::Result<(), String> {
+    let model = find_solution(ring, MAX_TURNS, &options.constraints);
+    let search = Search::new(options).find_solution(ring, MAX_TURNS).filter(|s| s.perfect);
+    if let Some(model) = &model {
+        if !model.perfect {
+            return Err(format!("model solution {:?} doesn't solve the board", model.moves));
+        }
+    }
+    let turns = |solution: &Option<Solution>| solution.as_ref().map(|s| s.moves.len());
+    if turns(&model) != turns(&search) {
+        return Err(format!(
+            "model needs {:?} turns, but the search needs {:?}",
+            turns(&model),
+            turns(&search)
+        ));
+    }
+    Ok(())
+}
+
+/// Solve a board with the constraint model instead of the search.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_ilp(ring: JsValue, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = crate::input_from_js(ring, options)?;
+    crate::solve_to_js(find_solution(ring, MAX_TURNS, &options.constraints), &options)
+}
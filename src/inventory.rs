@@ -0,0 +1,187 @@
+//! Choosing which of the player's weapons to make each attack with once the board is
+//! aligned, either for the most damage or to spare the weapons that wear out.
+//!
+//! ```json
+//! {
+//!   "table": {...},
+//!   "types": [[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], ...],
+//!   "items": [{"weapon": "Basic Boots"}, {"weapon": "Shiny Boots", "durability": 5}],
+//!   "priority": "durability"
+//! }
+//! ```
+//!
+//! The table and types are the same as in `weapons`. Every attack is made with an item of
+//! its kind, and each use of an item with durability wears it down by one. Items of the
+//! same weapon are interchangeable, so the assignments name weapons, not items.
+
+use crate::hp::{moved_hp, HpGrid};
+use crate::weapons::{WeaponKind, WeaponTable};
+use crate::{Attack, Ring, RingMovement};
+use serde::{Deserialize, Serialize};
+
+/// What to make the most of when choosing weapons.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Priority {
+    /// The most damage, then the least durability used.
+    #[default]
+    Damage,
+    /// Defeating every enemy if possible, with as little durability used as possible,
+    /// then the most damage.
+    Durability,
+}
+
+/// A weapon the player carries.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InventoryItem {
+    /// The name of the weapon in the table.
+    pub weapon: String,
+    /// How many more times it can be used, or None if it never breaks.
+    #[serde(default)]
+    pub durability: Option<u32>,
+}
+
+/// The player's weapons, and the enemies they're used on.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Inventory {
+    pub table: WeaponTable,
+    /// The type of the enemy on each panel of the board being solved, as in
+    /// `WeaponSetup::types`.
+    pub types: HpGrid,
+    pub items: Vec<InventoryItem>,
+    #[serde(default)]
+    pub priority: Priority,
+}
+
+impl Inventory {
+    /// Checks that the table is valid and every item is a weapon in it.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        self.table.check()?;
+        for item in &self.items {
+            self.table.weapon_index(&item.weapon)?;
+        }
+        Ok(())
+    }
+
+    /// How many times each weapon in the table can be used, or None for no limit.
+    fn uses(&self) -> Vec<Option<u32>> {
+        let mut uses = vec![Some(0); self.table.weapons.len()];
+        for item in &self.items {
+            if let Ok(weapon) = self.table.weapon_index(&item.weapon) {
+                uses[weapon] = match (uses[weapon], item.durability) {
+                    (Some(uses), Some(durability)) => Some(uses + durability),
+                    _ => None,
+                };
+            }
+        }
+        uses
+    }
+}
+
+/// The weapon an attack is made with.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Assignment {
+    pub weapon: String,
+    /// The damage the attack deals, not counting damage beyond an enemy's HP.
+    pub damage: u32,
+}
+
+/// The weapons to make a solution's attacks with.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Loadout {
+    /// The weapon for each attack in `Solution::attacks`, in order.
+    pub assignments: Vec<Assignment>,
+    pub total_damage: u32,
+    /// How much durability the attacks wear off the player's items.
+    pub durability_used: u32,
+    pub all_defeated: bool,
+}
+
+/// The search for the best weapon for each attack.
+struct Assigner<'a> {
+    inventory: &'a Inventory,
+    attacks: &'a [Attack],
+    types: HpGrid,
+    /// The best score so far, and its loadout.
+    best: Option<((u32, u32, u32), Loadout)>,
+}
+
+impl Assigner<'_> {
+    /// Higher is better.
+    fn score(&self, dealt: u32, used: u32, all_defeated: bool) -> (u32, u32, u32) {
+        match self.inventory.priority {
+            Priority::Damage => (dealt, u32::MAX - used, 0),
+            Priority::Durability => (all_defeated as u32, u32::MAX - used, dealt),
+        }
+    }
+
+    fn assign(
+        &mut self,
+        hp: HpGrid,
+        uses: &mut [Option<u32>],
+        chosen: &mut Vec<(usize, u32)>,
+        used: u32,
+    ) {
+        let step = chosen.len();
+        let Some(attack) = self.attacks.get(step) else {
+            let total_damage = chosen.iter().map(|&(_, damage)| damage).sum();
+            let all_defeated = hp.iter().flatten().all(|&hp| hp == 0);
+            let score = self.score(total_damage, used, all_defeated);
+            if self.best.as_ref().is_none_or(|(best, _)| score > *best) {
+                let assignments = chosen.iter().map(|&(weapon, damage)| Assignment {
+                    weapon: self.inventory.table.weapons[weapon].name.clone(),
+                    damage,
+                });
+                let assignments = assignments.collect();
+                let durability_used = used;
+                let loadout = Loadout { assignments, total_damage, durability_used, all_defeated };
+                self.best = Some((score, loadout));
+            }
+            return;
+        };
+        let (kind, th) = match *attack {
+            Attack::Jump { th } => (WeaponKind::Jump, th),
+            Attack::Hammer { th } => (WeaponKind::Hammer, th),
+        };
+        let table = &self.inventory.table;
+        for weapon in 0..table.weapons.len() {
+            if table.weapons[weapon].kind != kind || uses[weapon] == Some(0) {
+                continue;
+            }
+            let damage = match table.damage(weapon, &hp, &self.types, th as usize) {
+                Some(damage) => damage,
+                None => continue,
+            };
+            let mut after = hp;
+            for (hp, damage) in after.iter_mut().flatten().zip(damage.iter().flatten()) {
+                *hp -= damage;
+            }
+            let dealt = damage.iter().flatten().map(|&damage| damage as u32).sum();
+            let left = uses[weapon];
+            uses[weapon] = left.map(|left| left - 1);
+            chosen.push((weapon, dealt));
+            self.assign(after, uses, chosen, used + left.is_some() as u32);
+            chosen.pop();
+            uses[weapon] = left;
+        }
+    }
+}
+
+/// The best weapons to make `attacks` with on `ring`, reached by `moves`, or None if some
+/// attack can't be made with any weapon the player has.
+pub fn assign_weapons(
+    attacks: &[Attack],
+    ring: Ring,
+    moves: &[RingMovement],
+    inventory: &Inventory,
+) -> Option<Loadout> {
+    let types = moved_hp(&inventory.types, moves);
+    let hp = inventory.table.hp(&ring, &types);
+    let mut assigner = Assigner { inventory, attacks, types, best: None };
+    assigner.assign(hp, &mut inventory.uses(), &mut Vec::new(), 0);
+    assigner.best.map(|(_, loadout)| loadout)
+}
@@ -0,0 +1,148 @@
+//! Consumable items the player can use in place of an attack: a POW Block hits every enemy
+//! wherever it stands, and a Fire Flower burns a wide arc.
+//!
+//! ```json
+//! {"powBlocks": 0, "fireFlowers": 2}
+//! ```
+//!
+//! Each item used takes one of the player's actions. Items are only spent when they let
+//! the board be cleared in fewer turns than it could be without them.
+
+use crate::evaluator::Evaluator;
+use crate::preset::Rules;
+use crate::{
+    input_from_js, solve_to_js, Result, Ring, Search, Solution, SolveOptions, MAX_TURNS,
+    NUM_ANGLES, NUM_RINGS,
+};
+use serde::{Deserialize, Serialize};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// How many angles a Fire Flower burns, starting from the one it's aimed at and going
+/// clockwise. It burns every ring of those angles.
+pub const FIRE_FLOWER_ARC: u16 = 3;
+
+/// The items the player is carrying.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ItemBag {
+    pub pow_blocks: u32,
+    pub fire_flowers: u32,
+}
+
+/// An item used instead of an attack.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ItemUse {
+    /// A POW Block, which hits every enemy.
+    PowBlock,
+    /// A Fire Flower aimed at angle `th`.
+    FireFlower { th: u16 },
+}
+
+/// The cells a Fire Flower aimed at `th` burns.
+fn fire_arc(th: u16) -> u16 {
+    (0..FIRE_FLOWER_ARC).fold(0, |arc, i| arc | 1 << ((th + i) % NUM_ANGLES))
+}
+
+/// Every enemy can be hit with the actions and weapons of `rules`, using items from `bag`
+/// in place of some of the attacks. The distance is the same as for `rules`, for the best
+/// items to use.
+pub struct ItemGoal {
+    bag: ItemBag,
+    rules: Rules,
+}
+
+impl ItemGoal {
+    pub fn new(bag: ItemBag, rules: Rules) -> Self {
+        ItemGoal { bag, rules }
+    }
+
+    /// The best items to use on `ring`, with the distance left, and the enemies left for
+    /// the attacks.
+    pub fn plan(&self, ring: Ring) -> (u32, Vec<ItemUse>, Ring) {
+        let actions = self.rules.actions_for(ring);
+        if self.bag.pow_blocks > 0 && actions > 0 {
+            return (0, vec![ItemUse::PowBlock], [0; NUM_RINGS as usize]);
+        }
+        let flowers = self.bag.fire_flowers.min(actions);
+        let mut best = (self.rules.distance(ring), Vec::new(), ring);
+        self.burn(ring, actions, flowers, 0, &mut Vec::new(), &mut best);
+        best
+    }
+
+    /// Tries burning `ring` with up to `flowers` more Fire Flowers, aimed at `from` or
+    /// later, since the order they're used in doesn't matter.
+    fn burn(
+        &self,
+        ring: Ring,
+        actions: u32,
+        flowers: u32,
+        from: u16,
+        used: &mut Vec<ItemUse>,
+        best: &mut (u32, Vec<ItemUse>, Ring),
+    ) {
+        if flowers == 0 || best.0 == 0 {
+            return;
+        }
+        for th in from..NUM_ANGLES {
+            let arc = fire_arc(th);
+            if ring.iter().all(|&subring| subring & arc == 0) {
+                continue;
+            }
+            let rest = ring.map(|subring| subring & !arc);
+            used.push(ItemUse::FireFlower { th });
+            // With no actions left, every enemy has to have been burned.
+            let left = actions - used.len() as u32;
+            let distance = Rules { actions: Some(left), ..self.rules }.distance(rest);
+            if distance < best.0 {
+                *best = (distance, used.clone(), rest);
+            }
+            self.burn(rest, actions, flowers - 1, th, used, best);
+            used.pop();
+        }
+    }
+}
+
+impl Evaluator for ItemGoal {
+    fn distance(&self, ring: Ring) -> u32 {
+        self.plan(ring).0
+    }
+}
+
+/// Finds the best way to clear `ring` with the help of the items in `bag`, with the same
+/// ranking as `solve`, and the items to use. If the board can be cleared just as quickly
+/// without them, no items are used.
+pub fn find_solution(
+    ring: Ring,
+    bag: ItemBag,
+    options: &SolveOptions,
+) -> std::result::Result<Option<Solution>, String> {
+    let rules = options.battle_rules()?;
+    let goal = Rc::new(ItemGoal::new(bag, rules));
+    let mut search = Search::new(options).with_evaluator(goal.clone());
+    let mut solution = match search.find_solution(ring, MAX_TURNS) {
+        Some(solution) => solution,
+        None => return Ok(None),
+    };
+    if solution.perfect {
+        let turns = solution.moves.len() as u16;
+        if let Some(without) = Search::new(options).find_solution(ring, turns) {
+            if without.perfect {
+                return Ok(Some(without));
+            }
+        }
+    }
+    let (_, items, rest) = goal.plan(solution.result);
+    solution.attacks = rules.attack_plan(rest);
+    solution.items = items;
+    Ok(Some(solution))
+}
+
+/// Solve a board with the help of items, given as `{powBlocks, fireFlowers}` counts.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_with_items(ring: JsValue, bag: JsValue, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let bag: ItemBag = serde_wasm_bindgen::from_value(bag)?;
+    solve_to_js(find_solution(ring, bag, &options)?, &options)
+}
@@ -0,0 +1,230 @@
+//! Large enemies, which take up several adjacent panels and move as one.
+//!
+//! Each large enemy is given as a board of the panels it covers. A movement can only move
+//! all of a large enemy or none of it, and has to leave its panels adjacent, e.g. rotating
+//! the ring a wide enemy stands in, or shifting the row a tall one stands in without
+//! wrapping it around the end. Hitting any panel of a large enemy hits all of it, and it
+//! counts as a single enemy for the actions the player gets.
+//!
+//! ```json
+//! [[3, 0, 0, 0], [0, 0, 256, 256]]
+//! ```
+
+use crate::evaluator::Evaluator;
+use crate::preset::Rules;
+use crate::{
+    apply_movement, check_ring, input_from_js, panels_of, solve_to_js, Result, Ring,
+    RingMovement, Search, Solution, SolveOptions, MAX_TURNS, NUM_ANGLES, NUM_RINGS,
+};
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// The most large enemies a board can have. Every choice of which panel of each is hit
+/// gets evaluated, so this keeps that manageable.
+pub const MAX_LARGE_ENEMIES: usize = 4;
+
+/// The most panels a single large enemy can take up.
+pub const MAX_PANELS: u32 = 4;
+
+/// A board with large enemies.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LargeBoard {
+    /// Every panel with an enemy, including those of large enemies.
+    pub ring: Ring,
+    /// The panels of each large enemy.
+    pub large: Vec<Ring>,
+}
+
+impl LargeBoard {
+    /// Checks that each large enemy takes up adjacent panels that have enemies, and that
+    /// no two overlap.
+    pub fn new(ring: Ring, large: Vec<Ring>) -> std::result::Result<Self, String> {
+        check_ring(&ring)?;
+        if large.len() > MAX_LARGE_ENEMIES {
+            return Err(format!("at most {} large enemies are supported", MAX_LARGE_ENEMIES));
+        }
+        let mut covered = [0; NUM_RINGS as usize];
+        for (i, panels) in large.iter().enumerate() {
+            check_ring(panels)?;
+            let size: u32 = panels.iter().map(|subring| subring.count_ones()).sum();
+            if !(2..=MAX_PANELS).contains(&size) {
+                return Err(format!(
+                    "large enemy {} takes up {} panels, but must take up 2 to {}",
+                    i, size, MAX_PANELS
+                ));
+            }
+            if (0..NUM_RINGS as usize).any(|r| panels[r] & !ring[r] != 0) {
+                return Err(format!("large enemy {} is on panels without an enemy", i));
+            }
+            if (0..NUM_RINGS as usize).any(|r| panels[r] & covered[r] != 0) {
+                return Err(format!("large enemy {} overlaps another", i));
+            }
+            if !is_connected(*panels) {
+                return Err(format!("large enemy {} isn't on adjacent panels", i));
+            }
+            for (covered, subring) in covered.iter_mut().zip(panels) {
+                *covered |= subring;
+            }
+        }
+        Ok(LargeBoard { ring, large })
+    }
+
+    /// The board after `movement`, or None if it would break up a large enemy.
+    pub fn apply(&self, movement: &RingMovement) -> Option<LargeBoard> {
+        let moved = moved_panels(movement);
+        let mut large = self.large.clone();
+        for panels in &mut large {
+            let some = panels.iter().zip(&moved).any(|(panels, moved)| panels & moved != 0);
+            let all = panels.iter().zip(&moved).all(|(panels, moved)| panels & !moved == 0);
+            if some && !all {
+                return None;
+            }
+            *panels = apply_movement(*panels, movement);
+            if !is_connected(*panels) {
+                return None;
+            }
+        }
+        Some(LargeBoard { ring: apply_movement(self.ring, movement), large })
+    }
+
+    /// Every board with one panel of each large enemy, and none of their other panels,
+    /// which is each way the attacks could hit them.
+    fn representatives(&self) -> Vec<Ring> {
+        let mut rest = self.ring;
+        for panels in &self.large {
+            for (subring, panels) in rest.iter_mut().zip(panels) {
+                *subring &= !panels;
+            }
+        }
+        let mut boards = vec![rest];
+        for panels in &self.large {
+            boards = boards
+                .iter()
+                .flat_map(|board| {
+                    panels_of(*panels).map(move |(r, th)| {
+                        let mut board = *board;
+                        board[r] |= 1 << th;
+                        board
+                    })
+                })
+                .collect();
+        }
+        boards
+    }
+}
+
+/// The panels a movement moves, if it moves anything at all.
+fn moved_panels(movement: &RingMovement) -> Ring {
+    let mut moved = [0; NUM_RINGS as usize];
+    match *movement {
+        RingMovement::Ring { amount, .. } if amount % NUM_ANGLES as i16 == 0 => {}
+        RingMovement::Ring { r, .. } => moved[r as usize] = (1 << NUM_ANGLES) - 1,
+        RingMovement::Row { amount, .. } if amount % (NUM_RINGS * 2) as i16 == 0 => {}
+        RingMovement::Row { th, .. } => {
+            let th = th % (NUM_ANGLES / 2);
+            moved = [1 << th | 1 << (th + NUM_ANGLES / 2); NUM_RINGS as usize];
+        }
+    }
+    moved
+}
+
+/// Whether the panels of `cells` are all connected through panels next to each other in
+/// the same ring or the same angle.
+fn is_connected(cells: Ring) -> bool {
+    let first = match (0..NUM_RINGS as usize).find(|&r| cells[r] != 0) {
+        Some(r) => r,
+        None => return true,
+    };
+    let mut reached = [0u16; NUM_RINGS as usize];
+    reached[first] = cells[first] & cells[first].wrapping_neg();
+    loop {
+        let mut grown = reached;
+        for r in 0..NUM_RINGS as usize {
+            let subring = reached[r];
+            let mask = (1 << NUM_ANGLES) - 1;
+            grown[r] |= (subring << 1 | subring >> (NUM_ANGLES - 1)) & mask;
+            grown[r] |= subring >> 1 | (subring & 1) << (NUM_ANGLES - 1);
+            if r > 0 {
+                grown[r - 1] |= subring;
+            }
+            if r + 1 < NUM_RINGS as usize {
+                grown[r + 1] |= subring;
+            }
+        }
+        for (grown, cells) in grown.iter_mut().zip(&cells) {
+            *grown &= cells;
+        }
+        if grown == reached {
+            return reached == cells;
+        }
+        reached = grown;
+    }
+}
+
+/// Every enemy can be hit with the actions and weapons of `rules`, where hitting any panel
+/// of a large enemy hits all of it, and no movement breaks up a large enemy. The distance
+/// is the same as for `rules`, for the best panels to hit.
+pub struct LargeEnemyGoal {
+    start: LargeBoard,
+    rules: Rules,
+}
+
+impl LargeEnemyGoal {
+    pub fn new(start: LargeBoard, rules: Rules) -> Self {
+        LargeEnemyGoal { start, rules }
+    }
+
+    /// The board after `moves`, or None if one of them breaks up a large enemy.
+    fn board_after(&self, moves: &[RingMovement]) -> Option<LargeBoard> {
+        moves.iter().try_fold(self.start.clone(), |board, movement| board.apply(movement))
+    }
+}
+
+impl Evaluator for LargeEnemyGoal {
+    fn distance(&self, ring: Ring) -> u32 {
+        self.distance_after(ring, &[])
+    }
+
+    fn distance_after(&self, ring: Ring, moves: &[RingMovement]) -> u32 {
+        let board = match self.board_after(moves) {
+            Some(board) => board,
+            // Breaking up a large enemy isn't allowed, so it's never a solution, and any
+            // other board is closer.
+            None => return u32::MAX,
+        };
+        debug_assert_eq!(board.ring, ring);
+        board
+            .representatives()
+            .into_iter()
+            .map(|board| self.rules.distance(board))
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn allows(&self, moves: &[RingMovement]) -> bool {
+        self.board_after(moves).is_some()
+    }
+}
+
+/// Finds the best way to clear a board with large enemies, with the same ranking as
+/// `solve`.
+pub fn find_solution(
+    board: LargeBoard,
+    options: &SolveOptions,
+) -> std::result::Result<Option<Solution>, String> {
+    let rules = options.battle_rules()?;
+    let ring = board.ring;
+    let goal = Rc::new(LargeEnemyGoal::new(board, rules));
+    Ok(Search::new(options).with_evaluator(goal).find_solution(ring, MAX_TURNS))
+}
+
+/// Solve a board with large enemies, given as an array of boards of the panels each one
+/// takes up. The panels of large enemies must also have enemies in `ring`.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_with_large_enemies(ring: JsValue, large: JsValue, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let large: Vec<Ring> = serde_wasm_bindgen::from_value(large)?;
+    large.iter().try_for_each(check_ring)?;
+    let large = large.into_iter().map(|cells| options.orientation.board_in(cells)).collect();
+    solve_to_js(find_solution(LargeBoard::new(ring, large)?, &options)?, &options)
+}
@@ -0,0 +1,84 @@
+//! Planning for no-damage challenge runs: every enemy has to be cleared this round, so
+//! none survive to counterattack, and among those plans the one with the fewest turns and
+//! then the biggest coin bonus is best. The coin bonus is estimated by `rewards`, with the
+//! rates, weapons, timer, and accessories of the options.
+
+use crate::{
+    input_from_js, rewards, with_spoilers, Result, Ring, Search, Solution, SolveOptions, MAX_TURNS,
+};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// How many of the best-ranked plans with the fewest turns to compare coin bonuses of.
+const CANDIDATES: usize = 8;
+
+/// The coins `solution` is expected to earn with `options`.
+pub fn coin_bonus(solution: &Solution, options: &SolveOptions) -> u32 {
+    rewards::estimate_with(solution, options).coins
+}
+
+/// Why there's no plan that clears every enemy this round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum NoDamageFailure {
+    /// No board within the turn limit lets every enemy be hit.
+    Unsolvable { max_turns: u16 },
+    /// The search ran out of budget before finding a plan.
+    OutOfBudget,
+}
+
+/// The best plan for a no-damage run, or why there isn't one.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NoDamagePlan {
+    pub solution: Option<Solution>,
+    /// The estimated coin bonus of `solution`.
+    pub coin_bonus: Option<u32>,
+    pub failure: Option<NoDamageFailure>,
+}
+
+/// Finds the plan that clears every enemy with the fewest turns, then the biggest coin
+/// bonus. Imperfect boards are never returned, since surviving enemies would counterattack.
+/// Fails with `spoiler_free`, since the plan has the moves.
+pub fn plan(ring: Ring, options: &SolveOptions) -> std::result::Result<NoDamagePlan, String> {
+    with_spoilers(options)?;
+    let mut search = Search::new(options);
+    let candidates = search.find_solutions(ring, MAX_TURNS, CANDIDATES);
+    let perfect: Vec<Solution> =
+        candidates.into_iter().filter(|solution| solution.perfect).collect();
+    let fewest_turns = perfect.iter().map(|solution| solution.moves.len()).min();
+    // Candidates are ranked best first, so ties keep the usual ranking.
+    let best = perfect
+        .into_iter()
+        .filter(|solution| Some(solution.moves.len()) == fewest_turns)
+        .min_by_key(|solution| std::cmp::Reverse(coin_bonus(solution, options)));
+    Ok(match best {
+        Some(solution) => NoDamagePlan {
+            coin_bonus: Some(coin_bonus(&solution, options)),
+            solution: Some(solution),
+            failure: None,
+        },
+        None => NoDamagePlan {
+            solution: None,
+            coin_bonus: None,
+            failure: Some(if search.budget.truncated {
+                NoDamageFailure::OutOfBudget
+            } else {
+                // Options from JavaScript are checked as they're read.
+                let max_turns = options.battle_rules().unwrap_or_default().max_turns;
+                NoDamageFailure::Unsolvable { max_turns }
+            }),
+        },
+    })
+}
+
+/// Find the best plan for a no-damage run: every enemy cleared this round, with the
+/// fewest turns and then the biggest coin bonus. Returns a `NoDamagePlan`, with the reason
+/// if there's no such plan.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_no_damage(ring: JsValue, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let plan = plan(ring, &options)?;
+    let solution = plan.solution.map(|solution| options.orientation.solution_out(solution));
+    Ok(serde_wasm_bindgen::to_value(&NoDamagePlan { solution, ..plan })?)
+}
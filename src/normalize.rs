@@ -0,0 +1,87 @@
+//! Writing move sequences one way, so sequences a player enters can be compared with the
+//! solver's.
+//!
+//! A movement is written the way the search makes it: rings turned by less than half a
+//! turn either way, or by half a turn clockwise, and rows at angles below 6 shifted by
+//! less than half their length either way, or by half of it outward. So rotating a ring by
+//! 11 clockwise is written as rotating it by 1 counterclockwise. Consecutive movements of
+//! the same ring or row are merged into one, and movements that do nothing are dropped.
+
+use crate::{check_movement, Result, RingMovement, NUM_ANGLES, NUM_RINGS};
+use wasm_bindgen::prelude::*;
+
+/// Which ring or row a movement moves, and how far, as a signed amount clockwise or
+/// outward from an angle below 6.
+fn signed(movement: &RingMovement) -> (RingMovement, i32) {
+    match *movement {
+        RingMovement::Ring { r, amount, clockwise } => {
+            let target = RingMovement::Ring { r, amount: 0, clockwise: true };
+            (target, if clockwise { amount as i32 } else { -(amount as i32) })
+        }
+        RingMovement::Row { th, amount, outward } => {
+            let half = NUM_ANGLES / 2;
+            // The row at `th + 6` is the same row, shifted the other way.
+            let outward = outward != (th % NUM_ANGLES >= half);
+            let target = RingMovement::Row { th: th % half, amount: 0, outward: true };
+            (target, if outward { amount as i32 } else { -(amount as i32) })
+        }
+    }
+}
+
+/// `target` moved by `amount`, written the way the search would, or None if that does
+/// nothing.
+fn unsigned(target: RingMovement, amount: i32) -> Option<RingMovement> {
+    let length = match target {
+        RingMovement::Ring { .. } => NUM_ANGLES,
+        RingMovement::Row { .. } => NUM_RINGS * 2,
+    } as i32;
+    let amount = amount.rem_euclid(length);
+    let (amount, forward) = match amount {
+        0 => return None,
+        amount if amount <= length / 2 => (amount as i16, true),
+        amount => ((length - amount) as i16, false),
+    };
+    Some(match target {
+        RingMovement::Ring { r, .. } => RingMovement::Ring { r, amount, clockwise: forward },
+        RingMovement::Row { th, .. } => RingMovement::Row { th, amount, outward: forward },
+    })
+}
+
+/// `movement` written the way the search would make it, or None if it does nothing.
+pub fn normalize_movement(movement: &RingMovement) -> Option<RingMovement> {
+    let (target, amount) = signed(movement);
+    unsigned(target, amount)
+}
+
+/// `moves` with each movement normalized, consecutive movements of the same ring or row
+/// merged, and movements that do nothing dropped. The result moves every ring and row the
+/// same way `moves` do.
+pub fn normalize_moves<'m>(moves: impl IntoIterator<Item = &'m RingMovement>) -> Vec<RingMovement> {
+    let mut merged: Vec<(RingMovement, i32)> = Vec::new();
+    for movement in moves {
+        let (target, amount) = signed(movement);
+        match merged.last_mut() {
+            Some((last, total)) if *last == target => *total += amount,
+            _ => merged.push((target, amount)),
+        }
+        // Merging may have undone the last movement, which lets the one before it merge
+        // with whatever comes next.
+        if let Some(&(target, total)) = merged.last() {
+            if unsigned(target, total).is_none() {
+                merged.pop();
+            }
+        }
+    }
+    merged.into_iter().filter_map(|(target, amount)| unsigned(target, amount)).collect()
+}
+
+/// Normalize a list of movements, merging and dropping movements so it can be compared
+/// with the solver's.
+#[wasm_bindgen(js_name = normalize_moves, skip_typescript)]
+pub fn normalize_moves_js(moves: JsValue) -> Result<JsValue> {
+    let moves: Vec<RingMovement> = serde_wasm_bindgen::from_value(moves)?;
+    for movement in &moves {
+        check_movement(movement)?;
+    }
+    Ok(serde_wasm_bindgen::to_value(&normalize_moves(&moves))?)
+}
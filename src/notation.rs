@@ -0,0 +1,73 @@
+//! A short text notation for movements, for guides and chat.
+//!
+//! - `R2+3` rotates ring 2 clockwise by 3, and `R2-3` rotates it counterclockwise.
+//!   Rings are numbered from 0, the innermost.
+//! - `C4>2` shifts the row, or column, at angle 4 outward by 2, and `C4<2` shifts it
+//!   inward. Angles are numbered from 0 to 11 clockwise.
+//!
+//! A negative amount is written as the same amount the other way, so `R2+3` is written for
+//! a counterclockwise rotation by -3. Parsing ignores case and surrounding whitespace.
+
+use crate::{check_movement, Result, RingMovement};
+use std::fmt;
+use std::str::FromStr;
+use wasm_bindgen::prelude::*;
+
+impl fmt::Display for RingMovement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (letter, index, amount, forward, signs) = match *self {
+            RingMovement::Ring { r, amount, clockwise } => ('R', r, amount, clockwise, "+-"),
+            RingMovement::Row { th, amount, outward } => ('C', th, amount, outward, "><"),
+        };
+        let forward = forward == (amount >= 0);
+        let sign = if forward { &signs[..1] } else { &signs[1..] };
+        write!(f, "{}{}{}{}", letter, index, sign, amount.unsigned_abs())
+    }
+}
+
+impl FromStr for RingMovement {
+    type Err = String;
+
+    fn from_str(text: &str) -> std::result::Result<Self, String> {
+        let trimmed = text.trim();
+        let invalid = || format!("{:?} isn't a movement like R2+3 or C4>2", text);
+        let mut chars = trimmed.chars();
+        let letter = chars.next().ok_or_else(invalid)?.to_ascii_uppercase();
+        let rest = chars.as_str();
+        let split = rest.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+        let (index, rest) = rest.split_at(split);
+        let mut rest = rest.chars();
+        let sign = rest.next().ok_or_else(invalid)?;
+        let index: u16 = index.parse().map_err(|_| invalid())?;
+        let amount = rest.as_str();
+        if !amount.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let amount: i16 = amount.parse().map_err(|_| invalid())?;
+        let movement = match (letter, sign) {
+            ('R', '+') | ('R', '-') => {
+                RingMovement::Ring { r: index, amount, clockwise: sign == '+' }
+            }
+            ('C', '>') | ('C', '<') => {
+                RingMovement::Row { th: index, amount, outward: sign == '>' }
+            }
+            _ => return Err(invalid()),
+        };
+        check_movement(&movement)?;
+        Ok(movement)
+    }
+}
+
+/// Write a movement in the text notation, e.g. `R2+3`.
+#[wasm_bindgen(skip_typescript)]
+pub fn format_move(movement: JsValue) -> Result<String> {
+    let movement: RingMovement = serde_wasm_bindgen::from_value(movement)?;
+    Ok(movement.to_string())
+}
+
+/// Read a movement written in the text notation, e.g. `C4>2`.
+#[wasm_bindgen(skip_typescript)]
+pub fn parse_move(text: &str) -> Result<JsValue> {
+    let movement: RingMovement = text.parse()?;
+    Ok(serde_wasm_bindgen::to_value(&movement)?)
+}
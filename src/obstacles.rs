@@ -0,0 +1,172 @@
+//! Boards with holes or obstacles: panels that can't hold an enemy, and that a row can't
+//! be shifted through.
+//!
+//! ```json
+//! {"ring": [1, 0, 0, 1], "blocked": [0, 2, 0, 0]}
+//! ```
+//!
+//! A blocked panel turns with its ring like any other, but no row that crosses it can be
+//! shifted, so which rows can move changes as the rings turn. Since enemies only ever
+//! move with their ring or row, none can end up on a blocked panel.
+
+use crate::binding::{self, AvoidedHazard, Hazard};
+use crate::exhaustive::{Exhaustive, Moves};
+use crate::{
+    apply_movement, check_ring, evaluate, get_row, goal, iterate_movements,
+    oriented_options_from_js, solve_to_js, Budget, MoveConstraints, Result, Ring,
+    RingMovement, Search, Solution, SolveOptions, MAX_TURNS, NUM_ANGLES, NUM_RINGS,
+};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// A board and its blocked panels, as a `Ring` each.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ObstructedBoard {
+    pub ring: Ring,
+    pub blocked: Ring,
+}
+
+impl ObstructedBoard {
+    /// Checks that both rings are valid, and no enemy is on a blocked panel.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        check_ring(&self.ring)?;
+        check_ring(&self.blocked)?;
+        for r in 0..NUM_RINGS as usize {
+            let both = self.ring[r] & self.blocked[r];
+            if both != 0 {
+                let th = both.trailing_zeros();
+                return Err(format!("ring {} angle {} is blocked, but has an enemy", r, th));
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether the row at `th` crosses a blocked panel, so it can't be shifted.
+    pub fn row_blocked(&self, th: u16) -> bool {
+        get_row(&self.blocked, th % (NUM_ANGLES / 2)).0 != 0
+    }
+
+    /// The board after `movement`, or None if it shifts a row through a blocked panel.
+    pub fn moved(&self, movement: &RingMovement) -> Option<Self> {
+        if let RingMovement::Row { th, .. } = *movement {
+            if self.row_blocked(th) {
+                return None;
+            }
+        }
+        Some(ObstructedBoard {
+            ring: apply_movement(self.ring, movement),
+            blocked: apply_movement(self.blocked, movement),
+        })
+    }
+}
+
+/// Moves boards with blocked panels, never shifting through them.
+struct ObstructedMoves;
+
+impl Moves for ObstructedMoves {
+    type Board = ObstructedBoard;
+    type Error = Infallible;
+
+    fn each_movement(
+        &self,
+        board: &ObstructedBoard,
+        _turns: usize,
+        constraints: &MoveConstraints,
+        mut visit: impl FnMut(RingMovement, ObstructedBoard) -> std::result::Result<(), Infallible>,
+    ) -> std::result::Result<(), Infallible> {
+        // Turning a ring with only blocked panels on it can still free up a row.
+        let occupied: Ring = std::array::from_fn(|r| board.ring[r] | board.blocked[r]);
+        iterate_movements(occupied, constraints, |movement, _| {
+            let moved = board.moved(&movement)?;
+            visit(movement, moved).err()
+        });
+        Ok(())
+    }
+
+    fn first_moved(
+        &self,
+        board: &ObstructedBoard,
+        movement: &RingMovement,
+    ) -> std::result::Result<Option<ObstructedBoard>, Infallible> {
+        Ok(board.moved(movement))
+    }
+}
+
+/// Finds the fewest turns that clear `board` without shifting through its blocked panels,
+/// then the smallest total distance. Returns None if nothing within the turn limit clears
+/// it, or the search ran out of budget first.
+pub fn find_solution(
+    board: &ObstructedBoard,
+    options: &SolveOptions,
+) -> std::result::Result<Option<Solution>, String> {
+    board.check()?;
+    let rules = options.battle_rules()?;
+    let goal = goal(options, rules).unwrap_or_else(|| Rc::new(rules));
+    let mut search = Exhaustive::new(&ObstructedMoves, options);
+    let max_turns = rules.max_turns.min(MAX_TURNS);
+    let Ok(found) = search.find_shortest(board, max_turns, |board, path| {
+        goal.distance_after(board.ring, path) == 0
+    });
+    Ok(found.map(|(moves, result)| {
+        let mut solution = Solution::new(moves, evaluate(result.ring), false);
+        solution.perfect = true;
+        solution.attacks = rules.attack_plan(result.ring);
+        solution.hazards = blocked_hazards(board, &solution, options, &mut search.budget);
+        solution
+    }))
+}
+
+/// Finds the shifts through blocked panels that stopped a better solution than `solution`
+/// from being chosen, by searching again as if no panel were blocked, in what's left of
+/// `budget`.
+fn blocked_hazards(
+    board: &ObstructedBoard,
+    solution: &Solution,
+    options: &SolveOptions,
+    budget: &mut Budget,
+) -> Vec<AvoidedHazard> {
+    let mut search = Search::new(options);
+    search.budget = budget.remaining();
+    let better = search.find_solution(board.ring, solution.moves.len() as u16);
+    budget.nodes += search.budget.nodes;
+    let better = match better {
+        Some(better) if better.perfect => better,
+        _ => return Vec::new(),
+    };
+    let turns_saved = match binding::turns_saved(&better, solution) {
+        Some(turns_saved) => turns_saved,
+        None => return Vec::new(),
+    };
+    let mut board = *board;
+    let mut hazards = Vec::new();
+    for movement in &better.moves {
+        if board.moved(movement).is_none() {
+            hazards.push(AvoidedHazard {
+                hazard: Hazard::Blocked,
+                attack: None,
+                movement: Some(*movement),
+                turns_saved,
+            });
+        }
+        board.ring = apply_movement(board.ring, movement);
+        board.blocked = apply_movement(board.blocked, movement);
+    }
+    hazards
+}
+
+/// Solve a board with blocked panels, given as `{ring, blocked}`.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_obstructed(board: JsValue, options: JsValue) -> Result<JsValue> {
+    let board: ObstructedBoard = serde_wasm_bindgen::from_value(board)?;
+    board.check()?;
+    let options = oriented_options_from_js(options)?;
+    let orientation = options.orientation;
+    let board = ObstructedBoard {
+        ring: orientation.board_in(board.ring),
+        blocked: orientation.board_in(board.blocked),
+    };
+    solve_to_js(find_solution(&board, &options)?, &options)
+}
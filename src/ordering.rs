@@ -0,0 +1,128 @@
+//! The order the search tries movements in.
+//!
+//! With the backends that rank solutions, the order never changes how good the solution
+//! found is, only how fast it's found: a better order finds good solutions sooner, which
+//! lets the search prune more of the remaining boards.
+
+use crate::generator::XorShift;
+use crate::{evaluate, get_row, iterate_movements, MoveConstraints, Ring, RingMovement};
+use serde::Deserialize;
+use std::rc::Rc;
+
+/// Chooses the order movements are searched in.
+pub trait MoveOrder {
+    /// Calls `visit` with each movement allowed by `constraints` and the board it makes
+    /// from `ring`, in the order they should be searched, stopping at the first `Some`.
+    fn visit(
+        &self,
+        ring: Ring,
+        constraints: &MoveConstraints,
+        visit: &mut dyn FnMut(RingMovement, Ring) -> Option<()>,
+    ) -> Option<()>;
+}
+
+/// The default order: the smallest movements first, alternating between rotations and
+/// shifts. See `iterate_movements`.
+pub struct ZigZag;
+
+impl MoveOrder for ZigZag {
+    fn visit(
+        &self,
+        ring: Ring,
+        constraints: &MoveConstraints,
+        visit: &mut dyn FnMut(RingMovement, Ring) -> Option<()>,
+    ) -> Option<()> {
+        iterate_movements(ring, constraints, visit)
+    }
+}
+
+/// Visits every movement in zig-zag order, stably sorted by `key`.
+fn visit_sorted<K: Ord>(
+    ring: Ring,
+    constraints: &MoveConstraints,
+    mut key: impl FnMut(&RingMovement, &Ring) -> K,
+    visit: &mut dyn FnMut(RingMovement, Ring) -> Option<()>,
+) -> Option<()> {
+    let mut moves = Vec::new();
+    iterate_movements(ring, constraints, |movement, moved| {
+        moves.push((movement, moved));
+        None::<()>
+    });
+    moves.sort_by_cached_key(|(movement, moved)| key(movement, moved));
+    moves.into_iter().find_map(|(movement, moved)| visit(movement, moved))
+}
+
+/// Moves the rings and rows with the most enemies first.
+pub struct LargestRingFirst;
+
+impl MoveOrder for LargestRingFirst {
+    fn visit(
+        &self,
+        ring: Ring,
+        constraints: &MoveConstraints,
+        visit: &mut dyn FnMut(RingMovement, Ring) -> Option<()>,
+    ) -> Option<()> {
+        let enemies = |movement: &RingMovement, _: &Ring| match *movement {
+            RingMovement::Ring { r, .. } => std::cmp::Reverse(ring[r as usize].count_ones()),
+            RingMovement::Row { th, .. } => std::cmp::Reverse(get_row(&ring, th).0.count_ones()),
+        };
+        visit_sorted(ring, constraints, enemies, visit)
+    }
+}
+
+/// Makes the movements that leave the fewest extra actions first.
+pub struct AlignmentGainFirst;
+
+impl MoveOrder for AlignmentGainFirst {
+    fn visit(
+        &self,
+        ring: Ring,
+        constraints: &MoveConstraints,
+        visit: &mut dyn FnMut(RingMovement, Ring) -> Option<()>,
+    ) -> Option<()> {
+        let excess = |_: &RingMovement, moved: &Ring| evaluate(*moved).excess_actions();
+        visit_sorted(ring, constraints, excess, visit)
+    }
+}
+
+/// Shuffles the movements, differently for each board but the same for the same seed.
+/// Useful as a baseline for other orders.
+pub struct Randomized {
+    pub seed: u64,
+}
+
+impl MoveOrder for Randomized {
+    fn visit(
+        &self,
+        ring: Ring,
+        constraints: &MoveConstraints,
+        visit: &mut dyn FnMut(RingMovement, Ring) -> Option<()>,
+    ) -> Option<()> {
+        let board = ring.iter().fold(0u64, |bits, &subring| bits << 16 | subring as u64);
+        let mut rng = XorShift::new(self.seed ^ board);
+        visit_sorted(ring, constraints, |_, _| rng.next_u64(), visit)
+    }
+}
+
+/// Which `MoveOrder` a search uses, as chosen in `SolveOptions`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum MoveOrderKind {
+    #[default]
+    ZigZag,
+    LargestRingFirst,
+    AlignmentGainFirst,
+    Randomized { seed: u64 },
+}
+
+impl MoveOrderKind {
+    /// Creates the chosen order.
+    pub fn build(self) -> Rc<dyn MoveOrder> {
+        match self {
+            MoveOrderKind::ZigZag => Rc::new(ZigZag),
+            MoveOrderKind::LargestRingFirst => Rc::new(LargestRingFirst),
+            MoveOrderKind::AlignmentGainFirst => Rc::new(AlignmentGainFirst),
+            MoveOrderKind::Randomized { seed } => Rc::new(Randomized { seed }),
+        }
+    }
+}
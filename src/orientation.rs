@@ -0,0 +1,173 @@
+//! The orientation a frontend draws the board in, so it can pass boards and read solutions
+//! in its own angles instead of remapping them.
+//!
+//! ```json
+//! {"orientation": {"reference": 3, "counterclockwise": true}}
+//! ```
+//!
+//! The solver's angle 0 is where the frontend's angle `reference` is. With
+//! `counterclockwise`, the frontend's angles count up the other way around the board, and
+//! what it calls clockwise is what the solver calls counterclockwise.
+//!
+//! Every solve that takes a board reads it, and every angle in its options and other
+//! inputs, in the frontend's orientation, and gives back every angle of its solutions in
+//! it: the moves, the result, the attacks, the binding constraints and hazards, the
+//! partner's attack, and the Fire Flowers. The functions that can't, like `solve_with_goal` and `solve_boss`,
+//! fail if an orientation is given, instead of mixing the two up.
+
+use crate::symmetry::{mirror_board, mirror_movement, rotate_board, rotate_movement};
+use crate::hp::HpGrid;
+use crate::items::{ItemUse, FIRE_FLOWER_ARC};
+use crate::{Attack, Ring, RingMovement, Solution, SolveOptions, NUM_ANGLES};
+use serde::Deserialize;
+
+/// How a frontend's angles line up with the solver's.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Orientation {
+    /// The frontend's angle at the solver's angle 0.
+    pub reference: u16,
+    /// Whether the frontend's angles, and its clockwise, run the other way.
+    pub counterclockwise: bool,
+}
+
+impl Orientation {
+    /// Checks that `reference` is an angle.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        if self.reference >= NUM_ANGLES {
+            return Err(format!("reference angle {} is out of range", self.reference));
+        }
+        Ok(())
+    }
+
+    /// How far the solver's angles are rotated clockwise from the frontend's.
+    fn offset(&self) -> u16 {
+        (NUM_ANGLES - self.reference % NUM_ANGLES) % NUM_ANGLES
+    }
+
+    /// The solver's angle for the frontend's angle `th`.
+    pub fn angle_in(&self, th: u16) -> u16 {
+        match self.counterclockwise {
+            false => (th + self.offset()) % NUM_ANGLES,
+            true => (self.reference + NUM_ANGLES - th % NUM_ANGLES) % NUM_ANGLES,
+        }
+    }
+
+    /// The frontend's angle for the solver's angle `th`.
+    pub fn angle_out(&self, th: u16) -> u16 {
+        match self.counterclockwise {
+            false => (th + self.reference) % NUM_ANGLES,
+            // Mirroring is its own inverse.
+            true => self.angle_in(th),
+        }
+    }
+
+    /// The solver's board for a board in the frontend's angles.
+    pub fn board_in(&self, ring: Ring) -> Ring {
+        match self.counterclockwise {
+            false => rotate_board(ring, self.offset()),
+            true => mirror_board(ring, self.reference),
+        }
+    }
+
+    /// The frontend's board for a board in the solver's angles.
+    pub fn board_out(&self, ring: Ring) -> Ring {
+        match self.counterclockwise {
+            false => rotate_board(ring, self.reference),
+            true => mirror_board(ring, self.reference),
+        }
+    }
+
+    /// The solver's grid for a grid of values by ring and angle, like HP, in the frontend's
+    /// angles.
+    pub fn grid_in(&self, grid: HpGrid) -> HpGrid {
+        grid.map(|angles| {
+            let mut moved = angles;
+            for (th, &value) in angles.iter().enumerate() {
+                moved[self.angle_in(th as u16) as usize] = value;
+            }
+            moved
+        })
+    }
+
+    /// The solver's movement for a movement in the frontend's angles and directions.
+    pub fn movement_in(&self, movement: &RingMovement) -> RingMovement {
+        match self.counterclockwise {
+            false => rotate_movement(movement, self.offset()),
+            true => mirror_movement(movement, self.reference),
+        }
+    }
+
+    /// The frontend's movement for a movement in the solver's angles and directions.
+    pub fn movement_out(&self, movement: &RingMovement) -> RingMovement {
+        match self.counterclockwise {
+            false => rotate_movement(movement, self.reference),
+            true => mirror_movement(movement, self.reference),
+        }
+    }
+
+    /// The frontend's attack for an attack in the solver's angles. A hammer hits the angle
+    /// it's aimed at and the next one clockwise, so mirrored, it's aimed one angle over.
+    pub fn attack_out(&self, attack: Attack) -> Attack {
+        match attack {
+            Attack::Jump { th } => Attack::Jump { th: self.angle_out(th) },
+            Attack::Hammer { th } if self.counterclockwise => {
+                Attack::Hammer { th: self.angle_out((th + 1) % NUM_ANGLES) }
+            }
+            Attack::Hammer { th } => Attack::Hammer { th: self.angle_out(th) },
+        }
+    }
+
+    /// The frontend's item use for an item used in the solver's angles. A Fire Flower
+    /// burns the angle it's aimed at and the ones after it clockwise, so mirrored, it's
+    /// aimed at the last of them.
+    pub fn item_out(&self, item: ItemUse) -> ItemUse {
+        match item {
+            ItemUse::PowBlock => ItemUse::PowBlock,
+            ItemUse::FireFlower { th } if self.counterclockwise => ItemUse::FireFlower {
+                th: self.angle_out((th + FIRE_FLOWER_ARC - 1) % NUM_ANGLES),
+            },
+            ItemUse::FireFlower { th } => ItemUse::FireFlower { th: self.angle_out(th) },
+        }
+    }
+
+    /// `options` with everything given in the frontend's orientation turned to the
+    /// solver's.
+    pub fn options_in(&self, mut options: SolveOptions) -> SolveOptions {
+        let rows = options.constraints.rows;
+        options.constraints.rows = (0..NUM_ANGLES / 2)
+            .filter(|th| rows & (1 << th) != 0)
+            .fold(0, |rows, th| rows | 1 << (self.angle_in(th) % (NUM_ANGLES / 2)));
+        options.first_move = options.first_move.map(|movement| self.movement_in(&movement));
+        options.toads = options.toads.map(|toads| self.board_in(toads));
+        if let Some(battle) = &mut options.battle {
+            battle.hp = battle.hp.map(|hp| self.grid_in(hp));
+            battle.attack = battle.attack.map(|attack| self.grid_in(attack));
+            battle.priority = battle.priority.map(|priority| self.board_in(priority));
+        }
+        if let Some(inventory) = &mut options.inventory {
+            inventory.types = self.grid_in(inventory.types);
+        }
+        options
+    }
+
+    /// `solution` with every angle in it turned to the frontend's orientation.
+    pub fn solution_out(&self, mut solution: Solution) -> Solution {
+        solution.moves = solution.moves.iter().map(|movement| self.movement_out(movement)).collect();
+        solution.result = self.board_out(solution.result);
+        solution.attacks = solution.attacks.iter().map(|&attack| self.attack_out(attack)).collect();
+        for binding in &mut solution.binding {
+            binding.movement = self.movement_out(&binding.movement);
+        }
+        for hazard in &mut solution.hazards {
+            hazard.attack = hazard.attack.map(|attack| self.attack_out(attack));
+            hazard.movement = hazard.movement.map(|movement| self.movement_out(&movement));
+        }
+        solution.items = solution.items.iter().map(|&item| self.item_out(item)).collect();
+        if let Some(partner_attack) = &mut solution.partner_attack {
+            // Both partners' patterns are centered on the angle they're aimed at.
+            partner_attack.th = self.angle_out(partner_attack.th);
+        }
+        solution
+    }
+}
@@ -0,0 +1,144 @@
+//! Puzzle packs: curated sets of boards that can be shared between front ends.
+//!
+//! A pack is a JSON document:
+//! ```json
+//! {
+//!   "version": 1,
+//!   "metadata": {"name": "Earth Vellumental Temple", "author": "kupiakos"},
+//!   "puzzles": [
+//!     {"name": "Room 1", "board": [0, 3, 0, 0], "tags": ["easy"]},
+//!     {"board": [1, 0, 0, 1], "solution": [{"type": "ring", "r": 3, "amount": 1, "clockwise": true}]}
+//!   ]
+//! }
+//! ```
+//! Boards use the same encoding as `Ring`. Packs written by newer versions of this crate
+//! are rejected rather than partially understood.
+
+use crate::{check_movement, check_ring, Result, Ring, RingMovement};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// The newest pack version this crate can read, and the version it writes.
+pub const PACK_VERSION: u32 = 1;
+
+/// A versioned set of puzzles.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PuzzlePack {
+    pub version: u32,
+    #[serde(default)]
+    pub metadata: PackMetadata,
+    pub puzzles: Vec<PackPuzzle>,
+}
+
+/// Information about the pack as a whole.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackMetadata {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A single board in a pack.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackPuzzle {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub board: Ring,
+    /// The moves the pack author intended, if they want to share them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution: Option<Vec<RingMovement>>,
+    /// Free-form tags, e.g. difficulty ("easy", "hard") or where the board appears in-game.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// Why a pack couldn't be loaded or saved.
+#[derive(Debug)]
+pub enum PackError {
+    /// The pack isn't valid JSON, or doesn't have the expected shape.
+    Json(serde_json::Error),
+    /// The pack was written by a newer version of the format.
+    UnsupportedVersion(u32),
+    /// A puzzle's board or intended solution is out of range.
+    InvalidPuzzle { index: usize, reason: String },
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PackError::Json(e) => write!(f, "invalid puzzle pack: {}", e),
+            PackError::UnsupportedVersion(v) => write!(
+                f,
+                "puzzle pack version {} is newer than the supported version {}",
+                v, PACK_VERSION
+            ),
+            PackError::InvalidPuzzle { index, reason } => {
+                write!(f, "invalid puzzle {}: {}", index, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PackError {}
+
+impl From<serde_json::Error> for PackError {
+    fn from(e: serde_json::Error) -> Self {
+        PackError::Json(e)
+    }
+}
+
+impl PuzzlePack {
+    /// Creates an empty pack at the current version.
+    pub fn new(metadata: PackMetadata) -> Self {
+        PuzzlePack { version: PACK_VERSION, metadata, puzzles: Vec::new() }
+    }
+
+    /// Parses and validates a pack from JSON.
+    pub fn from_json(json: &str) -> std::result::Result<Self, PackError> {
+        let pack: PuzzlePack = serde_json::from_str(json)?;
+        pack.validate()?;
+        Ok(pack)
+    }
+
+    /// Validates and serializes the pack to JSON.
+    pub fn to_json(&self) -> std::result::Result<String, PackError> {
+        self.validate()?;
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Checks the version and that every puzzle is in range.
+    pub fn validate(&self) -> std::result::Result<(), PackError> {
+        if self.version > PACK_VERSION {
+            return Err(PackError::UnsupportedVersion(self.version));
+        }
+        for (index, puzzle) in self.puzzles.iter().enumerate() {
+            let invalid = |reason| PackError::InvalidPuzzle { index, reason };
+            check_ring(&puzzle.board).map_err(invalid)?;
+            for movement in puzzle.solution.iter().flatten() {
+                check_movement(movement).map_err(invalid)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Load a puzzle pack from its JSON text.
+#[wasm_bindgen(skip_typescript)]
+pub fn load_pack(json: &str) -> Result<JsValue> {
+    let pack = PuzzlePack::from_json(json).map_err(|e| e.to_string())?;
+    Ok(serde_wasm_bindgen::to_value(&pack)?)
+}
+
+/// Save a puzzle pack to JSON text.
+#[wasm_bindgen(skip_typescript)]
+pub fn save_pack(pack: JsValue) -> Result<String> {
+    let pack: PuzzlePack = serde_wasm_bindgen::from_value(pack)?;
+    Ok(pack.to_json().map_err(|e| e.to_string())?)
+}
@@ -0,0 +1,110 @@
+//! Trade-offs between solutions: instead of the single best solution, every solution that
+//! no other beats on turns, total distance, and expected damage at once.
+//!
+//! Damage is estimated as one counterattack for every action short of hitting every
+//! enemy, so doing nothing and taking the hits is always one of the trade-offs.
+
+use crate::evaluator::Evaluator;
+use crate::exhaustive::{Exhaustive, Leaves, RingMoves};
+use crate::{
+    evaluate, input_from_js, total_distance, Result, Ring, RingMovement, Solution,
+    SolveOptions, MAX_TURNS,
+};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// A solution on the Pareto front, and the objectives it's compared by.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParetoSolution {
+    pub solution: Solution,
+    pub turns: u16,
+    pub total_distance: u32,
+    /// The expected counterattacks from enemies that can't be hit.
+    pub expected_damage: u32,
+}
+
+impl ParetoSolution {
+    fn objectives(&self) -> (u16, u32, u32) {
+        (self.turns, self.total_distance, self.expected_damage)
+    }
+
+    /// Whether this is at least as good as `other` in every objective.
+    fn covers(&self, other: (u16, u32, u32)) -> bool {
+        let (turns, distance, damage) = self.objectives();
+        turns <= other.0 && distance <= other.1 && damage <= other.2
+    }
+}
+
+/// Every solution not beaten by another in all objectives, fewest turns first.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ParetoFront {
+    pub solutions: Vec<ParetoSolution>,
+    /// Whether the search ran out of budget, so the front may be missing solutions.
+    pub truncated: bool,
+}
+
+/// Collects the boards of an exhaustive search that aren't dominated.
+struct ParetoLeaves<'a> {
+    goal: &'a dyn Evaluator,
+    front: Vec<ParetoSolution>,
+}
+
+impl Leaves<Ring> for ParetoLeaves<'_> {
+    /// Adds the board reached to the front unless it's dominated.
+    fn leaf(&mut self, ring: &Ring, path: &[RingMovement]) {
+        let objectives = (path.len() as u16, total_distance(path), self.goal.distance(*ring));
+        if self.front.iter().any(|found| found.covers(objectives)) {
+            return;
+        }
+        self.front.retain(|found| {
+            let (turns, distance, damage) = found.objectives();
+            !(objectives.0 <= turns && objectives.1 <= distance && objectives.2 <= damage)
+        });
+        let mut solution = Solution::new(path.to_vec(), evaluate(*ring), false);
+        solution.perfect = objectives.2 == 0;
+        self.front.push(ParetoSolution {
+            solution,
+            turns: objectives.0,
+            total_distance: objectives.1,
+            expected_damage: objectives.2,
+        });
+    }
+
+    fn prune(&self, path: &[RingMovement], turn: u16) -> bool {
+        // Everything under here has at least this many turns and this distance, so a
+        // damage-free solution covering that is at least as good as all of it.
+        let turns = (path.len() as u16) + turn;
+        let distance = total_distance(path) + turn as u32;
+        self.front.iter().any(|found| found.covers((turns, distance, 0)))
+    }
+}
+
+/// Finds every trade-off between turns, total distance, and expected damage, within the
+/// turn limit. This has to look at far more boards than a normal solve, since it can't
+/// stop at the first solution.
+pub fn pareto_front(ring: Ring, options: &SolveOptions) -> ParetoFront {
+    let rules = options.battle_rules().unwrap_or_default();
+    let mut leaves = ParetoLeaves { goal: &rules, front: Vec::new() };
+    let mut search = Exhaustive::new(&RingMoves, options);
+    // Shallow solutions are found first, and prune everything deeper they're better than.
+    let max_turns = rules.max_turns.min(MAX_TURNS);
+    let Ok(()) = search.search_depths(&ring, max_turns, &mut leaves, |_| false);
+    let mut solutions = leaves.front;
+    solutions.sort_by_key(ParetoSolution::objectives);
+    ParetoFront { solutions, truncated: search.budget.truncated }
+}
+
+/// Find every solution that isn't beaten by another on turns, total distance, and
+/// expected damage at once, so the player can choose their own trade-off.
+/// Returns a `ParetoFront`.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_pareto(ring: JsValue, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let mut front = pareto_front(ring, &options);
+    for point in &mut front.solutions {
+        point.solution = options.orientation.solution_out(point.solution.clone());
+    }
+    Ok(serde_wasm_bindgen::to_value(&front)?)
+}
@@ -0,0 +1,177 @@
+//! Splitting a solve into one independent search for each first movement, so a frontend
+//! can farm them out to Web Workers and merge what they find.
+//!
+//! The frontend calls `partition_search`, hands each subproblem to a worker that calls
+//! `solve_subproblem`, and passes every worker's result to `merge_subsolutions`, which
+//! ranks the solutions the same way `solve_top_k` would.
+
+use crate::{
+    apply_movement, check_movement, check_ring, evaluate, iterate_movements, options_from_js,
+    Result, Ring, RingMovement, Search, Solution, SolveOptions, MAX_TURNS,
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// The search under a single first movement.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Subproblem {
+    pub first_move: RingMovement,
+    /// The board after `first_move`.
+    pub board: Ring,
+    /// How many more turns the search under `first_move` may take.
+    pub remaining_turns: u16,
+}
+
+/// The solutions a worker found for a subproblem.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubproblemResult {
+    pub subproblem: Subproblem,
+    pub solutions: Vec<SubSolution>,
+}
+
+/// The parts of a `Solution` needed to merge it; everything else is recomputed.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubSolution {
+    pub moves: Vec<RingMovement>,
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Every first movement allowed by `options`, and the board and turns left after it.
+/// A board that's already solved needs no subproblems to find that out, so
+/// `merge` checks for that itself.
+pub fn partition(ring: Ring, options: &SolveOptions) -> Vec<Subproblem> {
+    let rules = options.battle_rules().unwrap_or_default();
+    let remaining_turns = match rules.max_turns.min(MAX_TURNS).checked_sub(1) {
+        Some(remaining_turns) => remaining_turns,
+        None => return Vec::new(),
+    };
+    let subproblem = |first_move, board| Subproblem { first_move, board, remaining_turns };
+    if let Some(first_move) = options.first_move {
+        return vec![subproblem(first_move, apply_movement(ring, &first_move))];
+    }
+    let mut subproblems = Vec::new();
+    iterate_movements(ring, &options.constraints, |movement, moved| {
+        subproblems.push(subproblem(movement, moved));
+        None::<()>
+    });
+    subproblems
+}
+
+/// Finds up to `count` solutions from the board of `subproblem`, like `solve_top_k`.
+/// The moves don't include the first movement, and budgets in `options` apply to just
+/// this subproblem.
+pub fn solve(subproblem: &Subproblem, count: usize, options: &SolveOptions) -> Vec<Solution> {
+    // Searching from the board before the first movement, with it forced, ranks and checks
+    // every solution with all of its movements, the way a single search would.
+    let first_move = subproblem.first_move;
+    let ring = apply_movement(subproblem.board, &first_move.inverse());
+    let options = SolveOptions { first_move: Some(first_move), ..options.clone() };
+    let mut solutions =
+        Search::new(&options).find_solutions(ring, subproblem.remaining_turns + 1, count);
+    for solution in &mut solutions {
+        solution.moves.pop_front();
+        solution.total_distance -= first_move.distance();
+    }
+    solutions
+}
+
+/// Merges the solutions found for each subproblem of `ring` into the best `count`,
+/// ranked the same way as a single search. Sequences equivalent to one from another
+/// subproblem are only counted once.
+///
+/// If no subproblem found a solution, this returns the closest board any of them found,
+/// as a best-effort solution.
+pub fn merge(
+    ring: Ring,
+    results: &[SubproblemResult],
+    count: usize,
+    options: &SolveOptions,
+) -> std::result::Result<Vec<Solution>, String> {
+    let mut search = Search::new(options);
+    let mut truncated = false;
+    let mut perfect = Vec::new();
+    let mut closest: Option<(u32, Solution)> = None;
+    for result in results {
+        for sub_solution in &result.solutions {
+            let mut moves = vec![result.subproblem.first_move];
+            moves.extend_from_slice(&sub_solution.moves);
+            for movement in &moves {
+                check_movement(movement)?;
+            }
+            let board = moves.iter().fold(ring, apply_movement);
+            let evaluation = evaluate(board);
+            let distance = match &search.evaluator {
+                Some(evaluator) => evaluator.distance_after(board, &moves),
+                None => evaluation.excess_actions(),
+            };
+            truncated |= sub_solution.truncated;
+            let mut solution = Solution::new(moves, evaluation, false);
+            solution.perfect = distance == 0;
+            if solution.perfect {
+                perfect.push(solution);
+            } else if closest.as_ref().is_none_or(|&(best, _)| distance < best) {
+                closest = Some((distance, solution));
+            }
+        }
+    }
+    // A board that's already solved needs no movements at all, which beats everything.
+    let mut merged = if options.first_move.is_none() {
+        Search::new(options).find_solutions(ring, 0, count)
+    } else {
+        Vec::new()
+    };
+    // Without costs, fewer turns always wins, so each number of turns is ranked in turn.
+    let costs = search.costs.is_some();
+    perfect.sort_by_key(|solution| if costs { 0 } else { solution.moves.len() });
+    for same_rank in perfect.chunk_by(|a, b| costs || a.moves.len() == b.moves.len()) {
+        if merged.len() >= count {
+            break;
+        }
+        search.keep = count - merged.len();
+        for solution in same_rank {
+            search.keep_ranked(solution.clone());
+        }
+        merged.append(&mut search.found);
+    }
+    if merged.is_empty() {
+        merged.extend(closest.map(|(_, solution)| solution));
+    }
+    if truncated {
+        for solution in &mut merged {
+            solution.truncated = true;
+        }
+    }
+    Ok(merged)
+}
+
+/// Split a solve into a subproblem for each allowed first movement, which can be solved
+/// in parallel with `solve_subproblem`.
+#[wasm_bindgen(skip_typescript)]
+pub fn partition_search(ring: JsValue, options: JsValue) -> Result<JsValue> {
+    let ring: Ring = serde_wasm_bindgen::from_value(ring)?;
+    let options = options_from_js(options)?;
+    Ok(serde_wasm_bindgen::to_value(&partition(ring, &options))?)
+}
+
+/// Find up to `count` solutions to a subproblem from `partition_search`.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_subproblem(subproblem: JsValue, count: usize, options: JsValue) -> Result<JsValue> {
+    let subproblem: Subproblem = serde_wasm_bindgen::from_value(subproblem)?;
+    check_ring(&subproblem.board)?;
+    let options = options_from_js(options)?;
+    Ok(serde_wasm_bindgen::to_value(&solve(&subproblem, count, &options))?)
+}
+
+/// Merge the solutions found for each subproblem, as an array of
+/// `{subproblem, solutions}`, into the best `count` solutions to `ring`.
+#[wasm_bindgen(skip_typescript)]
+pub fn merge_subsolutions(ring: JsValue, results: JsValue, count: usize, options: JsValue) -> Result<JsValue> {
+    let ring: Ring = serde_wasm_bindgen::from_value(ring)?;
+    let results: Vec<SubproblemResult> = serde_wasm_bindgen::from_value(results)?;
+    let options = options_from_js(options)?;
+    Ok(serde_wasm_bindgen::to_value(&merge(ring, &results, count, &options)?)?)
+}
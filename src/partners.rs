@@ -0,0 +1,99 @@
+//! Partners, who attack once on their own alongside the player's actions, with their own
+//! patterns of panels they hit.
+//!
+//! ```json
+//! {"partner": "bobby"}
+//! ```
+//!
+//! The partner's attack comes first, so the player's attacks only have to clear what it
+//! leaves standing.
+
+use crate::evaluator::Evaluator;
+use crate::preset::Rules;
+use crate::{Ring, NUM_ANGLES, NUM_RINGS};
+use serde::{Deserialize, Serialize};
+
+/// A partner fighting alongside the player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Partner {
+    /// Blows up, hitting three adjacent angles of the ring he's thrown at and of the ring
+    /// outside it.
+    Bobby,
+    /// Digs out a single enemy anywhere on the board.
+    ProfessorToad,
+}
+
+impl Partner {
+    /// The panels hit when aimed at ring `r` and angle `th`.
+    pub fn pattern(self, r: usize, th: u16) -> Ring {
+        let mut cells = [0; NUM_RINGS as usize];
+        match self {
+            Partner::Bobby => {
+                let before = (th + NUM_ANGLES - 1) % NUM_ANGLES;
+                let angles = (0..3).fold(0, |angles, i| angles | 1 << ((before + i) % NUM_ANGLES));
+                for cells in cells.iter_mut().skip(r).take(2) {
+                    *cells = angles;
+                }
+            }
+            Partner::ProfessorToad => cells[r] = 1 << th,
+        }
+        cells
+    }
+}
+
+/// Where a partner attacks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartnerAttack {
+    pub partner: Partner,
+    pub r: u16,
+    pub th: u16,
+}
+
+/// Every enemy can be hit with the partner's attack and the actions and weapons of
+/// `rules`. The distance is the same as for `rules`, for the best place for the partner to
+/// attack.
+pub struct PartnerGoal {
+    partner: Partner,
+    rules: Rules,
+}
+
+impl PartnerGoal {
+    pub fn new(partner: Partner, rules: Rules) -> Self {
+        PartnerGoal { partner, rules }
+    }
+
+    /// The best place for the partner to attack `ring`, with the distance left and the
+    /// enemies left for the player, or None for the attack if it doesn't matter.
+    pub fn plan(&self, ring: Ring) -> (u32, Option<PartnerAttack>, Ring) {
+        // The enemies the partner defeats don't change how many actions the player gets.
+        let actions = self.rules.actions_for(ring);
+        let rules = Rules { actions: Some(actions), ..self.rules };
+        let mut best = (rules.distance(ring), None, ring);
+        for r in 0..NUM_RINGS as usize {
+            for th in 0..NUM_ANGLES {
+                if best.0 == 0 {
+                    return best;
+                }
+                let pattern = self.partner.pattern(r, th);
+                let rest: Ring = std::array::from_fn(|r| ring[r] & !pattern[r]);
+                if rest == ring {
+                    continue;
+                }
+                let distance = rules.distance(rest);
+                if distance < best.0 {
+                    let attack = PartnerAttack { partner: self.partner, r: r as u16, th };
+                    best = (distance, Some(attack), rest);
+                }
+            }
+        }
+        best
+    }
+}
+
+impl Evaluator for PartnerGoal {
+    fn distance(&self, ring: Ring) -> u32 {
+        self.plan(ring).0
+    }
+}
@@ -0,0 +1,32 @@
+//! Solutions a player can learn from, for `Selection::HumanLike`.
+//!
+//! Players don't plan a whole solution ahead: they line up a jump column or gather a 2×2
+//! block for the hammer, then look for the next one. Among solutions with the same turns,
+//! this prefers the ones where the structure the final board is attacked through shows up
+//! as early as possible, so each movement visibly gets somewhere.
+
+use crate::{apply_movement, MaskedInt, Ring, RingMovement, Subring, NUM_ANGLES};
+
+/// How much visible structure is on `ring`: the angles filled in every ring, which one
+/// jump clears, and the 2×2 blocks filled in the inner two rings, which one hammer clears.
+pub fn structure(ring: Ring) -> u32 {
+    let columns = ring.iter().fold(u16::MAX, |column, &angles| column & angles);
+    // An angle starts a block if it and the next angle clockwise are both filled.
+    let inner = ring[0] & ring[1];
+    let blocks = inner & Subring::<NUM_ANGLES>(inner).rotate_right(1).value();
+    columns.count_ones() + blocks.count_ones()
+}
+
+/// How long the structure of the final board takes to show up when `moves` are made on
+/// `ring`: for every movement, how much of it is still missing from the board it makes.
+/// 0 if the first movement already lines up everything the final board has.
+pub fn structure_delay(ring: Ring, moves: &[RingMovement]) -> u32 {
+    let mut boards = Vec::with_capacity(moves.len());
+    moves.iter().fold(ring, |ring, movement| {
+        let moved = apply_movement(ring, movement);
+        boards.push(moved);
+        moved
+    });
+    let target = boards.last().map_or(0, |&result| structure(result));
+    boards.iter().map(|&board| target.saturating_sub(structure(board))).sum()
+}
@@ -0,0 +1,205 @@
+//! Presets for the battle rules that change over the course of the game, so front ends
+//! don't have to keep the right combination of turns, actions, and weapons themselves.
+//!
+//! ```json
+//! {"preset": "early_game"}
+//! {"preset": "custom", "rules": {"maxTurns": 3, "actions": 2, "hammer": false}}
+//! {"preset": "standard", "available": "jump_only"}
+//! {"preset": "standard", "actions": 3}
+//! {"preset": "standard", "tolerance": 1}
+//! {"preset": "custom", "rules": {"formula": {"enemiesPerAction": 3, "bonus": 1}}}
+//! ```
+
+use crate::evaluator::Evaluator;
+use crate::{
+    attack_plan, evaluate, gapped_angles, hammer_blocks, Attack, Ring, MAX_TURNS, NUM_ANGLES,
+};
+use serde::Deserialize;
+use std::rc::Rc;
+
+/// A named set of battle rules.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preset {
+    /// Early battles: only 3 turns to line up the enemies.
+    EarlyGame,
+    /// The usual battle: 4 turns, one action per four enemies, and both weapons.
+    #[default]
+    Standard,
+    /// The rules given in `SolveOptions::rules`.
+    Custom,
+}
+
+/// Which kinds of attack the player can make right now, e.g. after their hammer broke,
+/// whatever the rules would otherwise allow.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Available {
+    /// Whichever weapons the rules allow.
+    #[default]
+    Both,
+    JumpOnly,
+    HammerOnly,
+}
+
+impl Available {
+    /// `rules` with only the weapons available, or an error if that leaves none.
+    pub fn restrict(self, mut rules: Rules) -> std::result::Result<Rules, String> {
+        match self {
+            Available::Both => {}
+            Available::JumpOnly => rules.hammer = false,
+            Available::HammerOnly => rules.jump = false,
+        }
+        rules.check()?;
+        Ok(rules)
+    }
+}
+
+/// How many actions the player gets for the enemies on the board: one for every
+/// `enemies_per_action` enemies, rounded up, plus `bonus`, e.g. from Toads in the stands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ActionFormula {
+    pub enemies_per_action: u32,
+    pub bonus: u32,
+}
+
+impl Default for ActionFormula {
+    fn default() -> Self {
+        ActionFormula { enemies_per_action: 4, bonus: 0 }
+    }
+}
+
+impl ActionFormula {
+    /// The actions the player gets against `enemies` enemies.
+    pub fn actions(&self, enemies: u32) -> u32 {
+        enemies.div_ceil(self.enemies_per_action) + self.bonus
+    }
+}
+
+/// The rules of a battle that decide which boards can be cleared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Rules {
+    /// The most turns the player may use to move the rings.
+    pub max_turns: u16,
+    /// How many actions the player gets, or None to use `formula`.
+    pub actions: Option<u32>,
+    /// How many actions the player gets when `actions` isn't given.
+    pub formula: ActionFormula,
+    /// How many more actions than the player gets a board may take and still count as
+    /// cleared, winning the fight but without the perfect bonus.
+    pub tolerance: u32,
+    /// Whether jumps are available. Only jumps can reach the outer two rings.
+    pub jump: bool,
+    /// Whether hammers are available.
+    pub hammer: bool,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            max_turns: MAX_TURNS,
+            actions: None,
+            formula: ActionFormula::default(),
+            tolerance: 0,
+            jump: true,
+            hammer: true,
+        }
+    }
+}
+
+impl Preset {
+    /// The rules bundled in this preset, with `custom` used for `Preset::Custom`.
+    pub fn rules(self, custom: Option<Rules>) -> std::result::Result<Rules, String> {
+        let rules = match (self, custom) {
+            (Preset::Custom, Some(rules)) => rules,
+            (Preset::Custom, None) => return Err("the custom preset needs rules".to_string()),
+            (_, Some(_)) => return Err("rules can only be given with the custom preset".to_string()),
+            (Preset::EarlyGame, None) => Rules { max_turns: 3, ..Rules::default() },
+            (Preset::Standard, None) => Rules::default(),
+        };
+        rules.check()?;
+        Ok(rules)
+    }
+}
+
+impl Rules {
+    /// Checks that the rules make a battle that can be played.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        if self.max_turns == 0 || self.max_turns > MAX_TURNS {
+            return Err(format!("max turns must be between 1 and {}", MAX_TURNS));
+        }
+        if self.actions == Some(0) {
+            return Err("there must be at least one action".to_string());
+        }
+        if self.formula.enemies_per_action == 0 {
+            return Err("each action must be for at least one enemy".to_string());
+        }
+        if !self.jump && !self.hammer {
+            return Err("at least one weapon must be available".to_string());
+        }
+        Ok(())
+    }
+
+    /// How many actions the player gets against the enemies on `ring`.
+    pub fn actions_for(&self, ring: Ring) -> u32 {
+        let enemies = ring.iter().map(|subring| subring.count_ones()).sum();
+        self.actions.unwrap_or_else(|| self.formula.actions(enemies))
+    }
+
+    /// The jumps and hammers that hit every enemy on `ring` with the weapons these rules
+    /// allow, like `Solution::attacks`.
+    pub fn attack_plan(&self, ring: Ring) -> Vec<Attack> {
+        match (self.jump, self.hammer) {
+            (true, true) => attack_plan(ring),
+            // A jump at every angle with an enemy, and a second where its line has a gap.
+            (true, false) => {
+                let angles = ring.iter().fold(0, |angles, &subring| angles | subring);
+                let gapped = gapped_angles(ring);
+                let jumps = (0..NUM_ANGLES).flat_map(|th| {
+                    let count = (angles >> th & 1) + (gapped >> th & 1);
+                    (0..count).map(move |_| Attack::Jump { th })
+                });
+                jumps.collect()
+            }
+            // Hammers can only reach the inner two rings.
+            (false, _) => {
+                let hammers = hammer_blocks([ring[0], ring[1], 0, 0]).into_iter();
+                hammers.map(|th| Attack::Hammer { th }).collect()
+            }
+        }
+    }
+
+    /// The goal for these rules, or None if it's the usual perfect clear.
+    pub(crate) fn evaluator(&self) -> Option<Rc<dyn Evaluator>> {
+        let usual = self.actions.is_none()
+            && self.formula == ActionFormula::default()
+            && self.tolerance == 0;
+        if usual && self.jump && self.hammer {
+            None
+        } else {
+            Some(Rc::new(*self))
+        }
+    }
+}
+
+/// Every enemy can be hit with the actions and weapons the rules allow. The distance is
+/// how many more actions it would take beyond the tolerance, plus one for every angle
+/// hammers can't reach.
+impl Evaluator for Rules {
+    fn distance(&self, ring: Ring) -> u32 {
+        let evaluation = evaluate(ring);
+        let actions = self.actions_for(ring);
+        let outer = ring[2] | ring[3];
+        let (needed, unreachable) = match (self.jump, self.hammer) {
+            (true, true) => (evaluation.jump_rows + evaluation.hammerable_groups, 0),
+            // Every angle with an enemy needs its own jump, or two if its line has a gap.
+            (true, false) => {
+                ((outer | ring[0] | ring[1]).count_ones() + gapped_angles(ring).count_ones(), 0)
+            }
+            (false, _) => (evaluation.hammerable_groups, outer.count_ones()),
+        };
+        needed.saturating_sub(actions).saturating_sub(self.tolerance) + unreachable
+    }
+}
@@ -0,0 +1,90 @@
+//! Priority targets: enemies like healers and buffers (e.g. Sombrero Guys) that have to go
+//! down first, or they undo the rest of the round's damage.
+//!
+//! ```json
+//! {"battle": {"priority": [0, 8, 0, 0]}}
+//! ```
+//!
+//! Priority targets move with the rings like the other enemies. The attacks that hit them
+//! are made first, and a board that can't be cleared is ranked by how many of them its
+//! attacks leave standing before anything else.
+
+use crate::evaluator::Evaluator;
+use crate::preset::Rules;
+use crate::{apply_movement, gapped_angles, Attack, Ring, RingMovement, NUM_ANGLES};
+use std::rc::Rc;
+
+/// How much a priority target left standing counts for against the rest of the distance,
+/// more than any board is otherwise from its goal.
+const MISSED_WEIGHT: u32 = 64;
+
+/// `attacks` on `ring`, with those that hit an enemy on `priority` first. A jump on a line
+/// with a gap goes after the hammer that breaks it up, so that hammer goes first with it.
+pub fn prioritize(attacks: &[Attack], ring: Ring, priority: Ring) -> Vec<Attack> {
+    let targets: Ring = std::array::from_fn(|r| ring[r] & priority[r]);
+    let hits = |attack: &Attack| attack.footprint().iter().zip(&targets).any(|(a, t)| a & t != 0);
+    let mut first: Vec<bool> = attacks.iter().map(hits).collect();
+    let gapped = gapped_angles(ring);
+    for (i, attack) in attacks.iter().enumerate() {
+        let th = match *attack {
+            Attack::Jump { th } if first[i] && gapped & (1 << th) != 0 => th,
+            _ => continue,
+        };
+        for (j, hammer) in attacks.iter().enumerate() {
+            if let Attack::Hammer { th: aim } = *hammer {
+                first[j] |= aim == th || (aim + 1) % NUM_ANGLES == th;
+            }
+        }
+    }
+    let (mut ordered, rest): (Vec<_>, Vec<_>) =
+        attacks.iter().zip(&first).partition(|&(_, &first)| first);
+    ordered.extend(rest);
+    ordered.into_iter().map(|(&attack, _)| attack).collect()
+}
+
+/// Clears the board for `goal`, and otherwise hits as many priority targets as it can.
+/// The distance is 0 if `goal` is reached, and otherwise that of `goal` plus
+/// `MISSED_WEIGHT` for every priority target the actions the player gets don't hit.
+pub struct PriorityGoal {
+    priority: Ring,
+    rules: Rules,
+    goal: Rc<dyn Evaluator>,
+}
+
+impl PriorityGoal {
+    /// The priority targets start on `priority`, and the attacks are the ones `rules` allow.
+    pub fn new(priority: Ring, rules: Rules, goal: Rc<dyn Evaluator>) -> Self {
+        PriorityGoal { priority, rules, goal }
+    }
+
+    /// How many priority targets the actions the player gets on `ring` leave standing,
+    /// after the targets are moved by `moves`.
+    pub fn missed(&self, ring: Ring, moves: &[RingMovement]) -> u32 {
+        let priority = moves.iter().fold(self.priority, apply_movement);
+        let actions = (self.rules.actions_for(ring) + self.rules.tolerance) as usize;
+        let attacks = prioritize(&self.rules.attack_plan(ring), ring, priority);
+        let hit = attacks.iter().take(actions).fold([0; 4], |hit: Ring, attack| {
+            let footprint = attack.footprint();
+            std::array::from_fn(|r| hit[r] | footprint[r])
+        });
+        let standing = priority.iter().zip(&ring).zip(&hit);
+        standing.map(|((priority, enemies), hit)| (priority & enemies & !hit).count_ones()).sum()
+    }
+}
+
+impl Evaluator for PriorityGoal {
+    fn distance(&self, ring: Ring) -> u32 {
+        self.distance_after(ring, &[])
+    }
+
+    fn distance_after(&self, ring: Ring, moves: &[RingMovement]) -> u32 {
+        match self.goal.distance_after(ring, moves) {
+            0 => 0,
+            distance => distance + MISSED_WEIGHT * self.missed(ring, moves),
+        }
+    }
+
+    fn allows(&self, moves: &[RingMovement]) -> bool {
+        self.goal.allows(moves)
+    }
+}
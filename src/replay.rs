@@ -0,0 +1,191 @@
+//! Replay files: a board and the moves that solve it, with the timing to animate each move
+//! and optional notes, so a solved battle can be archived, shared, and played back.
+//!
+//! A replay is a JSON document:
+//! ```json
+//! {
+//!   "version": 1,
+//!   "title": "Boss fight, round 2",
+//!   "board": [1, 0, 0, 1],
+//!   "steps": [
+//!     {"movement": {"type": "ring", "r": 3, "amount": 1, "clockwise": true}, "durationSec": 0.15},
+//!     {"movement": {"type": "row", "th": 0, "amount": 2, "outward": false}, "durationSec": 0.4,
+//!      "annotation": "line them up for a jump"}
+//!   ],
+//!   "result": [3, 0, 0, 0]
+//! }
+//! ```
+//! `result` is the board after every step, so viewers can check they replayed it the same
+//! way. Replays written by newer versions of this crate are rejected.
+
+use crate::{apply_movement, check_movement, check_ring, Result, Ring, RingMovement};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// The newest replay version this crate can read, and the version it writes.
+pub const REPLAY_VERSION: u32 = 1;
+
+/// How long the web UI takes to animate rotating a ring by one notch.
+pub const ROTATION_SECONDS_PER_NOTCH: f64 = 0.15;
+/// How long the web UI takes to animate shifting a row by one notch.
+pub const SHIFT_SECONDS_PER_NOTCH: f64 = 0.2;
+
+/// A versioned recording of a solve.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Replay {
+    pub version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The board before the first step.
+    pub board: Ring,
+    pub steps: Vec<ReplayStep>,
+    /// The board after the last step.
+    pub result: Ring,
+}
+
+/// A single movement in a replay.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayStep {
+    pub movement: RingMovement,
+    /// How long to animate the movement for.
+    pub duration_sec: f64,
+    /// A note to show while the movement plays.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<String>,
+}
+
+/// Why a replay couldn't be loaded or saved.
+#[derive(Debug)]
+pub enum ReplayError {
+    /// The replay isn't valid JSON, or doesn't have the expected shape.
+    Json(serde_json::Error),
+    /// The replay was written by a newer version of the format.
+    UnsupportedVersion(u32),
+    /// The starting board is out of range.
+    InvalidBoard(String),
+    /// A step's movement is out of range, or its duration isn't a positive number.
+    InvalidStep { index: usize, reason: String },
+    /// Playing back the steps doesn't end at `result`.
+    WrongResult,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Json(e) => write!(f, "invalid replay: {}", e),
+            ReplayError::UnsupportedVersion(v) => write!(
+                f,
+                "replay version {} is newer than the supported version {}",
+                v, REPLAY_VERSION
+            ),
+            ReplayError::InvalidBoard(reason) => write!(f, "invalid board: {}", reason),
+            ReplayError::InvalidStep { index, reason } => {
+                write!(f, "invalid step {}: {}", index, reason)
+            }
+            ReplayError::WrongResult => write!(f, "the steps don't end at the recorded result"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl From<serde_json::Error> for ReplayError {
+    fn from(e: serde_json::Error) -> Self {
+        ReplayError::Json(e)
+    }
+}
+
+/// How long the web UI takes to animate `movement`.
+pub fn animation_seconds(movement: &RingMovement) -> f64 {
+    let per_notch = if movement.is_rotation() {
+        ROTATION_SECONDS_PER_NOTCH
+    } else {
+        SHIFT_SECONDS_PER_NOTCH
+    };
+    per_notch * movement.distance().max(1) as f64
+}
+
+impl Replay {
+    /// Records `moves` made on `board`, timed like the web UI's animations.
+    pub fn new(board: Ring, moves: &[RingMovement]) -> Self {
+        let steps = moves
+            .iter()
+            .map(|&movement| ReplayStep {
+                movement,
+                duration_sec: animation_seconds(&movement),
+                annotation: None,
+            })
+            .collect();
+        Replay {
+            version: REPLAY_VERSION,
+            title: None,
+            board,
+            steps,
+            result: moves.iter().fold(board, apply_movement),
+        }
+    }
+
+    /// Parses and validates a replay from JSON.
+    pub fn from_json(json: &str) -> std::result::Result<Self, ReplayError> {
+        let replay: Replay = serde_json::from_str(json)?;
+        replay.validate()?;
+        Ok(replay)
+    }
+
+    /// Validates and serializes the replay to JSON.
+    pub fn to_json(&self) -> std::result::Result<String, ReplayError> {
+        self.validate()?;
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// The total time it takes to play back every step.
+    pub fn duration_sec(&self) -> f64 {
+        self.steps.iter().map(|step| step.duration_sec).sum()
+    }
+
+    /// Checks the version, that every step is in range, and that the steps end at `result`.
+    pub fn validate(&self) -> std::result::Result<(), ReplayError> {
+        if self.version > REPLAY_VERSION {
+            return Err(ReplayError::UnsupportedVersion(self.version));
+        }
+        check_ring(&self.board).map_err(ReplayError::InvalidBoard)?;
+        for (index, step) in self.steps.iter().enumerate() {
+            let invalid = |reason| ReplayError::InvalidStep { index, reason };
+            check_movement(&step.movement).map_err(invalid)?;
+            if !step.duration_sec.is_finite() || step.duration_sec <= 0.0 {
+                return Err(invalid(format!("duration {} isn't a positive number", step.duration_sec)));
+            }
+        }
+        let played = self.steps.iter().fold(self.board, |ring, step| apply_movement(ring, &step.movement));
+        if played != self.result {
+            return Err(ReplayError::WrongResult);
+        }
+        Ok(())
+    }
+}
+
+/// Record a replay of `moves` made on `board`, with the web UI's animation timing,
+/// as JSON text.
+#[wasm_bindgen(skip_typescript)]
+pub fn export_replay(board: JsValue, moves: JsValue) -> Result<String> {
+    let board: Ring = serde_wasm_bindgen::from_value(board)?;
+    let moves: Vec<RingMovement> = serde_wasm_bindgen::from_value(moves)?;
+    Ok(Replay::new(board, &moves).to_json().map_err(|e| e.to_string())?)
+}
+
+/// Load a replay from its JSON text.
+#[wasm_bindgen(skip_typescript)]
+pub fn load_replay(json: &str) -> Result<JsValue> {
+    let replay = Replay::from_json(json).map_err(|e| e.to_string())?;
+    Ok(serde_wasm_bindgen::to_value(&replay)?)
+}
+
+/// Save a replay, e.g. one with annotations added, to JSON text.
+#[wasm_bindgen(skip_typescript)]
+pub fn save_replay(replay: JsValue) -> Result<String> {
+    let replay: Replay = serde_wasm_bindgen::from_value(replay)?;
+    Ok(replay.to_json().map_err(|e| e.to_string())?)
+}
@@ -0,0 +1,84 @@
+//! Estimates of the coins and confetti a solution earns, so front ends can show what each
+//! line is worth.
+//!
+//! ```json
+//! {"rewards": {"coinsPerEnemy": 10, "coinsPerSecond": 1}, "timer": {"seconds": 60}}
+//! ```
+//!
+//! The estimate builds on the `battle` damage report: only enemies defeated this round pay
+//! out, the perfect bonus multiplies their coins, every attack hit "Excellent" adds a few
+//! more, and so does the time left on the `countdown` timer once the rings are lined up.
+//! The default rates are typical of regular battles; bosses and special fights pay
+//! differently.
+
+use crate::accessories::Effects;
+use crate::battle::{damage_report, BattleSetup};
+use crate::countdown::Timer;
+use crate::{RingMovement, Solution, SolveOptions};
+use serde::{Deserialize, Serialize};
+
+/// What a battle pays out.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RewardRates {
+    pub coins_per_enemy: u32,
+    pub confetti_per_enemy: u32,
+    /// What the coins for defeated enemies are multiplied by with the perfect bonus.
+    pub perfect_multiplier: f64,
+    /// The coins for each attack hit "Excellent".
+    pub excellent_coins: u32,
+    /// The coins for each whole second left on the timer.
+    pub coins_per_second: u32,
+}
+
+impl Default for RewardRates {
+    fn default() -> Self {
+        RewardRates {
+            coins_per_enemy: 10,
+            confetti_per_enemy: 5,
+            perfect_multiplier: 1.5,
+            excellent_coins: 5,
+            coins_per_second: 1,
+        }
+    }
+}
+
+/// The payout a solution is expected to earn this round.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RewardEstimate {
+    pub coins: u32,
+    pub confetti: u32,
+}
+
+/// The payout `solution` is expected to earn with the weapons of `battle`, made on `timer`.
+pub fn estimate(
+    solution: &Solution,
+    battle: &BattleSetup,
+    rates: &RewardRates,
+    timer: &Timer,
+) -> RewardEstimate {
+    let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+    let report = damage_report(&moves, solution.result, battle);
+    let mut enemy_coins = (report.defeated * rates.coins_per_enemy) as f64;
+    if solution.perfect_bonus {
+        enemy_coins *= rates.perfect_multiplier;
+    }
+    let excellent = if battle.excellent { report.attacks.len() as u32 } else { 0 };
+    let time_left = timer.seconds - timer.seconds_for(&moves);
+    let time_coins = time_left.max(0.0) as u32 * rates.coins_per_second;
+    RewardEstimate {
+        coins: enemy_coins as u32 + excellent * rates.excellent_coins + time_coins,
+        confetti: report.defeated * rates.confetti_per_enemy,
+    }
+}
+
+/// The payout `solution` is expected to earn with the rates, weapons, timer, and
+/// accessories of `options`, using the defaults for any that aren't set.
+pub fn estimate_with(solution: &Solution, options: &SolveOptions) -> RewardEstimate {
+    let effects = Effects::of(&options.accessories);
+    let timer = effects.timer(options.timer.unwrap_or_default());
+    let rates = options.rewards.unwrap_or_default();
+    let battle = options.battle.unwrap_or_default();
+    effects.reward(estimate(solution, &battle, &rates, &timer))
+}
@@ -0,0 +1,132 @@
+//! Boss phases that move the rings on their own: after each of the player's turns, the
+//! boss rotates or shifts some rings before the next one. Given the script of those
+//! movements, the search plans around them.
+//!
+//! ```json
+//! {"betweenTurns": [[{"type": "ring", "r": 0, "amount": 1, "clockwise": true}]], "repeat": true}
+//! ```
+//!
+//! The script only runs between turns: once the player's last turn is made, they attack
+//! straight away.
+
+use crate::exhaustive::{Exhaustive, Moves, RingMoves};
+use crate::{
+    apply_movement, check_movement, check_ring, evaluate, goal, input_from_js, solve_to_js,
+    MoveConstraints, Result, Ring, RingMovement, Solution, SolveOptions, MAX_TURNS,
+};
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// The movements forced on the board between the player's turns.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Script {
+    /// The movements made after each turn, in order: the first entry after the first
+    /// turn, and so on.
+    pub between_turns: Vec<Vec<RingMovement>>,
+    /// Whether the script starts over once it runs out. Otherwise nothing moves after it.
+    pub repeat: bool,
+}
+
+impl Script {
+    /// Checks that every movement in the script exists.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        self.between_turns.iter().flatten().try_for_each(check_movement)
+    }
+
+    /// The movements made after the player's turn `turn`, counting from 0.
+    pub fn after(&self, turn: usize) -> &[RingMovement] {
+        let len = self.between_turns.len();
+        let index = if self.repeat && len > 0 { turn % len } else { turn };
+        self.between_turns.get(index).map_or(&[], Vec::as_slice)
+    }
+
+    /// Every movement made on the board for the player's `moves`, with the script's run
+    /// between them.
+    pub fn played(&self, moves: &[RingMovement]) -> Vec<RingMovement> {
+        let mut played = Vec::new();
+        for (turn, movement) in moves.iter().enumerate() {
+            if turn > 0 {
+                played.extend_from_slice(self.after(turn - 1));
+            }
+            played.push(*movement);
+        }
+        played
+    }
+
+    /// The board after `moves`, with the script run between them.
+    pub fn play(&self, ring: Ring, moves: &[RingMovement]) -> Ring {
+        self.played(moves).iter().fold(ring, apply_movement)
+    }
+}
+
+/// Moves the board with the script run between the player's turns.
+impl Moves for Script {
+    type Board = Ring;
+    type Error = Infallible;
+
+    fn each_movement(
+        &self,
+        ring: &Ring,
+        turns: usize,
+        constraints: &MoveConstraints,
+        visit: impl FnMut(RingMovement, Ring) -> std::result::Result<(), Infallible>,
+    ) -> std::result::Result<(), Infallible> {
+        let ring = match turns {
+            0 => *ring,
+            turns => self.after(turns - 1).iter().fold(*ring, apply_movement),
+        };
+        RingMoves.each_movement(&ring, turns, constraints, visit)
+    }
+
+    fn first_moved(
+        &self,
+        ring: &Ring,
+        movement: &RingMovement,
+    ) -> std::result::Result<Option<Ring>, Infallible> {
+        RingMoves.first_moved(ring, movement)
+    }
+}
+
+/// Finds the fewest turns that clear `ring` with `script` moving the rings between them,
+/// then the smallest total distance. The moves are only the player's. Returns None if
+/// nothing within the turn limit clears it, or the search ran out of budget first.
+pub fn find_solution(
+    ring: Ring,
+    script: &Script,
+    options: &SolveOptions,
+) -> std::result::Result<Option<Solution>, String> {
+    check_ring(&ring)?;
+    script.check()?;
+    let rules = options.battle_rules()?;
+    let goal = goal(options, rules).unwrap_or_else(|| Rc::new(rules));
+    let mut search = Exhaustive::new(script, options);
+    let max_turns = rules.max_turns.min(MAX_TURNS);
+    // Goals that follow the movements, like where the Toads are, see the script's too.
+    let Ok(found) = search.find_shortest(&ring, max_turns, |&ring, path| {
+        goal.distance_after(ring, &script.played(path)) == 0
+    });
+    Ok(found.map(|(moves, result)| {
+        let mut solution = Solution::new(moves, evaluate(result), false);
+        solution.perfect = true;
+        solution.attacks = rules.attack_plan(result);
+        solution
+    }))
+}
+
+/// Solve a board whose rings move on their own between turns, given the script of those
+/// movements as `{betweenTurns, repeat}`.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_scripted(ring: JsValue, script: JsValue, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let script: Script = serde_wasm_bindgen::from_value(script)?;
+    script.check()?;
+    let orientation = options.orientation;
+    let between_turns = script.between_turns.iter().map(|movements| {
+        movements.iter().map(|movement| orientation.movement_in(movement)).collect()
+    });
+    let script = Script { between_turns: between_turns.collect(), ..script };
+    solve_to_js(find_solution(ring, &script, &options)?, &options)
+}
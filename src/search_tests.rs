@@ -0,0 +1,985 @@
+//! Checks of what the search and the tools built on it produce, beyond whether a solution
+//! is found: that shortcuts like symmetry reduction never change the answer or break the
+//! options, and that every input gives an answer.
+
+use super::*;
+
+/// Whether every movement of `solution` is allowed by `constraints`.
+fn obeys(solution: &Solution, constraints: &MoveConstraints) -> bool {
+    solution.moves.iter().all(|movement| constraints.violation(movement).is_none())
+}
+
+#[test]
+fn symmetry_respects_row_constraints() {
+    // Angles 0, 3, 6, and 9 of rings 0 and 2: symmetric under rotations by 3, which map
+    // the forbidden row 0 onto the allowed row 3.
+    let board = [0b0010_0100_1001, 0, 0b0010_0100_1001, 0];
+    let constraints = MoveConstraints { rows: 0b11_1110, ..Default::default() };
+    let options = SolveOptions { constraints, ..Default::default() };
+    let solution = find_solution(board, MAX_TURNS, &options).unwrap();
+    assert!(solution.perfect);
+    assert!(obeys(&solution, &constraints), "{:?}", solution.moves);
+    let unreduced = Search::new(&options).find_solutions(board, MAX_TURNS, 2).remove(0);
+    assert_eq!(solution.moves, unreduced.moves);
+}
+
+#[test]
+fn pruned_ties_choose_the_same_solution() {
+    // Boards with many solutions tied on turns and distance, some of them symmetric.
+    let boards = [[0b11, 0, 0, 0b11 << 6], [0b10_0001, 0b10_0001, 0, 0], [0b1001, 0, 0b10, 0b100_0000]];
+    for selection in [Selection::SmallestDistance, Selection::RotationsFirst, Selection::Lexicographic] {
+        let options = SolveOptions { selection, ..Default::default() };
+        for board in boards {
+            let mut pruned = Search::new(&options);
+            let chosen = pruned.find_solution(board, MAX_TURNS).unwrap();
+            // Keeping many solutions leaves almost nothing to prune by.
+            let mut ranked = Search::new(&options);
+            let first = ranked.find_solutions(board, MAX_TURNS, 64).remove(0);
+            assert_eq!(chosen.moves, first.moves, "{:?} {:?}", selection, board);
+            assert!(pruned.budget.nodes <= ranked.budget.nodes);
+        }
+    }
+}
+
+#[test]
+fn every_seed_generates_boards() {
+    use crate::generator::{generate_boards, GeneratorSpec};
+    // This seed once left the random number generator stuck at zero, placing enemies forever.
+    let spec = GeneratorSpec { count: 2, enemies: 5, seed: 0x9E37_79B9_7F4A_7C15, ..Default::default() };
+    for board in generate_boards(&spec) {
+        assert_eq!(board.iter().map(|subring| subring.count_ones()).sum::<u32>(), 5);
+    }
+}
+
+#[test]
+fn searching_again_shares_the_budget() {
+    // Without the first two rows, `solve` searches again to find what they ruled out,
+    // which takes more boards than the search itself.
+    let board = [0b11, 0, 0, 0b11 << 6];
+    let constraints = MoveConstraints { rows: 0b11_1100, ..Default::default() };
+    let options = SolveOptions { constraints, max_nodes: Some(300_000), ..Default::default() };
+    let mut search = Search::new(&options);
+    let solution = search.find_solution(board, MAX_TURNS).unwrap();
+    assert!(solution.perfect && !solution.truncated);
+    without_yielding(finish_solution(&mut search, solution, board, MAX_TURNS, &options));
+    assert!(search.budget.nodes <= 300_000, "{}", search.budget.nodes);
+
+    // Once cancelled, searching again stops at the first check for it.
+    let options = SolveOptions { constraints, ..Default::default() };
+    let token = cancel::CancelToken::new();
+    let mut search = Search::new(&options).with_cancel(token.clone());
+    let solution = search.find_solution(board, MAX_TURNS).unwrap();
+    let searched = search.budget.nodes;
+    token.cancel();
+    let solution = without_yielding(finish_solution(&mut search, solution, board, MAX_TURNS, &options));
+    assert!(search.budget.nodes <= searched + CLOCK_INTERVAL, "{}", search.budget.nodes - searched);
+    assert!(solution.binding.is_empty());
+}
+
+#[test]
+fn orientation_maps_every_angle() {
+    let orientation = orientation::Orientation { reference: 5, counterclockwise: true };
+    let frontend = orientation.board_out([0b11, 0, 0, 0b11 << 6]);
+    // Without the rows at the solver's angles 0 and 1, the best line is ruled out.
+    let forbidden = [0, 1].map(|th| orientation.angle_out(th) % (NUM_ANGLES / 2));
+    let rows = forbidden.iter().fold(0b11_1111, |rows, th| rows & !(1 << th));
+    let constraints = MoveConstraints { rows, ..Default::default() };
+    let options = SolveOptions { constraints, orientation, ..Default::default() };
+    let options = orientation.options_in(options);
+    let solution = find_solution(orientation.board_in(frontend), MAX_TURNS, &options).unwrap();
+    let solution = orientation.solution_out(solution);
+    assert_eq!(solution.moves.iter().fold(frontend, apply_movement), solution.result);
+    assert!(obeys(&solution, &constraints), "{:?}", solution.moves);
+    assert!(!solution.binding.is_empty());
+    for binding in &solution.binding {
+        assert!(constraints.violation(&binding.movement).is_some(), "{:?}", binding);
+    }
+
+    let grid = |ring: Ring| ring.map(|angles| std::array::from_fn(|th| angles >> th & 1));
+    assert_eq!(orientation.grid_in(grid(frontend)), grid(orientation.board_in(frontend)));
+}
+
+#[test]
+fn no_damage_reports_the_turn_limit_of_the_rules() {
+    use crate::no_damage::{plan, NoDamageFailure};
+    let rules = preset::Rules { max_turns: 1, ..Default::default() };
+    let options = SolveOptions { preset: preset::Preset::Custom, rules: Some(rules), ..Default::default() };
+    let plan = plan([0b1001, 0, 0b10, 0b100_0000], &options).unwrap();
+    assert_eq!(plan.failure, Some(NoDamageFailure::Unsolvable { max_turns: 1 }));
+}
+
+#[test]
+fn no_damage_plans_rank_by_the_coins_they_earn() {
+    let board = [0b1000, 0b0100_0000_1000, 0b10_0000, 0b0010_0000_1000];
+    // Hammers (4) finish an enemy with 4 HP, but jumps (3) don't.
+    let mut hp = [[0; NUM_ANGLES as usize]; NUM_RINGS as usize];
+    for (r, row) in hp.iter_mut().enumerate() {
+        for (th, panel) in row.iter_mut().enumerate() {
+            if board[r] >> th & 1 == 1 {
+                *panel = 4;
+            }
+        }
+    }
+    let battle = battle::BattleSetup { hp: Some(hp), ..Default::default() };
+    let options = SolveOptions { battle: Some(battle), ..Default::default() };
+    let first = Search::new(&options).find_solutions(board, MAX_TURNS, 8)
+        .into_iter().find(|solution| solution.perfect).unwrap();
+    let plan = no_damage::plan(board, &options).unwrap().solution.unwrap();
+    assert_ne!(plan.moves, first.moves);
+    assert!(no_damage::coin_bonus(&plan, &options) > no_damage::coin_bonus(&first, &options));
+}
+
+#[test]
+fn audit_checks_attributes_and_toads() {
+    use crate::attributes::EnemyAttributes;
+    use crate::audit::{audit, FindingKind, Severity};
+    let kinds = |ring, attributes: &EnemyAttributes, options: &SolveOptions| {
+        audit(ring, attributes, options).into_iter().map(|finding| (finding.severity, finding.kind))
+    };
+    let board = [0b1, 0, 0, 0b1];
+    let armored = EnemyAttributes { spiked: [0b1, 0, 0, 0], flying: [0b1, 0, 0, 0], ..Default::default() };
+    let found: Vec<_> = kinds(board, &armored, &SolveOptions::default()).collect();
+    assert!(found.contains(&(Severity::Error, FindingKind::UnreachableEnemy { r: 0, th: 0 })));
+
+    let stray = EnemyAttributes { flying: [0b10, 0, 0, 0], ..Default::default() };
+    let found: Vec<_> = kinds(board, &stray, &SolveOptions::default()).collect();
+    assert!(matches!(found[..], [(Severity::Error, FindingKind::InconsistentLayers { .. })]));
+
+    // Both hammers that hit angle 0 also hit a Toad, one at angle 1 and one at angle 11.
+    let spiked = EnemyAttributes { spiked: [0b1, 0, 0, 0], ..Default::default() };
+    let options = SolveOptions { toads: Some([1 << 11, 0b10, 0, 0]), ..Default::default() };
+    let found: Vec<_> = kinds(board, &spiked, &options).collect();
+    assert!(found.contains(&(Severity::Warning, FindingKind::ToadsInEveryHammerBlock { r: 0, th: 0 })));
+}
+
+#[test]
+fn hazards_explain_longer_solutions() {
+    use crate::attributes::EnemyAttributes;
+    use crate::binding::Hazard;
+    use crate::obstacles::ObstructedBoard;
+    let hazards = |solution: &Solution| {
+        assert!(solution.perfect && !solution.moves.is_empty(), "{:?}", solution);
+        assert!(!solution.hazards.is_empty());
+        solution.hazards.iter().map(|hazard| (hazard.hazard, hazard.attack)).collect::<Vec<_>>()
+    };
+
+    // Either hammer at angle 0 would land on a Toad, though it would take no movements.
+    let board = [0b1, 0b1, 0, 0];
+    let options = SolveOptions { toads: Some([1 << 11 | 0b10, 0, 0, 0]), ..Default::default() };
+    let solution = find_solution(board, MAX_TURNS, &options).unwrap();
+    assert!(hazards(&solution).iter().all(|&(hazard, _)| hazard == Hazard::Toad));
+
+    // The jump at angle 0 would land on a spiked enemy.
+    let board = [0, 0, 0b1, 0b1];
+    let attributes = EnemyAttributes { spiked: [0, 0, 0, 0b1], ..Default::default() };
+    let options = SolveOptions::default();
+    let solution = attributes::find_solution(board, attributes, &options).unwrap().unwrap();
+    assert_eq!(hazards(&solution), [(Hazard::Spiked, Some(Attack::Jump { th: 0 }))]);
+
+    // Shifting row 3 would take a turn fewer, but it crosses a blocked panel.
+    let board = ObstructedBoard { ring: [0b1011, 0, 0, 0], blocked: [0, 0, 0, 0b1000] };
+    let solution = obstacles::find_solution(&board, &options).unwrap().unwrap();
+    assert_eq!(hazards(&solution), [(Hazard::Blocked, None)]);
+    let shift = RingMovement::Row { th: 3, amount: 1, outward: true };
+    assert_eq!(solution.hazards[0].movement, Some(shift));
+    assert_eq!(solution.hazards[0].turns_saved, 1);
+}
+
+#[test]
+fn incremental_solves_use_the_goal_of_the_options() {
+    use crate::engine::{Reuse, Solver};
+    // One action short of hitting both enemies is close enough with a tolerance of 1.
+    let mut solver = Solver::with_options(SolveOptions { tolerance: Some(1), ..Default::default() });
+    assert!(solver.find_solution([0b1, 0, 0, 0]).unwrap().moves.is_empty());
+    let (solution, reuse) = solver.find_solution_incremental([0b100_0001, 0, 0, 0]);
+    assert_eq!(reuse, Reuse::Reused);
+    assert!(solution.unwrap().moves.is_empty());
+    assert_eq!(solver.cached_boards(), 1);
+    assert_eq!(solver.find_solution_incremental([0b100_0001, 0, 0, 0]).1, Reuse::Cached);
+}
+
+#[test]
+fn patched_solutions_arent_searched_solutions() {
+    use crate::engine::{Reuse, Solver};
+    let mut solver = Solver::with_options(SolveOptions::default());
+    assert_eq!(solver.find_solution([0, 0b101, 0b1, 0]).unwrap().moves.len(), 2);
+    // Without the enemy at angle 0 of ring 1, one rotation is enough.
+    let (patched, reuse) = solver.find_solution_incremental([0, 0b100, 0b1, 0]);
+    assert_eq!(reuse, Reuse::Patched);
+    assert_eq!(patched.unwrap().moves.len(), 2);
+    assert_eq!(solver.find_solution([0, 0b100, 0b1, 0]).unwrap().moves.len(), 1);
+}
+
+#[test]
+fn obstructed_boards_keep_the_toads_safe() {
+    use crate::obstacles::ObstructedBoard;
+    // The same board as a Toad solve, with a blocked panel out of the way.
+    let board = ObstructedBoard { ring: [0b1, 0b1, 0, 0], blocked: [0, 0, 0, 1 << 6] };
+    let options = SolveOptions { toads: Some([1 << 11 | 0b10, 0, 0, 0]), ..Default::default() };
+    let solution = obstacles::find_solution(&board, &options).unwrap().unwrap();
+    let rules = preset::Rules::default();
+    let goal = toads::ToadGoal::new(options.toads.unwrap(), rules, Rc::new(rules));
+    let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+    assert!(!moves.is_empty());
+    assert_eq!(goal.toads_hit(solution.result, &moves), 0);
+}
+
+#[test]
+fn partitioned_solves_rank_like_one_search() {
+    use crate::partition::{merge, partition, solve, SubSolution, SubproblemResult};
+    // The cost of a movement depends on the one before it, so a subproblem ranks its
+    // solutions differently if it leaves out the first movement.
+    let costs = MoveCosts { travel: 5, switch: 7, ..Default::default() };
+    let rules = preset::Rules { max_turns: 3, ..Default::default() };
+    let options = SolveOptions { preset: preset::Preset::Custom, rules: Some(rules), ..Default::default() };
+    let every_options = [
+        SolveOptions { costs: Some(costs), ..options.clone() },
+        SolveOptions { selection: Selection::RotationsFirst, ..options.clone() },
+        options,
+    ];
+    for options in &every_options {
+        for board in [[1, 129, 132, 4], [1, 1, 192, 132]] {
+            let results: Vec<SubproblemResult> = partition(board, options)
+                .into_iter()
+                .map(|subproblem| {
+                    let solutions = solve(&subproblem, 1, options)
+                        .into_iter()
+                        .map(|solution| SubSolution { moves: solution.moves.into(), truncated: false })
+                        .collect();
+                    SubproblemResult { subproblem, solutions }
+                })
+                .collect();
+            let merged = merge(board, &results, 1, options).unwrap();
+            let single = Search::new(options).find_solutions(board, rules.max_turns, 1);
+            assert!(single[0].perfect);
+            assert_eq!(merged[0].moves, single[0].moves, "{:?} {:?} {:?}", options.costs, options.selection, board);
+        }
+    }
+}
+
+#[test]
+fn large_enemies_are_never_searched_apart() {
+    use crate::evaluator::Evaluator;
+    use crate::large_enemies::{LargeBoard, LargeEnemyGoal};
+    /// The same goal, checked only once the movements are made.
+    struct AtTheLeaves(LargeEnemyGoal);
+    impl Evaluator for AtTheLeaves {
+        fn distance(&self, ring: Ring) -> u32 {
+            self.0.distance(ring)
+        }
+        fn distance_after(&self, ring: Ring, moves: &[RingMovement]) -> u32 {
+            self.0.distance_after(ring, moves)
+        }
+    }
+    // A wide enemy on angles 0 and 1 of ring 0, which shifting row 0 or 1 would split.
+    let board = LargeBoard::new([0b11, 0, 0b1 << 4, 0b1 << 8], vec![[0b11, 0, 0, 0]]).unwrap();
+    let goal = || LargeEnemyGoal::new(board.clone(), preset::Rules::default());
+    let mut pruned = Search::new(&SolveOptions::default()).with_evaluator(Rc::new(goal()));
+    let solution = pruned.find_solution(board.ring, MAX_TURNS).unwrap();
+    assert!(solution.perfect);
+    assert!(solution.moves.iter().try_fold(board.clone(), |board, movement| board.apply(movement)).is_some());
+    let mut unpruned = Search::new(&SolveOptions::default()).with_evaluator(Rc::new(AtTheLeaves(goal())));
+    assert_eq!(unpruned.find_solution(board.ring, MAX_TURNS).unwrap().moves, solution.moves);
+    assert!(pruned.budget.nodes < unpruned.budget.nodes, "{} {}", pruned.budget.nodes, unpruned.budget.nodes);
+}
+
+#[test]
+fn hammer_blocks_are_the_fewest_that_hit_every_target() {
+    let full = (1 << NUM_ANGLES) - 1;
+    // The fewest hammers that hit every angle of each set, found by trying every set of aims
+    // and then letting each set of angles take the best of any set containing it.
+    let mut fewest = vec![u32::MAX; 1 << NUM_ANGLES];
+    for aims in 0..1u16 << NUM_ANGLES {
+        let aimed = (0..NUM_ANGLES).filter(|th| aims & (1 << th) != 0);
+        let hit = aimed.fold(0, |hit, th| hit | hammer_block(th)[0]);
+        fewest[hit as usize] = fewest[hit as usize].min(aims.count_ones());
+    }
+    for th in 0..NUM_ANGLES {
+        for angles in (0..=full).rev() {
+            if angles & (1 << th) == 0 {
+                let with_th = fewest[(angles | 1 << th) as usize];
+                fewest[angles as usize] = fewest[angles as usize].min(with_th);
+            }
+        }
+    }
+    for inner in 0..=full {
+        // Hammers hit both inner rings, so splitting the targets between them changes nothing.
+        let targets = [inner & 0b1010_1010_1010, inner & 0b0101_0101_0101, 0, 0];
+        let blocks = hammer_blocks(targets);
+        assert_eq!(blocks.len() as u32, fewest[inner as usize], "{:012b}", inner);
+        assert_eq!(tables().hammer_groups[inner as usize] as usize, blocks.len());
+        let hit = blocks.iter().fold(0, |hit, &th| hit | hammer_block(th)[0]);
+        assert_eq!(inner & !hit, 0, "{:012b}", inner);
+    }
+}
+
+#[test]
+fn half_turns_are_only_tried_once() {
+    use std::collections::BTreeSet;
+    // Every ring and row has an enemy, so every ring and row is moved.
+    let board = [0b1, 0b110, 0b1_0100_0000, 0b1000_0011_1000];
+    let constraints = MoveConstraints::default();
+    let mut tried = Vec::new();
+    iterate_movements(board, &constraints, |movement, moved| {
+        tried.push((movement, moved));
+        None::<()>
+    });
+    // The half turn of each ring and row is only tried one way.
+    let half_turns = tried.iter().filter(|(movement, _)| match *movement {
+        RingMovement::Ring { amount, .. } => amount == NUM_ANGLES as i16 / 2,
+        RingMovement::Row { amount, .. } => amount == NUM_RINGS as i16,
+    });
+    assert_eq!(half_turns.count() as u16, NUM_RINGS + NUM_ANGLES / 2);
+    let mut every = BTreeSet::new();
+    for way in [false, true] {
+        for r in 0..NUM_RINGS {
+            for amount in 1..=NUM_ANGLES as i16 / 2 {
+                every.insert(apply_movement(board, &RingMovement::Ring { r, amount, clockwise: way }));
+            }
+        }
+        for th in 0..NUM_ANGLES / 2 {
+            for amount in 1..=NUM_RINGS as i16 {
+                every.insert(apply_movement(board, &RingMovement::Row { th, amount, outward: way }));
+            }
+        }
+    }
+    // Skipping them leaves out no board, so no solution either.
+    let reached: BTreeSet<Ring> = tried.iter().map(|&(_, moved)| moved).collect();
+    assert_eq!(reached, every);
+}
+
+#[test]
+fn jumps_only_hit_the_line_outward_from_the_innermost_enemy() {
+    /// The fewest jumps and hammers that clear `ring`, which only has enemies at angles 0
+    /// and 1, trying every attack that could hit them in every order.
+    fn fewest_attacks(ring: Ring) -> u32 {
+        if ring.iter().all(|&subring| subring == 0) {
+            return 0;
+        }
+        let attacks = [
+            Attack::Jump { th: 0 },
+            Attack::Jump { th: 1 },
+            Attack::Hammer { th: 11 },
+            Attack::Hammer { th: 0 },
+            Attack::Hammer { th: 1 },
+        ];
+        let hit = |attack: Attack| match attack {
+            Attack::Jump { th } => {
+                let mut line = [0; NUM_RINGS as usize];
+                (0..NUM_RINGS as usize)
+                    .skip_while(|&r| ring[r] & (1 << th) == 0)
+                    .take_while(|&r| ring[r] & (1 << th) != 0)
+                    .for_each(|r| line[r] = 1 << th);
+                line
+            }
+            hammer => hammer.footprint(),
+        };
+        let after = attacks.iter().map(|&attack| {
+            let hit = hit(attack);
+            std::array::from_fn(|r| ring[r] & !hit[r])
+        });
+        1 + after.filter(|after: &Ring| *after != ring).map(fewest_attacks).min().unwrap()
+    }
+    for cells in 0..1u16 << (2 * NUM_RINGS) {
+        let ring: Ring = std::array::from_fn(|r| cells >> (2 * r) & 0b11);
+        let evaluation = evaluate(ring);
+        let attacks = evaluation.jump_rows + evaluation.hammerable_groups;
+        assert_eq!(attacks, fewest_attacks(ring), "{:?}", ring);
+    }
+}
+
+#[test]
+fn searches_run_natively_with_debug_assertions() {
+    // Tests are built with debug assertions, which in a browser log each movement searched
+    // to the console, but there's no console to log to here.
+    let solution = find_solution([0b11, 0, 0, 0b11 << 6], MAX_TURNS, &SolveOptions::default()).unwrap();
+    assert!(solution.perfect);
+}
+
+#[test]
+fn unique_solutions_make_boards_harder() {
+    use crate::difficulty::{rate, Difficulty};
+    // Only rotating ring 3 back lines up the last jump, though splitting that rotation in
+    // two would also work with another turn.
+    let rating = rate([0b11, 0b11, 0b11, 0b11 << 5]);
+    assert_eq!((rating.difficulty, rating.min_turns), (Difficulty::Medium, Some(1)));
+}
+
+/// Options that search boss arenas one turn deep.
+fn one_turn() -> SolveOptions {
+    let rules = preset::Rules { max_turns: 1, ..Default::default() };
+    SolveOptions { preset: preset::Preset::Custom, rules: Some(rules), ..Default::default() }
+}
+
+#[test]
+fn boss_routes_follow_the_arrows_to_the_attack_panel() {
+    use crate::boss::*;
+    let arrow = |direction| Panel::Arrow { direction };
+    let mut board = BossBoard::default();
+    assert!(board.check().is_err());
+    // Straight in at angle 2, turning clockwise on ring 1 to the attack panel at angle 4.
+    board.panels[1][2] = arrow(Direction::Clockwise);
+    board.panels[1][3] = Panel::Action;
+    board.panels[1][4] = Panel::Attack;
+    assert!(board.check().is_ok());
+    let route = board.best_route().unwrap();
+    assert_eq!((route.start, route.actions, route.path.len()), (2, 1, 5));
+    let solution = boss::find_solution(&board, &one_turn()).unwrap().unwrap();
+    assert!(solution.moves.is_empty());
+    let moved = board.moved(&RingMovement::Ring { r: 1, amount: 5, clockwise: true }).unwrap();
+    assert_eq!(moved.best_route().unwrap().start, 7);
+    // Every way in is turned back out until the attack panel is shifted past the arrows.
+    let mut lost = BossBoard::default();
+    lost.panels[0][0] = Panel::Attack;
+    lost.panels[1] = [arrow(Direction::Out); NUM_ANGLES as usize];
+    assert!(lost.best_route().is_none());
+    let solution = boss::find_solution(&lost, &one_turn()).unwrap().unwrap();
+    assert_eq!(solution.moves.len(), 1);
+    assert_eq!(solution.board, lost.moved(&solution.moves[0]).unwrap());
+    assert_eq!(solution.board.best_route(), Some(solution.route));
+}
+
+#[test]
+fn boss_panel_codes_are_all_panels() {
+    use crate::boss::Panel;
+    // The magic circles are last.
+    for code in 0..104 {
+        assert_eq!(Panel::from_code(code).unwrap().code(), code);
+    }
+    assert!(Panel::from_code(104).is_err());
+}
+
+#[test]
+fn boss_fights_follow_the_pattern() {
+    use crate::boss::*;
+    let mut board = BossBoard::default();
+    board.panels[2][0] = Panel::Attack;
+    // The boss spins the outer rings after every attack, which doesn't stop Mario walking in.
+    let spin = RingMovement::Ring { r: 2, amount: 1, clockwise: true };
+    let turn = BossTurn { moves: vec![spin], panels: vec![] };
+    let pattern = BossPattern { turns: vec![turn], repeat: true };
+    assert_eq!(pattern.after(5, &board).unwrap().panels[2][1], Panel::Attack);
+    let plan = plan_fight(&board, &pattern, 3, &one_turn()).unwrap();
+    assert_eq!(plan.turns.len(), 3);
+    assert!(plan.turns.iter().all(|turn| turn.moves.is_empty()));
+    assert_eq!(plan.turns[2].route.start, 2);
+    // Walling off the attack panel with an outward arrow takes a movement to get around.
+    let wall = PlacedPanel { r: 3, th: 1, panel: Panel::Arrow { direction: Direction::Out } };
+    let turn = BossTurn { moves: vec![spin], panels: vec![wall] };
+    let plan = plan_fight(&board, &BossPattern { turns: vec![turn], repeat: false }, 3, &one_turn());
+    let plan = plan.unwrap();
+    assert_eq!(plan.turns.len(), 3);
+    assert!(plan.turns[0].moves.is_empty());
+    assert_eq!(plan.turns[1].moves.len(), 1);
+    // Covering the attack panel leaves nothing to plan after the first turn.
+    let cover = PlacedPanel { r: 2, th: 1, panel: Panel::Empty };
+    let turn = BossTurn { moves: vec![spin], panels: vec![cover] };
+    let plan = plan_fight(&board, &BossPattern { turns: vec![turn], repeat: false }, 3, &one_turn());
+    assert_eq!(plan.unwrap().turns.len(), 1);
+    let off_arena = BossTurn { moves: vec![], panels: vec![PlacedPanel { r: 4, ..wall }] };
+    let pattern = BossPattern { turns: vec![off_arena], repeat: false };
+    assert!(plan_fight(&board, &pattern, 1, &one_turn()).is_err());
+    let undefined = PlacedPanel { panel: Panel::Hazard { kind: 0 }, ..wall };
+    let undefined = BossTurn { moves: vec![], panels: vec![undefined] };
+    let pattern = BossPattern { turns: vec![undefined], repeat: false };
+    assert!(plan_fight(&board, &pattern, 1, &one_turn()).is_err());
+}
+
+#[test]
+fn boss_switches_raise_and_lower_bridges() {
+    use crate::boss::*;
+    let arrow = |direction| Panel::Arrow { direction };
+    let mut board = BossBoard::default();
+    // Entering at angle 0 crosses the switch, raising the bridge at angle 1.
+    board.panels[3][0] = Panel::Switch { group: 1 };
+    board.panels[2][0] = arrow(Direction::Clockwise);
+    board.panels[2][1] = Panel::Bridge { group: 1, on: true };
+    board.panels[2][2] = Panel::SwitchArrow { group: 1, on: Direction::In, off: Direction::Out };
+    board.panels[1][2] = Panel::Attack;
+    // Entering right at the bridge finds it lowered.
+    board.panels[3][1] = Panel::Action;
+    assert!(board.check().is_ok());
+    assert!(board.walk(1).is_none());
+    assert_eq!(board.walk(0).unwrap().path.len(), 5);
+    // Starting with the switches on, crossing the switch turns them off.
+    assert!(BossBoard { switches: 1 << 1, ..board.clone() }.walk(0).is_none());
+    let moved = board.moved(&RingMovement::Row { th: 0, amount: 3, outward: true }).unwrap();
+    let back = moved.moved(&RingMovement::Row { th: 0, amount: 3, outward: false }).unwrap();
+    assert_eq!(back, board);
+    let mut bad = board.clone();
+    bad.panels[0][0] = Panel::Switch { group: SWITCH_GROUPS };
+    assert!(bad.check().is_err());
+    assert!(BossBoard { switches: 1 << SWITCH_GROUPS, ..board }.check().is_err());
+}
+
+#[test]
+fn boss_routes_pick_up_what_theyre_weighted_to() {
+    use crate::boss::*;
+    let arrow = |direction| Panel::Arrow { direction };
+    let mut board = BossBoard::default();
+    board.panels[1][0] = Panel::Attack;
+    // Straight in at angle 0 is an action panel; around from angle 3 are two hearts.
+    board.panels[2][0] = Panel::Action;
+    board.panels[3][3] = arrow(Direction::Counterclockwise);
+    board.panels[3][2] = Panel::Heart;
+    board.panels[3][1] = Panel::Heart;
+    board.panels[3][0] = arrow(Direction::In);
+    let route = board.best_route().unwrap();
+    assert_eq!((route.start, route.actions, route.chests, route.hearts), (0, 1, 0, 0));
+    let weights = RouteWeights { heart: 2, ..Default::default() };
+    let healing = BossBoard { weights, ..board };
+    let route = healing.best_route().unwrap();
+    assert_eq!((route.start, route.actions, route.chests, route.hearts), (3, 1, 0, 2));
+    assert_eq!(healing.weights.score(&route), 5);
+}
+
+#[test]
+fn boss_hazards_block_or_hurt_mario() {
+    use crate::boss::*;
+    let hazards = vec![
+        Hazard { name: "water".to_string(), effect: HazardEffect::Block },
+        Hazard { name: "fire".to_string(), effect: HazardEffect::Damage { damage: 2 } },
+    ];
+    let mut board = BossBoard { hazards, ..Default::default() };
+    board.panels[0][0] = Panel::Attack;
+    board.panels[1][0] = Panel::Arrow { direction: Direction::Clockwise };
+    board.panels[1][1] = Panel::Attack;
+    // Straight in at angle 1 is flooded; at angle 0 it's burning.
+    board.panels[2][1] = Panel::Hazard { kind: 0 };
+    board.panels[3][0] = Panel::Hazard { kind: 1 };
+    assert!(board.check().is_ok());
+    assert!(board.walk(1).is_none());
+    let route = board.walk(0).unwrap();
+    assert_eq!(route.damage, 2);
+    assert_eq!(board.best_route(), Some(route));
+    // Another way in around the fire is better, even though it's longer.
+    board.panels[3][11] = Panel::Arrow { direction: Direction::In };
+    board.panels[2][11] = Panel::Arrow { direction: Direction::Clockwise };
+    board.panels[2][0] = Panel::Arrow { direction: Direction::In };
+    let route = board.best_route().unwrap();
+    assert_eq!((route.start, route.damage), (11, 0));
+    board.panels[2][2] = Panel::Hazard { kind: 2 };
+    assert!(board.check().is_err());
+}
+
+#[test]
+fn boss_magic_circles_are_worth_a_detour() {
+    use crate::boss::*;
+    let circle = |spell| Panel::MagicCircle { spell };
+    let mut board = BossBoard::default();
+    board.panels[0][0] = Panel::Attack;
+    board.panels[2][0] = circle(Spell::Attack);
+    // Around from angle 2 also picks up the 1000-Fold Arms before going in.
+    board.panels[3][2] = Panel::Arrow { direction: Direction::Counterclockwise };
+    board.panels[3][1] = circle(Spell::ThousandFoldArms);
+    board.panels[3][0] = Panel::Arrow { direction: Direction::In };
+    let route = board.best_route().unwrap();
+    assert_eq!(route.start, 2);
+    assert_eq!(route.spells, [Spell::ThousandFoldArms, Spell::Attack]);
+    assert_eq!(board.weights.score(&route), 5);
+    // A circle off every route is only moved onto one when turns are weighed against it.
+    let mut board = BossBoard::default();
+    board.panels[0][0] = Panel::Attack;
+    board.panels[2][3] = circle(Spell::ThousandFoldArms);
+    let solution = boss::find_solution(&board, &one_turn()).unwrap().unwrap();
+    assert!(solution.moves.is_empty());
+    board.weights.turn = Some(1);
+    let solution = boss::find_solution(&board, &one_turn()).unwrap().unwrap();
+    assert_eq!(solution.moves.len(), 1);
+    assert_eq!(solution.route.spells, [Spell::ThousandFoldArms]);
+}
+
+#[test]
+fn scripted_boards_keep_the_toads_safe() {
+    use crate::scripted::Script;
+    // The hammer that clears the board as it is lands on the Toad next to the enemy.
+    let board = [0b1, 0, 0, 0];
+    let spin = RingMovement::Ring { r: 0, amount: 3, clockwise: true };
+    let script = Script { between_turns: vec![vec![spin]], repeat: true };
+    let options = SolveOptions { toads: Some([0b10, 0, 0, 0]), ..Default::default() };
+    let solution = scripted::find_solution(board, &script, &options).unwrap().unwrap();
+    let rules = preset::Rules::default();
+    let goal = toads::ToadGoal::new(options.toads.unwrap(), rules, Rc::new(rules));
+    let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+    assert!(!moves.is_empty());
+    assert_eq!(goal.toads_hit(solution.result, &script.played(&moves)), 0);
+}
+
+#[test]
+fn hp_solves_keep_the_toads_safe() {
+    use crate::binding::Hazard;
+    use crate::hp::EnemyHp;
+    // The same board as a Toad solve, with every enemy taking two hits.
+    let board = [0b1, 0b1, 0, 0];
+    let mut hp = EnemyHp { damage: 1, ..Default::default() };
+    hp.hp[0][0] = 2;
+    hp.hp[1][0] = 2;
+    let options = SolveOptions { toads: Some([1 << 11 | 0b10, 0, 0, 0]), ..Default::default() };
+    let solution = hp::find_solution(board, &hp, &options).unwrap().unwrap();
+    assert!(solution.perfect && !solution.moves.is_empty());
+    assert!(solution.hazards.iter().all(|hazard| hazard.hazard == Hazard::Toad));
+    assert!(!solution.hazards.is_empty());
+    let partner = SolveOptions { partner: Some(partners::Partner::Bobby), ..Default::default() };
+    assert!(hp::find_solution(board, &hp, &partner).is_err());
+}
+
+#[test]
+fn transformations_keep_the_toads_safe() {
+    use crate::evaluator::find_transformation;
+    // The enemy is already in place, but the hammer at it lands on the Toad next to it.
+    let board = [0b1, 0, 0, 0];
+    let options = SolveOptions { toads: Some([0b10, 0, 0, 0]), ..Default::default() };
+    let solution = find_transformation(board, board, &options).unwrap().unwrap();
+    assert_eq!(solution.result, board);
+    let rules = preset::Rules::default();
+    let goal = toads::ToadGoal::new(options.toads.unwrap(), rules, Rc::new(rules));
+    let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+    assert!(!moves.is_empty());
+    assert_eq!(goal.toads_hit(solution.result, &moves), 0);
+    let tolerant = SolveOptions { tolerance: Some(1), ..Default::default() };
+    assert!(find_transformation(board, board, &tolerant).is_err());
+}
+
+#[test]
+fn sized_first_moves_are_checked_against_the_board() {
+    use crate::sized::{find_solution, SizedBoard};
+    // Ring 5 only exists on the bigger board, and can't move while it's locked.
+    let board = SizedBoard { rings: 6, angles: 8, cells: vec![0b1, 0, 0, 0, 0, 0b10] };
+    let first_move = RingMovement::Ring { r: 5, amount: 1, clockwise: false };
+    let options = SolveOptions { first_move: Some(first_move), ..Default::default() };
+    assert!(options.check().is_err());
+    let solution = find_solution(&board, &options).unwrap().unwrap();
+    assert_eq!(solution.moves[0], first_move);
+    let constraints = MoveConstraints { locked: 1 << 5, ..Default::default() };
+    let locked = SolveOptions { constraints, ..options };
+    assert!(find_solution(&board, &locked).is_err());
+}
+
+#[test]
+fn spoiler_free_progress_keeps_the_best_board_hidden() {
+    let board = [0b1, 0b1, 0, 0];
+    let best = find_solution(board, MAX_TURNS, &SolveOptions::default());
+    let shown = JsObserver::new(&SolveOptions::default()).best_out(&best);
+    assert_eq!(shown.unwrap().moves, best.as_ref().unwrap().moves);
+    let options = SolveOptions { spoiler_free: true, ..Default::default() };
+    assert!(JsObserver::new(&options).best_out(&best).is_none());
+}
+
+#[test]
+fn solution_counts_stop_at_the_budget() {
+    let board = [0b1, 0b10, 0, 0];
+    let counted = count_solutions(board, 1, &mut Budget::new(&SolveOptions::default()));
+    assert!(counted.is_some_and(|count| count > 0));
+    // A partial count would be wrong, so a count that runs out of budget is no count.
+    let tight = SolveOptions { max_nodes: Some(10), ..Default::default() };
+    let mut budget = Budget::new(&tight);
+    assert_eq!(count_solutions(board, 2, &mut budget), None);
+    assert!(budget.nodes <= 10);
+}
+
+/// A board with no two rings or rows alike, so any movement that changes a ring or row
+/// changes the board.
+const UNEVEN: Ring = [0b1010_0110_1001, 0b0110_1100_0011, 0b1111_0000_1010, 0b0001_0111_1100];
+
+/// Every movement of every ring and row, by up to a full turn either way, including the
+/// ones the search never makes.
+fn every_movement() -> impl Iterator<Item = RingMovement> {
+    let turn = NUM_ANGLES as i16;
+    let rings = (0..NUM_RINGS).flat_map(move |r| {
+        (-turn..=turn).flat_map(move |amount| {
+            [true, false].map(|clockwise| RingMovement::Ring { r, amount, clockwise })
+        })
+    });
+    let length = (NUM_RINGS * 2) as i16;
+    let rows = (0..NUM_ANGLES).flat_map(move |th| {
+        (-length..=length).flat_map(move |amount| {
+            [true, false].map(|outward| RingMovement::Row { th, amount, outward })
+        })
+    });
+    rings.chain(rows)
+}
+
+/// Boards and the solutions found for them, some best-effort or truncated.
+fn solved_boards() -> Vec<(Ring, Solution)> {
+    let boards = [[0b1, 0, 0, 0], [0b1, 0b10, 0, 0], [0b11, 0, 0, 0b11 << 6], UNEVEN];
+    let options = SolveOptions { max_nodes: Some(100_000), ..Default::default() };
+    let solve = |&board: &Ring| Some((board, find_solution(board, MAX_TURNS, &options)?));
+    boards.iter().filter_map(solve).collect()
+}
+
+#[test]
+fn encoded_solutions_decode_to_the_same_solution() {
+    for movement in every_movement() {
+        let byte = encoding::encode_movement(&movement);
+        let decoded = encoding::decode_movement(byte).unwrap();
+        assert_eq!(apply_movement(UNEVEN, &decoded), apply_movement(UNEVEN, &movement));
+        assert_eq!(encoding::encode_movement(&decoded), byte, "{:?}", movement);
+    }
+    let solutions = solved_boards();
+    assert!(solutions.iter().any(|(_, solution)| solution.truncated));
+    for (_, solution) in solutions {
+        let bytes = encoding::solution_to_bytes(&solution).unwrap();
+        let decoded = encoding::solution_from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.moves, solution.moves);
+        assert_eq!(decoded.result, solution.result);
+        assert_eq!(decoded.truncated, solution.truncated);
+        assert_eq!(decoded.perfect, solution.perfect);
+    }
+}
+
+#[test]
+fn shared_boards_decode_to_the_same_board_and_solution() {
+    for (board, solution) in solved_boards() {
+        let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+        let code = share::encode(&board, Some(&moves)).unwrap();
+        let shared = share::decode(&code).unwrap();
+        assert_eq!(shared.ring, board);
+        let shared_solution = shared.solution.unwrap();
+        assert_eq!(shared_solution.moves, solution.moves);
+        assert_eq!(shared_solution.result, solution.result);
+        // Padding is ignored, and so is a board without a solution.
+        let padded = format!("{}{}", code, "=".repeat((4 - code.len() % 4) % 4));
+        assert_eq!(share::decode(&padded).unwrap().ring, board);
+        let alone = share::decode(&share::encode(&board, None).unwrap()).unwrap();
+        assert_eq!(alone.ring, board);
+        assert!(alone.solution.is_none());
+    }
+}
+
+#[test]
+fn written_movements_parse_to_the_same_movement() {
+    for movement in every_movement() {
+        let text = movement.to_string();
+        let parsed: RingMovement = text.parse().unwrap();
+        assert_eq!(apply_movement(UNEVEN, &parsed), apply_movement(UNEVEN, &movement));
+        assert_eq!(parsed.to_string(), text);
+        // Only negative amounts are written the other way.
+        let (RingMovement::Ring { amount, .. } | RingMovement::Row { amount, .. }) = movement;
+        if amount >= 0 {
+            assert_eq!(parsed, movement);
+        }
+        let sloppy: RingMovement = format!("  {}\t", text.to_lowercase()).parse().unwrap();
+        assert_eq!(sloppy, parsed);
+    }
+}
+
+#[test]
+fn normalized_moves_move_the_board_the_same_way() {
+    use crate::normalize::{normalize_movement, normalize_moves};
+    let movements: Vec<RingMovement> = every_movement().collect();
+    for movement in &movements {
+        let moved = apply_movement(UNEVEN, movement);
+        match normalize_movement(movement) {
+            Some(normalized) => {
+                assert_eq!(apply_movement(UNEVEN, &normalized), moved);
+                assert_eq!(normalize_movement(&normalized), Some(normalized));
+            }
+            None => assert_eq!(moved, UNEVEN, "{:?}", movement),
+        }
+    }
+    for (a, b) in movements.iter().flat_map(|a| movements.iter().map(move |b| (a, b))) {
+        let normalized = normalize_moves([a, b]);
+        let moved = apply_movement(apply_movement(UNEVEN, a), b);
+        assert_eq!(normalized.iter().fold(UNEVEN, apply_movement), moved);
+        assert_eq!(normalize_moves(&normalized), normalized);
+    }
+    // The search's own movements are already normalized.
+    iterate_movements(UNEVEN, &MoveConstraints::default(), |movement, _| -> Option<()> {
+        assert_eq!(normalize_movement(&movement), Some(movement));
+        None
+    });
+}
+
+#[test]
+fn undone_movements_are_redone_the_same_way() {
+    use crate::board::RingBoard;
+    let mut board = RingBoard::with_options(UNEVEN, SolveOptions::default());
+    let moves: Vec<RingMovement> = every_movement().step_by(37).collect();
+    let mut boards = vec![UNEVEN];
+    for movement in &moves {
+        board.make(movement).unwrap();
+        boards.push(board.ring());
+    }
+    assert_eq!(board.history(), moves);
+    for (movement, before) in moves.iter().zip(&boards).rev() {
+        assert_eq!(board.undo(), Some(*movement));
+        assert_eq!(board.ring(), *before);
+    }
+    assert_eq!(board.undo(), None);
+    for (movement, after) in moves.iter().zip(&boards[1..]) {
+        assert_eq!(board.redo(), Some(*movement));
+        assert_eq!(board.ring(), *after);
+    }
+    assert_eq!(board.redo(), None);
+    assert_eq!(board.history(), moves);
+    // A new movement after undoing one leaves nothing to redo.
+    board.undo();
+    board.make(&moves[0]).unwrap();
+    assert_eq!(board.redo(), None);
+}
+
+#[test]
+fn saved_packs_load_as_the_same_pack() {
+    use crate::pack::{PackMetadata, PackPuzzle, PuzzlePack, PACK_VERSION};
+    let metadata = PackMetadata {
+        name: "Earth Vellumental Temple".to_string(),
+        author: Some("kupiakos".to_string()),
+        description: None,
+    };
+    let mut pack = PuzzlePack::new(metadata);
+    for (i, (board, solution)) in solved_boards().into_iter().enumerate() {
+        pack.puzzles.push(PackPuzzle {
+            name: (i % 2 == 0).then(|| format!("Room {}", i + 1)),
+            board,
+            solution: (i % 3 != 0).then(|| solution.moves.into_iter().collect()),
+            tags: if i == 1 { vec!["easy".to_string()] } else { Vec::new() },
+        });
+    }
+    let json = pack.to_json().unwrap();
+    let loaded = PuzzlePack::from_json(&json).unwrap();
+    assert_eq!(loaded.version, PACK_VERSION);
+    assert_eq!(loaded.metadata.author, pack.metadata.author);
+    for (loaded, puzzle) in loaded.puzzles.iter().zip(&pack.puzzles) {
+        assert_eq!(loaded.name, puzzle.name);
+        assert_eq!(loaded.board, puzzle.board);
+        assert_eq!(loaded.solution, puzzle.solution);
+        assert_eq!(loaded.tags, puzzle.tags);
+    }
+    assert_eq!(loaded.puzzles.len(), pack.puzzles.len());
+    assert_eq!(loaded.to_json().unwrap(), json);
+}
+
+
+#[test]
+fn coin_plans_buy_the_turns_accessories_dont_give() {
+    use crate::accessories::Accessory;
+    use crate::coins::{cheapest_plan, CoinCosts};
+    // The board needs three turns, with one from the rules and one from an accessory.
+    let board = THREE_TURNS;
+    let rules = preset::Rules { max_turns: 1, ..Default::default() };
+    let options = SolveOptions {
+        preset: preset::Preset::Custom,
+        rules: Some(rules),
+        accessories: vec![Accessory::TurnPlus { turns: 1 }],
+        ..Default::default()
+    };
+    let costs = CoinCosts { turn: Some(10), ..Default::default() };
+    let plan = cheapest_plan(board, &costs, &options).unwrap().unwrap();
+    assert_eq!((plan.coins, plan.extra_turns), (10, 1));
+    assert_eq!(plan.solution.moves.len(), 3);
+}
+
+#[test]
+fn accessories_cant_add_endless_turns() {
+    use crate::accessories::{Accessory, Effects};
+    let turns = [Accessory::TurnPlus { turns: u16::MAX }, Accessory::TurnPlus { turns: u16::MAX }];
+    let effects = Effects::of(&turns);
+    assert_eq!(effects.turns, u16::MAX);
+    assert!(effects.check().is_err());
+    assert!(Effects::of(&[Accessory::TurnPlus { turns: 1 }]).check().is_ok());
+}
+
+/// Options that ask for the moves to be kept hidden.
+fn spoiler_free() -> SolveOptions {
+    SolveOptions { spoiler_free: true, ..Default::default() }
+}
+
+#[test]
+fn sized_solves_arent_spoiler_free() {
+    use crate::sized::{find_solution, SizedBoard};
+    let board = SizedBoard { rings: 3, angles: 8, cells: vec![0b1, 0b10, 0] };
+    assert!(find_solution(&board, &SolveOptions::default()).is_ok());
+    assert!(find_solution(&board, &spoiler_free()).is_err());
+}
+
+#[test]
+fn coin_plans_arent_spoiler_free() {
+    use crate::coins::{cheapest_plan, CoinCosts};
+    let board = [0b1, 0b10, 0, 0];
+    let costs = CoinCosts { turn: Some(10), ..Default::default() };
+    assert!(cheapest_plan(board, &costs, &SolveOptions::default()).is_ok());
+    assert!(cheapest_plan(board, &costs, &spoiler_free()).is_err());
+}
+
+#[test]
+fn no_damage_plans_arent_spoiler_free() {
+    let board = [0b1, 0b10, 0, 0];
+    assert!(no_damage::plan(board, &SolveOptions::default()).is_ok());
+    assert!(no_damage::plan(board, &spoiler_free()).is_err());
+}
+
+#[test]
+fn wave_plans_arent_spoiler_free() {
+    use crate::waves::{solve_waves, Waves};
+    let waves = Waves { waves: vec![[0b1, 0b10, 0, 0], [0b11, 0, 0, 0]], ..Default::default() };
+    assert!(solve_waves(&waves, &SolveOptions::default()).is_ok());
+    assert!(solve_waves(&waves, &spoiler_free()).is_err());
+}
+
+/// A board that needs three turns.
+const THREE_TURNS: Ring = [0b0000_0011_0001, 0b0000_0101_0000, 0b0000_0001_0001, 0b0000_0001_0000];
+
+/// The default rules with only three turns.
+fn three_turns() -> SolveOptions {
+    let rules = preset::Rules { max_turns: 3, ..Default::default() };
+    SolveOptions { preset: preset::Preset::Custom, rules: Some(rules), ..Default::default() }
+}
+
+#[test]
+fn moves_already_made_use_up_turns() {
+    use crate::coach::Coach;
+    // A full turn of a ring wastes a turn without moving anything.
+    let wasted = RingMovement::Ring { r: 0, amount: NUM_ANGLES as i16, clockwise: true };
+    let options = three_turns();
+    let solution = find_solution_from(THREE_TURNS, &[], &options).unwrap().unwrap();
+    assert!(solution.perfect);
+    let after = find_solution_from(THREE_TURNS, &[wasted], &options).unwrap();
+    assert!(after.is_none_or(|solution| !solution.perfect));
+    let mut coach = Coach::start(THREE_TURNS, options).unwrap();
+    assert_eq!(coach.turns_left(), 3);
+    assert!(coach.next_move().is_some());
+    let update = coach.play(&wasted).unwrap();
+    assert_eq!(update.extra_turns, None);
+    assert_eq!(update.next, None);
+}
+
+#[test]
+fn coached_boards_are_solved_by_the_goal_of_the_options() {
+    use crate::coach::Coach;
+    let wasted = RingMovement::Ring { r: 0, amount: NUM_ANGLES as i16, clockwise: true };
+    // Every enemy can be hit, but the hammer that does it lands on the Toad next to it.
+    let board = [0b1, 0, 0, 0];
+    let options = SolveOptions { toads: Some([0b10, 0, 0, 0]), ..Default::default() };
+    let mut coach = Coach::start(board, options).unwrap();
+    assert!(!coach.play(&wasted).unwrap().solved);
+    // One attack short of hitting every enemy, which the tolerance forgives.
+    let board = [0b1, 0, 0b100, 0];
+    assert_eq!(evaluate(board).excess_actions(), 1);
+    let tolerant = SolveOptions { tolerance: Some(1), ..Default::default() };
+    let mut coach = Coach::start(board, tolerant).unwrap();
+    assert!(coach.play(&wasted).unwrap().solved);
+}
+
+
+#[test]
+fn rewards_count_the_time_left_on_the_countdown() {
+    use crate::accessories::Accessory;
+    use crate::countdown::Timer;
+    use crate::rewards::{estimate_with, RewardRates};
+    let solution = find_solution(THREE_TURNS, MAX_TURNS, &three_turns()).unwrap();
+    let timer = Timer { seconds: 20.0, ..Default::default() };
+    let rewards = Some(RewardRates::default());
+    let options = SolveOptions { rewards, timer: Some(timer), ..three_turns() };
+    let coins = estimate_with(&solution, &options).coins;
+    let rewards = Some(RewardRates { coins_per_second: 0, ..Default::default() });
+    let enemy_coins = estimate_with(&solution, &SolveOptions { rewards, ..options.clone() }).coins;
+    let time_left = timer.seconds - timer.seconds_for(&solution.moves);
+    assert_eq!(coins - enemy_coins, time_left as u32);
+    // Time bought with an accessory pays out too, and a solution too slow for the clock
+    // earns nothing for time.
+    let accessories = vec![Accessory::TimePlus { seconds: 10.0 }];
+    let longer = SolveOptions { accessories, ..options.clone() };
+    assert_eq!(estimate_with(&solution, &longer).coins, coins + 10);
+    let late = SolveOptions { timer: Some(Timer { seconds: 1.0, ..timer }), ..options };
+    assert_eq!(estimate_with(&solution, &late).coins, enemy_coins);
+}
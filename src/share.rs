@@ -0,0 +1,129 @@
+//! Short, URL-safe codes for a board and optionally a solution, for permalinks and QR
+//! codes.
+//!
+//! A code is base64url, without padding, of:
+//! - a header byte: bits 0-3 are the number of moves, bit 4 is set if there's a solution,
+//!   and bits 5-7 are the version;
+//! - the board, packed into 6 bytes as in `encoding`;
+//! - one byte per move of the solution, as in `encoding::encode_movement`.
+//!
+//! A board alone takes 10 characters, and every move adds one or two more.
+
+use crate::encoding::{decode_board, decode_movement, encode_board, encode_movement, BOARD_BYTES};
+use crate::{
+    apply_movement, check_movement, check_ring, evaluate, Result, Ring, RingMovement, Solution,
+};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// The version written in the header.
+pub const SHARE_VERSION: u8 = 1;
+
+/// The most moves a code can hold.
+const MAX_MOVES: usize = 0xF;
+const HAS_SOLUTION: u8 = 1 << 4;
+const VERSION_SHIFT: u8 = 5;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// A board and its solution, read back from a code.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedPuzzle {
+    pub ring: Ring,
+    /// The solution shared with the board, re-evaluated from its moves, if any.
+    pub solution: Option<Solution>,
+}
+
+/// Writes `bytes` as base64url, without padding.
+fn to_base64url(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity((bytes.len() * 4).div_ceil(3));
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| {
+            bits | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            text.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3F) as usize] as char);
+        }
+    }
+    text
+}
+
+/// Reads base64url, with or without padding.
+fn from_base64url(text: &str) -> std::result::Result<Vec<u8>, String> {
+    let text = text.trim_end_matches('=');
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    for chunk in text.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return Err("the code has a stray character at the end".to_string());
+        }
+        let mut bits = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = ALPHABET
+                .iter()
+                .position(|&a| a == c)
+                .ok_or_else(|| format!("{:?} can't be in a code", c as char))?;
+            bits |= (value as u32) << (18 - 6 * i);
+        }
+        bytes.extend((0..chunk.len() - 1).map(|i| (bits >> (16 - 8 * i)) as u8));
+    }
+    Ok(bytes)
+}
+
+/// Writes a code for `ring`, and for `moves` solving it if given.
+pub fn encode(ring: &Ring, moves: Option<&[RingMovement]>) -> std::result::Result<String, String> {
+    check_ring(ring)?;
+    let has_solution = if moves.is_some() { HAS_SOLUTION } else { 0 };
+    let moves = moves.unwrap_or_default();
+    if moves.len() > MAX_MOVES {
+        let count = moves.len();
+        return Err(format!("{} moves is more than the {} a code can hold", count, MAX_MOVES));
+    }
+    for movement in moves {
+        check_movement(movement)?;
+    }
+    let mut bytes = Vec::with_capacity(1 + BOARD_BYTES + moves.len());
+    bytes.push(SHARE_VERSION << VERSION_SHIFT | has_solution | moves.len() as u8);
+    bytes.extend_from_slice(&encode_board(ring));
+    bytes.extend(moves.iter().map(encode_movement));
+    Ok(to_base64url(&bytes))
+}
+
+/// Reads a code written by `encode`.
+pub fn decode(code: &str) -> std::result::Result<SharedPuzzle, String> {
+    let bytes = from_base64url(code.trim())?;
+    let header = *bytes.first().ok_or("the code is empty")?;
+    let version = header >> VERSION_SHIFT;
+    if version != SHARE_VERSION {
+        return Err(format!("code version {} isn't the supported {}", version, SHARE_VERSION));
+    }
+    let count = (header & MAX_MOVES as u8) as usize;
+    let expected = 1 + BOARD_BYTES + count;
+    if bytes.len() != expected {
+        return Err(format!("the code is {} bytes, expected {}", bytes.len(), expected));
+    }
+    let ring = decode_board(&bytes[1..=BOARD_BYTES]);
+    let moves = bytes[1 + BOARD_BYTES..]
+        .iter()
+        .map(|&byte| decode_movement(byte).map_err(|e| e.to_string()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let solution = (header & HAS_SOLUTION != 0).then(|| {
+        let result = moves.iter().fold(ring, apply_movement);
+        Solution::new(moves, evaluate(result), false)
+    });
+    Ok(SharedPuzzle { ring, solution })
+}
+
+/// Write a short URL-safe code for a board, and the moves of a solution if given.
+#[wasm_bindgen(skip_typescript)]
+pub fn encode_share(ring: JsValue, moves: JsValue) -> Result<String> {
+    let ring: Ring = serde_wasm_bindgen::from_value(ring)?;
+    let moves: Option<Vec<RingMovement>> = serde_wasm_bindgen::from_value(moves)?;
+    Ok(encode(&ring, moves.as_deref())?)
+}
+
+/// Read a code written by `encode_share`, as a `SharedPuzzle`.
+#[wasm_bindgen(skip_typescript)]
+pub fn decode_share(code: &str) -> Result<JsValue> {
+    Ok(serde_wasm_bindgen::to_value(&decode(code)?)?)
+}
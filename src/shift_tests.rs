@@ -0,0 +1,201 @@
+//! Exhaustive checks of how rows are paired and laid out for shifting: every angle, every
+//! pattern of enemies in a row, and every shift amount.
+
+use super::*;
+
+const ROW_CELLS: u16 = NUM_RINGS * 2;
+const HALF: u16 = NUM_ANGLES / 2;
+
+/// Boards that everything is checked on top of, so shifts are seen not to disturb the
+/// rest of the board.
+const BACKGROUNDS: [Ring; 3] = [
+    [0; 4],
+    [0xFFF; 4],
+    [0b1010_0110_1001, 0b0110_1100_0011, 0b1111_0000_1010, 0b0001_0111_1100],
+];
+
+/// Every cell of the row at `th` and its opposite angle.
+fn row_mask(th: u16) -> Ring {
+    let cells = (1 << (th % HALF)) | (1 << (th % HALF + HALF));
+    [cells; 4]
+}
+
+/// `background` with the row at `th` replaced by `pattern`, in the order of `row_view`.
+fn with_row(background: Ring, th: u16, pattern: u16) -> Ring {
+    let bits = if th < HALF { pattern } else { pattern.reverse_bits() >> (16 - ROW_CELLS) };
+    let mut ring = background;
+    Game::set_row(&mut ring, th % HALF, Row(bits));
+    ring
+}
+
+fn view_bits(ring: &Ring, th: u16) -> u16 {
+    let view = row_view(ring, th).unwrap();
+    assert_eq!(view.th, th);
+    assert_eq!(view.opposite, (th + HALF) % NUM_ANGLES);
+    view.cells.iter().enumerate().map(|(i, &cell)| (cell as u16) << i).sum()
+}
+
+/// Moves every cell `amount` places later in the row, wrapping around.
+fn rotate_pattern(pattern: u16, amount: u16) -> u16 {
+    let row: Row = Row(pattern);
+    row.rotate_left(amount % ROW_CELLS).value()
+}
+
+fn outside_row(ring: &Ring, th: u16) -> Ring {
+    let mask = row_mask(th);
+    [0, 1, 2, 3].map(|r| ring[r] & !mask[r])
+}
+
+fn enemies(ring: &Ring) -> u32 {
+    ring.iter().map(|subring| subring.count_ones()).sum()
+}
+
+#[test]
+fn get_row_and_set_row_are_inverses() {
+    for background in BACKGROUNDS {
+        for th in 0..HALF {
+            for pattern in 0..(1 << ROW_CELLS) {
+                let mut ring = background;
+                Game::set_row(&mut ring, th, Row(pattern));
+                assert_eq!(get_row(&ring, th).value(), pattern);
+                assert_eq!(outside_row(&ring, th), outside_row(&background, th));
+            }
+            let mut ring = background;
+            Game::set_row(&mut ring, th, get_row(&background, th));
+            assert_eq!(ring, background);
+        }
+    }
+}
+
+#[test]
+fn row_view_pairs_opposite_angles() {
+    for background in BACKGROUNDS {
+        for th in 0..NUM_ANGLES {
+            for pattern in 0..(1 << ROW_CELLS) {
+                let ring = with_row(background, th, pattern);
+                assert_eq!(view_bits(&ring, th), pattern);
+                // The opposite angle sees the same cells in the reverse order.
+                let reversed = pattern.reverse_bits() >> (16 - ROW_CELLS);
+                assert_eq!(view_bits(&ring, (th + HALF) % NUM_ANGLES), reversed);
+                // The first cells are at `th`, from the inner ring outward.
+                for r in 0..NUM_RINGS {
+                    let at_th = ring[r as usize] & (1 << th) != 0;
+                    assert_eq!(at_th, pattern & (1 << r) != 0);
+                }
+            }
+        }
+    }
+    assert!(row_view(&[0; 4], NUM_ANGLES).is_err());
+}
+
+#[test]
+fn shifts_match_row_view_and_undo() {
+    for background in BACKGROUNDS {
+        for th in 0..NUM_ANGLES {
+            for pattern in 0..(1 << ROW_CELLS) {
+                let ring = with_row(background, th, pattern);
+                for amount in 0..ROW_CELLS as i16 {
+                    for outward in [false, true] {
+                        let shift = RingMovement::Row { th, amount, outward };
+                        let moved = apply_movement(ring, &shift);
+                        let expected = if outward {
+                            rotate_pattern(pattern, amount as u16)
+                        } else {
+                            rotate_pattern(pattern, ROW_CELLS - amount as u16)
+                        };
+                        assert_eq!(view_bits(&moved, th), expected, "{:?}", shift);
+                        assert_eq!(outside_row(&moved, th), outside_row(&ring, th));
+                        assert_eq!(enemies(&moved), enemies(&ring));
+
+                        let unshift = RingMovement::Row { th, amount, outward: !outward };
+                        assert_eq!(apply_movement(moved, &unshift), ring, "{:?}", shift);
+                        let negated = RingMovement::Row { th, amount: -amount, outward: !outward };
+                        assert_eq!(apply_movement(ring, &negated), moved);
+                        let wrapped = RingMovement::Row { th, amount: amount + ROW_CELLS as i16, outward };
+                        assert_eq!(apply_movement(ring, &wrapped), moved);
+                        // Shifting outward from one end is shifting inward from the other.
+                        let opposite = RingMovement::Row {
+                            th: (th + HALF) % NUM_ANGLES,
+                            amount,
+                            outward: !outward,
+                        };
+                        assert_eq!(apply_movement(ring, &opposite), moved);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn ring_shifts_match_apply_movement() {
+    for background in BACKGROUNDS {
+        for th in 0..HALF {
+            for pattern in 1..(1 << ROW_CELLS) {
+                let ring = with_row(background, th, pattern);
+                let shifts = RingShifts::<4, NUM_ANGLES>::new(ring, th).unwrap();
+                let mut seen = Vec::new();
+                for (moved, movement) in shifts.take(ROW_CELLS as usize - 1) {
+                    assert_eq!(apply_movement(ring, &movement), moved, "{:?}", movement);
+                    if let RingMovement::Row { th: moved_th, amount, .. } = movement {
+                        assert_eq!(moved_th, th);
+                        assert!(amount > 0 && amount <= NUM_RINGS as i16);
+                    } else {
+                        panic!("{:?} isn't a shift", movement);
+                    }
+                    seen.push(movement);
+                }
+                // Every shift but the identity is made exactly once.
+                let mut keys: Vec<_> = seen.iter().map(RingMovement::sort_key).collect();
+                keys.sort();
+                keys.dedup();
+                assert_eq!(keys.len(), ROW_CELLS as usize - 1);
+            }
+            assert!(RingShifts::<4, NUM_ANGLES>::new(with_row(background, th, 0), th).is_none());
+        }
+    }
+}
+
+#[test]
+fn zig_zag_order() {
+    let outputs: Vec<(Row, i16)> = ZigZagBits::new(Row(0b0000_0010)).take(7).collect();
+    let expected = [
+        (0b0000_0100, 1),
+        (0b0000_0001, -1),
+        (0b0000_1000, 2),
+        (0b1000_0000, -2),
+        (0b0001_0000, 3),
+        (0b0100_0000, -3),
+        (0b0010_0000, 4),
+    ];
+    let outputs: Vec<_> = outputs.into_iter().map(|(row, amount)| (row.value(), amount)).collect();
+    assert_eq!(outputs, expected);
+}
+
+/// A board small enough to check every board and every movement on it: 2 rings of 4
+/// angles.
+type Small = Layout<2, 4>;
+
+#[test]
+fn small_layout_movements_are_exhaustive() {
+    let constraints = MoveConstraints::default();
+    for bits in 0..(1 << 8) {
+        let ring = [bits & 0xF, bits >> 4];
+        let mut seen = Vec::new();
+        Small::iterate_movements(ring, &constraints, |movement, moved| {
+            assert_eq!(Small::apply_movement(ring, &movement), moved, "{:?}", movement);
+            assert_eq!(Small::apply_movement(moved, &movement.inverse()), ring, "{:?}", movement);
+            seen.push(movement.sort_key());
+            None::<()>
+        });
+        // Every rotation of an occupied ring and shift of an occupied row but the
+        // identity, each once.
+        let rings = ring.iter().filter(|&&subring| subring != 0).count();
+        let rows = (0..2).filter(|&th| Small::get_row(&ring, th).value() != 0).count();
+        let count = seen.len();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), count);
+        assert_eq!(count, rings * 3 + rows * 3);
+    }
+}
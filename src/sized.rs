@@ -0,0 +1,311 @@
+//! Boards of other sizes than the game's 4 rings of 12 angles, e.g. fan-made variants with
+//! 3 rings or 16 angles, given their dimensions alongside the enemies.
+//!
+//! ```json
+//! {"rings": 3, "angles": 16, "cells": [1, 0, 32768]}
+//! ```
+//!
+//! Each of `cells` is a ring, inner first, with bit `th` set if there's an enemy at angle
+//! `th`. The rules are the game's, scaled to the board: hammers hit the inner two rings at
+//! an angle and the next one clockwise, and a jump hits a line of enemies outward from the
+//! innermost one at its angle.
+//!
+//! Only `rotations`, `shifts`, and `locked` of the move constraints apply, since the rest
+//! are sized for the game's board.
+
+use crate::exhaustive::{Exhaustive, Moves};
+use crate::{
+    read_options_with, total_distance, unoriented, with_spoilers, Attack, MoveConstraints,
+    Result, RingMovement, SolveOptions, MAX_TURNS,
+};
+use serde::{Deserialize, Serialize};
+use std::convert::Infallible;
+use wasm_bindgen::prelude::*;
+
+/// The most rings a board may have, so a row fits in 16 bits.
+pub const MAX_RINGS: u16 = 8;
+/// The most angles a board may have, so a ring fits in 32 bits.
+pub const MAX_ANGLES: u16 = 32;
+
+/// A board of any size, with a bit mask of enemies for each ring.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizedBoard {
+    pub rings: u16,
+    pub angles: u16,
+    pub cells: Vec<u32>,
+}
+
+impl SizedBoard {
+    /// Checks that the board has a size the solver supports, and only has enemies on
+    /// panels that exist.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        if self.rings == 0 || self.rings > MAX_RINGS {
+            return Err(format!("rings must be between 1 and {}", MAX_RINGS));
+        }
+        if self.angles < 2 || self.angles > MAX_ANGLES || !self.angles.is_multiple_of(2) {
+            return Err(format!("angles must be even, and between 2 and {}", MAX_ANGLES));
+        }
+        if self.cells.len() != self.rings as usize {
+            return Err(format!("expected {} rings of cells, got {}", self.rings, self.cells.len()));
+        }
+        match self.cells.iter().position(|&subring| subring & !self.full() != 0) {
+            Some(r) => Err(format!("ring {} has bits set above angle {}", r, self.angles - 1)),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that a movement refers to a ring or row of this board.
+    pub fn check_movement(&self, movement: &RingMovement) -> std::result::Result<(), String> {
+        match *movement {
+            RingMovement::Ring { r, .. } if r >= self.rings => {
+                Err(format!("ring index {} out of range", r))
+            }
+            RingMovement::Row { th, .. } if th >= self.angles => {
+                Err(format!("row angle {} out of range", th))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// A mask with a bit for every angle.
+    fn full(&self) -> u32 {
+        u32::MAX >> (32 - self.angles)
+    }
+
+    fn rotate(&self, subring: u32, amount: u16) -> u32 {
+        let amount = amount % self.angles;
+        if amount == 0 {
+            return subring;
+        }
+        (subring << amount | subring >> (self.angles - amount)) & self.full()
+    }
+
+    /// The row at `th` and its opposite angle in shifting order, like `get_row`: the cells
+    /// at `th` from the inner ring outward, then those opposite from the outer ring inward.
+    fn row(&self, th: u16) -> u16 {
+        let opposite = th + self.angles / 2;
+        let last = self.rings * 2 - 1;
+        (0..self.rings).fold(0, |row, r| {
+            let subring = self.cells[r as usize];
+            let low = (subring >> th & 1) as u16;
+            let high = (subring >> opposite & 1) as u16;
+            row | low << r | high << (last - r)
+        })
+    }
+
+    fn set_row(&mut self, th: u16, row: u16) {
+        let opposite = th + self.angles / 2;
+        let last = self.rings * 2 - 1;
+        for r in 0..self.rings {
+            let subring = &mut self.cells[r as usize];
+            let low = (row >> r & 1) as u32;
+            let high = (row >> (last - r) & 1) as u32;
+            *subring = (*subring & !(1 << th) & !(1 << opposite)) | low << th | high << opposite;
+        }
+    }
+
+    /// The board after `movement`, which may be any movement `check_movement` accepts.
+    pub fn moved(&self, movement: &RingMovement) -> Self {
+        let mut board = self.clone();
+        match *movement {
+            RingMovement::Ring { r, amount, clockwise } => {
+                let amount = amount.rem_euclid(self.angles as i16) as u16;
+                let amount = if clockwise { amount } else { (self.angles - amount) % self.angles };
+                board.cells[r as usize] = self.rotate(self.cells[r as usize], amount);
+            }
+            RingMovement::Row { th, amount, outward } => {
+                let half = self.angles / 2;
+                let (th, outward) = if th >= half { (th - half, !outward) } else { (th, outward) };
+                let bits = self.rings * 2;
+                let amount = amount.rem_euclid(bits as i16) as u16;
+                let amount = if outward { amount } else { (bits - amount) % bits };
+                let row = self.row(th) as u32;
+                let row = if amount == 0 {
+                    row
+                } else {
+                    (row << amount | row >> (bits - amount)) & ((1 << bits) - 1)
+                };
+                board.set_row(th, row as u16);
+            }
+        }
+        board
+    }
+
+    /// The enemies at angle `th`, bit `r` for ring `r`.
+    fn column(&self, th: u16) -> u32 {
+        (0..self.rings).fold(0, |column, r| column | (self.cells[r as usize] >> th & 1) << r)
+    }
+
+    /// A mask of the rings hammers reach.
+    fn inner(&self) -> u32 {
+        (1 << self.rings.min(2)) - 1
+    }
+
+    /// The angles to aim hammers at to hit every angle in `targets`, as few as possible,
+    /// like `hammer_blocks`.
+    fn hammer_blocks(&self, mut targets: u32) -> Vec<u16> {
+        let start = (0..self.angles).find(|&th| targets >> th & 1 == 0).unwrap_or(0);
+        let mut blocks = Vec::new();
+        for offset in 1..=self.angles {
+            let th = (start + offset) % self.angles;
+            if targets >> th & 1 != 0 {
+                blocks.push(th);
+                targets &= !self.rotate(0b11, th);
+            }
+        }
+        blocks
+    }
+
+    /// The jumps and hammers that hit every enemy with the weapons given, or None if some
+    /// enemy can't be reached.
+    pub fn attack_plan(&self, jump: bool, hammer: bool) -> Option<Vec<Attack>> {
+        let inner = self.inner();
+        let mut targets = 0;
+        let mut jumps = Vec::new();
+        for th in 0..self.angles {
+            let column = self.column(th);
+            // Hammering the inner rings first leaves fewer lines to jump on, if it helps.
+            let (hammered, jumped) = match (jump, hammer) {
+                (true, true) => {
+                    let rest = runs(column & !inner);
+                    (runs(column) > rest, rest)
+                }
+                (true, false) => (false, runs(column)),
+                (false, _) if column & !inner != 0 => return None,
+                (false, _) => (column != 0, 0),
+            };
+            targets |= (hammered as u32) << th;
+            jumps.extend((0..jumped).map(|_| Attack::Jump { th }));
+        }
+        let hammers = self.hammer_blocks(targets).into_iter().map(|th| Attack::Hammer { th });
+        Some(hammers.chain(jumps).collect())
+    }
+
+    fn enemies(&self) -> u32 {
+        self.cells.iter().map(|subring| subring.count_ones()).sum()
+    }
+}
+
+/// How many separate lines of enemies there are in a column, each taking its own jump.
+fn runs(column: u32) -> u32 {
+    (column & !(column << 1)).count_ones()
+}
+
+/// A solution for a `SizedBoard`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SizedSolution {
+    pub moves: Vec<RingMovement>,
+    /// The board after the moves.
+    pub result: SizedBoard,
+    /// The jumps and hammers that hit every enemy on `result`.
+    pub attacks: Vec<Attack>,
+    pub total_distance: u32,
+}
+
+/// Moves boards of any size, making every movement `movements` allows.
+struct SizedMoves;
+
+impl SizedMoves {
+    /// Every movement allowed by `constraints`, smallest first.
+    fn movements(board: &SizedBoard, constraints: &MoveConstraints) -> Vec<RingMovement> {
+        let mut movements = Vec::new();
+        if constraints.rotations {
+            for r in (0..board.rings).filter(|&r| constraints.locked & (1 << r) == 0) {
+                if board.cells[r as usize] == 0 {
+                    continue;
+                }
+                for amount in 1..=board.angles / 2 {
+                    let half_turn = amount == board.angles / 2;
+                    let amount = amount as i16;
+                    movements.push(RingMovement::Ring { r, amount, clockwise: true });
+                    if !half_turn {
+                        movements.push(RingMovement::Ring { r, amount, clockwise: false });
+                    }
+                }
+            }
+        }
+        if constraints.shifts && constraints.locked == 0 {
+            for th in (0..board.angles / 2).filter(|&th| board.row(th) != 0) {
+                for amount in 1..=board.rings {
+                    let across = amount == board.rings;
+                    let amount = amount as i16;
+                    movements.push(RingMovement::Row { th, amount, outward: true });
+                    if !across {
+                        movements.push(RingMovement::Row { th, amount, outward: false });
+                    }
+                }
+            }
+        }
+        movements
+    }
+}
+
+impl Moves for SizedMoves {
+    type Board = SizedBoard;
+    type Error = Infallible;
+
+    fn each_movement(
+        &self,
+        board: &SizedBoard,
+        _turns: usize,
+        constraints: &MoveConstraints,
+        mut visit: impl FnMut(RingMovement, SizedBoard) -> std::result::Result<(), Infallible>,
+    ) -> std::result::Result<(), Infallible> {
+        Self::movements(board, constraints)
+            .into_iter()
+            .try_for_each(|movement| visit(movement, board.moved(&movement)))
+    }
+
+    fn first_moved(
+        &self,
+        board: &SizedBoard,
+        movement: &RingMovement,
+    ) -> std::result::Result<Option<SizedBoard>, Infallible> {
+        Ok(Some(board.moved(movement)))
+    }
+}
+
+/// Finds the fewest turns that clear `board` under the rules of `options`, then the
+/// smallest total distance. Returns None if nothing within the turn limit clears it, or
+/// the search ran out of budget first. Fails with `spoiler_free`, since the solution is
+/// the moves.
+pub fn find_solution(
+    board: &SizedBoard,
+    options: &SolveOptions,
+) -> std::result::Result<Option<SizedSolution>, String> {
+    board.check()?;
+    with_spoilers(options)?;
+    if let Some(first_move) = &options.first_move {
+        board.check_movement(first_move)?;
+        if options.constraints.moves_locked(first_move) {
+            return Err("the first move moves a locked ring".to_string());
+        }
+    }
+    let rules = options.battle_rules()?;
+    let actions = rules.actions.unwrap_or_else(|| rules.formula.actions(board.enemies()));
+    let cleared = |board: &SizedBoard, _: &[RingMovement]| {
+        board.attack_plan(rules.jump, rules.hammer).is_some_and(|attacks| {
+            attacks.len() as u32 <= actions + rules.tolerance
+        })
+    };
+    let mut search = Exhaustive::new(&SizedMoves, options);
+    let Ok(found) = search.find_shortest(board, rules.max_turns.min(MAX_TURNS), cleared);
+    Ok(found.map(|(moves, result)| {
+        let attacks = result.attack_plan(rules.jump, rules.hammer).unwrap_or_default();
+        SizedSolution { total_distance: total_distance(&moves), moves, result, attacks }
+    }))
+}
+
+/// Solve a board of any size, given as `{rings, angles, cells}`.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_sized(board: JsValue, options: JsValue) -> Result<JsValue> {
+    let board: SizedBoard = serde_wasm_bindgen::from_value(board)?;
+    // `first_move` is checked against this board rather than the game's, by `find_solution`.
+    let options = read_options_with(options, |options| {
+        SolveOptions { first_move: None, ..options.clone() }.check()
+    })?;
+    let options = unoriented(options)?;
+    Ok(serde_wasm_bindgen::to_value(&find_solution(&board, &options)?)?)
+}
@@ -1,8 +1,75 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use arrayvec::ArrayVec;
+use std::cmp::Ordering;
 use std::collections::VecDeque;
+use std::future::Future;
+use std::rc::Rc;
+use std::sync::OnceLock;
+use std::task::{Context, Poll, Waker};
 use wasm_bindgen::prelude::*;
 
+pub mod accessories;
+pub mod async_solve;
+pub mod attributes;
+pub mod audit;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod batch;
+pub mod battle;
+pub mod bench;
+pub mod binding;
+pub mod board;
+pub mod boss;
+pub mod cancel;
+pub mod coach;
+pub mod coins;
+pub mod countdown;
+pub mod difficulty;
+pub mod encoding;
+pub mod engine;
+pub mod evaluator;
+mod exhaustive;
+pub mod first_strike;
+pub mod flat;
+pub mod formations;
+pub mod generator;
+pub mod heuristic;
+pub mod hp;
+#[cfg(feature = "ilp")]
+pub mod ilp;
+pub mod inventory;
+pub mod items;
+pub mod large_enemies;
+pub mod no_damage;
+pub mod normalize;
+pub mod notation;
+pub mod obstacles;
+pub mod ordering;
+pub mod orientation;
+pub mod pack;
+pub mod pareto;
+pub mod partition;
+pub mod partners;
+pub mod pedagogy;
+pub mod preset;
+pub mod priority;
+pub mod replay;
+pub mod rewards;
+pub mod scripted;
+pub mod share;
+pub mod sized;
+pub mod symmetry;
+pub mod toads;
+pub mod trace;
+pub mod two_phase;
+pub mod validation;
+pub mod waves;
+pub mod weapons;
+
+#[cfg(test)]
+mod search_tests;
+#[cfg(test)]
+mod shift_tests;
+
 #[cfg(debug_assertions)]
 use web_sys::console;
 
@@ -16,27 +83,193 @@ static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 /// It's organized where each index is a subring, from inner to outer.
 /// The 12 lower bits of each element is set if there is an enemy at that angle.
 /// The lowest bit is angle 0, and it goes clockwise from there.
-type Ring = [u16; 4];
+pub type Ring = [u16; 4];
 const NUM_RINGS: u16 = 4;
 const NUM_ANGLES: u16 = 12;
 const MAX_TURNS: u16 = 4;
 
+/// The shape of a board with `R` rings of `A` angles, for the movement code that works on
+/// any size, e.g. to test it exhaustively on small boards. `Game` is the game's board,
+/// which everything else uses.
+///
+/// A subring's angles and a row's cells each have to fit in a `u16`, so there are at most
+/// 16 angles and 8 rings, and the angles are even so each row has an opposite angle.
+struct Layout<const R: usize, const A: u16>;
+
+/// The game's board, of 4 rings of 12 angles.
+type Game = Layout<{ NUM_RINGS as usize }, NUM_ANGLES>;
+
+/// The most rings, or rows, a `Layout` can have.
+const MAX_LAYOUT: usize = 8;
+
 /// A Rust version of a RingMovement.
-#[derive(Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all="camelCase")]
 pub enum RingMovement {
     Ring { r: u16, amount: i16, clockwise: bool },
     Row { th: u16, amount: i16, outward: bool },
 }
 
+impl RingMovement {
+    /// Whether this rotates a ring, as opposed to shifting a row.
+    pub fn is_rotation(&self) -> bool {
+        matches!(self, RingMovement::Ring { .. })
+    }
+
+    /// A key that orders movements by type, index, then signed amount.
+    fn sort_key(&self) -> (bool, u16, i16) {
+        match *self {
+            RingMovement::Ring { r, amount, clockwise } => {
+                (false, r, if clockwise { amount } else { -amount })
+            }
+            RingMovement::Row { th, amount, outward } => {
+                (true, th, if outward { amount } else { -amount })
+            }
+        }
+    }
+
+    /// How many notches this movement drags its ring or row.
+    pub fn distance(&self) -> u32 {
+        match *self {
+            RingMovement::Ring { amount, .. } | RingMovement::Row { amount, .. } => {
+                amount.unsigned_abs() as u32
+            }
+        }
+    }
+
+    /// The movement that undoes this one: the same ring or row, dragged back the other way.
+    pub fn inverse(&self) -> RingMovement {
+        match *self {
+            RingMovement::Ring { r, amount, clockwise } => {
+                RingMovement::Ring { r, amount, clockwise: !clockwise }
+            }
+            RingMovement::Row { th, amount, outward } => {
+                RingMovement::Row { th, amount, outward: !outward }
+            }
+        }
+    }
+}
+
+/// The movements that undo `moves`: the inverse of each, last first. Made on the board
+/// `moves` lead to, they lead back to the board `moves` started from.
+pub fn inverse_moves<'m>(moves: impl IntoIterator<Item = &'m RingMovement>) -> Vec<RingMovement> {
+    let mut inverse: Vec<RingMovement> = moves.into_iter().map(RingMovement::inverse).collect();
+    inverse.reverse();
+    inverse
+}
+
 /// Represents a solution to the problem.
-#[derive(Serialize)]
+///
+/// A solution read back from JSON, e.g. one saved earlier, has its optional reports left
+/// out if they weren't saved with it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all="camelCase")]
 pub struct Solution {
     pub moves: VecDeque<RingMovement>,
     pub result: Ring,
     pub jump_rows: u32,
     pub hammerable_groups: u32,
+    /// The jumps and hammers that hit every enemy on `result`, in the order to make them.
+    pub attacks: Vec<Attack>,
+    /// Whether the goal was reached, which unless an `Evaluator` says otherwise means
+    /// every enemy can be hit. Only false for a best-effort result.
+    pub perfect: bool,
+    /// Whether the attacks hit every enemy within the actions the player gets, earning the
+    /// perfect bonus. A board only cleared thanks to `Rules::tolerance` is `perfect` but
+    /// doesn't earn the bonus.
+    pub perfect_bonus: bool,
+    /// Whether the search ran out of budget before finishing.
+    pub truncated: bool,
+    /// The sum of every movement's amount, i.e. how many notches the player has to drag.
+    pub total_distance: u32,
+    /// The constraints that ruled out a better solution, if any.
+    pub binding: Vec<binding::BindingConstraint>,
+    /// The Toads, enemy attributes, or blocked panels that ruled out a better solution, if
+    /// any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hazards: Vec<binding::AvoidedHazard>,
+    /// How much work the search did, if `SolveOptions::stats` was set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<SearchStats>,
+    /// The damage the attacks deal, if `SolveOptions::battle` was set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub damage: Option<battle::DamageReport>,
+    /// The weapons to make the attacks with, if `SolveOptions::inventory` was set and
+    /// every attack can be made with one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub loadout: Option<inventory::Loadout>,
+    /// The items to use in place of some of the attacks, if any.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<items::ItemUse>,
+    /// Where the partner attacks before the player, if `SolveOptions::partner` was set and
+    /// it helps.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partner_attack: Option<partners::PartnerAttack>,
+    /// The coins and confetti the solution is expected to earn, if
+    /// `SolveOptions::rewards` was set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reward: Option<rewards::RewardEstimate>,
+    /// How long the movements take to make, if `SolveOptions::timer` was set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timing: Option<countdown::Timing>,
+    /// How many crumpled Toads are on the board, if an accessory alerts the player to them.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub toad_alert: Option<u32>,
+}
+
+/// How many moves a solution takes, without the moves themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MoveCount {
+    pub moves: u16,
+    pub perfect: bool,
+    pub truncated: bool,
+}
+
+impl From<&Solution> for MoveCount {
+    fn from(solution: &Solution) -> Self {
+        MoveCount {
+            moves: solution.moves.len() as u16,
+            perfect: solution.perfect,
+            truncated: solution.truncated,
+        }
+    }
+}
+
+/// An attack the player makes on the board once the movements are done.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Attack {
+    /// A jump on the line of enemies at angle `th`, outward from the innermost.
+    Jump { th: u16 },
+    /// A hammer aimed at angle `th`. A basic hammer hits the inner two rings there and at
+    /// the next angle clockwise.
+    Hammer { th: u16 },
+}
+
+impl Attack {
+    /// The panels this attack lands on with the basic boots and hammer.
+    pub fn footprint(&self) -> Ring {
+        match *self {
+            Attack::Jump { th } => [1 << th; NUM_RINGS as usize],
+            Attack::Hammer { th } => hammer_block(th),
+        }
+    }
+}
+
+/// Counters of how much work a search did, for understanding slow solves.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchStats {
+    /// The number of boards visited.
+    pub nodes: u64,
+    /// The most turns searched.
+    pub max_depth: u16,
+    /// Branches skipped without being searched, because they couldn't rank better than
+    /// the solutions already found, couldn't be solved in the turns left, or were
+    /// symmetric images of others.
+    pub prunes: u64,
+    pub elapsed_millis: f64,
 }
 
 /// A MaskedInt allows rotation of its internal bits.
@@ -51,7 +284,7 @@ trait MaskedInt: Sized + Copy {
         let n = n % Self::NUM_BITS;
         let m = ((1 << n) - 1) << (Self::NUM_BITS - n);
         let y = (x & m) >> (Self::NUM_BITS - n);
-        return Self::new(((x << n) | y) & ((1 << Self::NUM_BITS) - 1));
+        Self::new(((x << n) | y) & ((1 << Self::NUM_BITS) - 1))
     }
 
     /// Rotate the value right by N bits.
@@ -60,15 +293,16 @@ trait MaskedInt: Sized + Copy {
         let n = n % Self::NUM_BITS;
         let m = (1 << n) - 1;
         let y = (x & m) << (Self::NUM_BITS - n);
-        return Self::new((x >> n) | y);
+        Self::new((x >> n) | y)
     }
 }
 
-/// The data contained in a subring, the same format as in Row, with 12 bits.
+/// The data contained in a subring of `A` angles, the same format as in Row, with 12 bits
+/// on the game's board.
 #[derive(Clone, Copy, PartialEq, Eq)]
-struct Subring(pub u16);
-impl MaskedInt for Subring {
-    const NUM_BITS: u16 = NUM_ANGLES;
+struct Subring<const A: u16 = NUM_ANGLES>(pub u16);
+impl<const A: u16> MaskedInt for Subring<A> {
+    const NUM_BITS: u16 = A;
 
     fn new(value: u16) -> Self {
         Self(value)
@@ -79,16 +313,17 @@ impl MaskedInt for Subring {
     }
 }
 
-/// The data contained in a full row in shifting order, with 8 bits.
+/// The data contained in a full row of `R` rings in shifting order, with 8 bits on the
+/// game's board.
 ///
 /// The lowest bit is the closest cell in an angle.
 /// The next three bits are in the same angle, moving outwards.
 /// Then, the fourth bit is the farthest cell in the opposite angle (same row).
 /// The next three bits are in that opposite angle, moving inwards.
 #[derive(Clone, Copy, PartialEq, Eq)]
-struct Row(pub u16);
-impl MaskedInt for Row {
-    const NUM_BITS: u16 = NUM_RINGS * 2;
+struct Row<const R: usize = { NUM_RINGS as usize }>(pub u16);
+impl<const R: usize> MaskedInt for Row<R> {
+    const NUM_BITS: u16 = R as u16 * 2;
 
     fn new(value: u16) -> Self {
         Self(value)
@@ -112,7 +347,7 @@ impl MaskedInt for Row {
 /// This is used to iterate through the smallest movements first.
 ///
 /// # Example
-/// ```
+/// ```ignore
 /// let z = ZigZagBits::new(Row(0b00000010));
 /// assert_eq!(z.next(), Some(Row(0b00000100)));
 /// assert_eq!(z.next(), Some(Row(0b00000001)));
@@ -138,7 +373,7 @@ impl<T: MaskedInt> Iterator for ZigZagBits<T> {
     fn next(&mut self) -> Option<Self::Item> {
         let new_amount = -self.amount + ((self.amount <= 0) as i16);
         let diff = new_amount - self.amount;
-        #[cfg(debug_assertions)]
+        #[cfg(all(debug_assertions, target_arch = "wasm32"))]
         console::log_3(
             &JsValue::from(self.amount),
             &JsValue::from(new_amount),
@@ -154,15 +389,15 @@ impl<T: MaskedInt> Iterator for ZigZagBits<T> {
 }
 
 /// An iterator over all rotations for a subring, smallest first.
-struct RingRotations {
-    ring: Ring,
+struct RingRotations<const R: usize, const A: u16> {
+    ring: [u16; R],
     pub r: u16,
-    subring_iter: ZigZagBits<Subring>,
+    subring_iter: ZigZagBits<Subring<A>>,
 }
 
-impl RingRotations {
-    fn new(ring: Ring, r: u16) -> Option<Self> {
-        let subring = Subring(ring[r as usize]);
+impl<const R: usize, const A: u16> RingRotations<R, A> {
+    fn new(ring: [u16; R], r: u16) -> Option<Self> {
+        let subring = Subring::<A>(ring[r as usize]);
         if subring.0 == 0 {
             return None;
         }
@@ -171,16 +406,16 @@ impl RingRotations {
     }
 }
 
-impl Iterator for RingRotations {
-    type Item = (Ring, RingMovement);
+impl<const R: usize, const A: u16> Iterator for RingRotations<R, A> {
+    type Item = ([u16; R], RingMovement);
     fn next(&mut self) -> Option<Self::Item> {
         let (subring, amount) = self.subring_iter.next()?;
         self.ring[self.r as usize] = subring.value();
-        #[cfg(debug_assertions)]
+        #[cfg(all(debug_assertions, target_arch = "wasm32"))]
         console::log_1(
             &JsValue::from(&format!(
-                "r: {}, amount: {}, \n{:012b}\n{:012b}\n{:012b}\n{:012b}\n",
-                self.r, amount, self.ring[3], self.ring[2], self.ring[1], self.ring[0]
+                "r: {}, amount: {}, \n{}",
+                self.r, amount, Layout::<R, A>::describe(&self.ring)
             )),
         );
         Some((self.ring, RingMovement::Ring {
@@ -191,16 +426,23 @@ impl Iterator for RingRotations {
     }
 }
 
-/// An iterator over all shifts for a row, smallest first.
-struct RingShifts {
-    ring: Ring,
-    pub th: u16,
-    row_iter: ZigZagBits<Row>,
+/// Extracts the row at angle `th` (and its opposite angle, `th + 6`) in shifting order.
+fn get_row(ring: &Ring, th: u16) -> Row {
+    Game::get_row(ring, th)
 }
 
-impl RingShifts {
-    fn new(ring: Ring, th: u16) -> Option<Self> {
+impl<const R: usize, const A: u16> Layout<R, A> {
+    /// Stops a layout that doesn't fit from building.
+    const FITS: () = assert!(R >= 1 && R <= MAX_LAYOUT && A >= 2 && A <= 16 && A.is_multiple_of(2));
+
+    /// Extracts the row at angle `th` (and its opposite angle, `th + A / 2`) in shifting
+    /// order.
+    fn get_row(ring: &[u16; R], th: u16) -> Row<R> {
+        let () = Self::FITS;
+        let opposite = th + A / 2;
+        let last = R as u16 * 2 - 1;
         let mut row: u16 = 0;
+        // On the game's board:
         // Bit 0-3: th: th, r: r
         // Bit 4-7: th: th + 6, r: 7 - r
         // - Bit 0: th: th, r: 0
@@ -211,39 +453,112 @@ impl RingShifts {
         // - Bit 5: th: th + 6, r: 2
         // - Bit 6: th: th + 6, r: 1
         // - Bit 7: th: th + 6, r: 0
-        for r in 0..4u16 {
+        for r in 0..R as u16 {
             let subring = &ring[r as usize];
             let low = ((*subring & (1 << th)) != 0) as u16;
-            let high = ((*subring & (1 << (th + 6))) != 0) as u16;
+            let high = ((*subring & (1 << opposite)) != 0) as u16;
             row |= low << r;
-            row |= high << (7 - r);
+            row |= high << (last - r);
         }
-        if row == 0 {
+        Row(row)
+    }
+
+    /// Writes a row in shifting order back to angles `th` and `th + A / 2`, the inverse of
+    /// `get_row`.
+    fn set_row(ring: &mut [u16; R], th: u16, row: Row<R>) {
+        let () = Self::FITS;
+        let opposite = th + A / 2;
+        let last = R as u16 * 2 - 1;
+        let row = row.value();
+        for r in 0..R as u16 {
+            let subring = &mut ring[r as usize];
+            let low = (row & (1 << r) != 0) as u16;
+            let high = (row & (1 << (last - r)) != 0) as u16;
+            *subring = (*subring & !(1 << th)) | (low << th);
+            *subring = (*subring & !(1 << opposite)) | (high << opposite);
+        }
+    }
+
+    /// The subrings in binary, outer first, for debug logging.
+    #[cfg(debug_assertions)]
+    fn describe(ring: &[u16; R]) -> String {
+        let width = A as usize;
+        ring.iter().rev().map(|subring| format!("{:0width$b}\n", subring, width = width)).collect()
+    }
+}
+
+/// The cells of the row at an angle, in the order a shift moves them, for front ends that
+/// show what a shift will do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RowView {
+    /// The angle the row is viewed from.
+    pub th: u16,
+    /// The opposite angle, `th + 6` wrapped around, which is part of the same row.
+    pub opposite: u16,
+    /// Whether each cell has an enemy: the cells at `th` from the inner ring outward, then
+    /// the cells at `opposite` from the outer ring inward. Shifting the row outward from
+    /// `th` by 1 moves every cell one place later in this list, and the last to the first.
+    pub cells: [bool; (NUM_RINGS * 2) as usize],
+}
+
+/// Views the row at angle `th` as the solver shifts it.
+///
+/// Rows at `th` and `th + 6` are the same row viewed from opposite ends, so one view is
+/// the other reversed.
+pub fn row_view(ring: &Ring, th: u16) -> std::result::Result<RowView, String> {
+    if th >= NUM_ANGLES {
+        return Err(format!("row angle {} out of range", th));
+    }
+    let half = NUM_ANGLES / 2;
+    let mut bits = get_row(ring, th % half).value();
+    if th >= half {
+        bits = bits.reverse_bits() >> (16 - NUM_RINGS * 2);
+    }
+    let mut cells = [false; (NUM_RINGS * 2) as usize];
+    for (i, cell) in cells.iter_mut().enumerate() {
+        *cell = bits & (1 << i) != 0;
+    }
+    Ok(RowView { th, opposite: (th + half) % NUM_ANGLES, cells })
+}
+
+/// Show the cells of the row at angle `th` in shifting order, as a `RowView`.
+#[wasm_bindgen(js_name = row_view, skip_typescript)]
+pub fn row_view_js(ring: JsValue, th: u16) -> Result<JsValue> {
+    let ring: Ring = serde_wasm_bindgen::from_value(ring)?;
+    check_ring(&ring)?;
+    Ok(serde_wasm_bindgen::to_value(&row_view(&ring, th)?)?)
+}
+
+/// An iterator over all shifts for a row, smallest first.
+struct RingShifts<const R: usize, const A: u16> {
+    ring: [u16; R],
+    pub th: u16,
+    row_iter: ZigZagBits<Row<R>>,
+}
+
+impl<const R: usize, const A: u16> RingShifts<R, A> {
+    fn new(ring: [u16; R], th: u16) -> Option<Self> {
+        let row = Layout::<R, A>::get_row(&ring, th);
+        if row.0 == 0 {
             return None;
         }
-        let row_iter = ZigZagBits::new(Row(row));
+        let row_iter = ZigZagBits::new(row);
         Some(RingShifts {ring, th, row_iter})
     }
 }
 
-impl Iterator for RingShifts {
-    type Item = (Ring, RingMovement);
+impl<const R: usize, const A: u16> Iterator for RingShifts<R, A> {
+    type Item = ([u16; R], RingMovement);
     fn next(&mut self) -> Option<Self::Item> {
         let (row, amount) = self.row_iter.next()?;
-        let row = row.value();
-        for r in 0..4 {
-            let subring = &mut self.ring[r as usize];
-            let low = (row & (1 << r) != 0) as u16;
-            let high = (row & (1 << (7 - r)) != 0) as u16;
-            let th = self.th;
-            *subring = (*subring & !(1 << th)) | (low << th);
-            *subring = (*subring & !(1 << (th + 6))) | (high << (th + 6));
-        }
-        #[cfg(debug_assertions)]
+        Layout::<R, A>::set_row(&mut self.ring, self.th, row);
+        #[cfg(all(debug_assertions, target_arch = "wasm32"))]
         console::log_1(
             &JsValue::from(&format!(
-                "th: {}, amount: {}, row: {:08b}\n{:012b}\n{:012b}\n{:012b}\n{:012b}\n",
-                self.th, amount, row, self.ring[3], self.ring[2], self.ring[1], self.ring[0]
+                "th: {}, amount: {}, row: {:0width$b}\n{}",
+                self.th, amount, row.value(), Layout::<R, A>::describe(&self.ring),
+                width = R * 2
             )),
         );
         Some((self.ring, RingMovement::Row {
@@ -254,140 +569,2010 @@ impl Iterator for RingShifts {
     }
 }
 
-/// Calls the given callback for each ring movement.
+/// Which movements a search is allowed to make.
+/// Useful for players with controller issues, or practicing specific techniques.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MoveConstraints {
+    /// Whether rings may be rotated at all.
+    pub rotations: bool,
+    /// Whether rows may be shifted at all.
+    pub shifts: bool,
+    /// A mask of the rings that may be rotated, bit `r` for ring `r`.
+    pub rings: u16,
+    /// A mask of the rows that may be shifted, bit `th` for the row at `th` and `th + 6`.
+    pub rows: u16,
+    /// The largest amount a ring may be rotated by.
+    pub max_rotation: u16,
+    /// The largest amount a row may be shifted by.
+    pub max_shift: u16,
+    /// A mask of the rings the encounter locks in place, bit `r` for ring `r`. A locked
+    /// ring can't be rotated, and its panels can't be shifted through, which rules out
+    /// every row shift since each row crosses every ring.
+    pub locked: u16,
+}
+
+impl Default for MoveConstraints {
+    fn default() -> Self {
+        MoveConstraints {
+            rotations: true,
+            shifts: true,
+            rings: (1 << NUM_RINGS) - 1,
+            rows: (1 << (NUM_ANGLES / 2)) - 1,
+            max_rotation: NUM_ANGLES / 2,
+            max_shift: NUM_RINGS,
+            locked: 0,
+        }
+    }
+}
+
+impl MoveConstraints {
+    fn allows_ring(&self, r: u16) -> bool {
+        self.rotations && self.rings & (1 << r) != 0 && self.locked & (1 << r) == 0
+    }
+
+    fn allows_row(&self, th: u16) -> bool {
+        self.shifts && self.rows & (1 << th) != 0 && self.locked == 0
+    }
+
+    /// Whether `movement` moves a locked ring, which no solution can do.
+    fn moves_locked(&self, movement: &RingMovement) -> bool {
+        match *movement {
+            RingMovement::Ring { r, .. } => self.locked & (1 << r) != 0,
+            RingMovement::Row { .. } => self.locked != 0,
+        }
+    }
+}
+
+/// How much each movement costs the player, for solves that should minimize effort rather
+/// than turns. A movement costs its type's weight plus its notch weight per notch dragged,
+/// plus the cursor's travel from the movement before it.
+///
+/// Given in milliseconds, these are a timing model for speedruns, e.g.
+/// `{"rotation": 350, "shift": 350, "rotationNotch": 120, "shiftNotch": 150, "travel": 90}`,
+/// where the type weights are the confirmation delay of picking a ring or row up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MoveCosts {
+    /// The cost of starting a ring rotation.
+    pub rotation: u32,
+    /// The cost of starting a row shift.
+    pub shift: u32,
+    /// The cost of each notch a ring is rotated by.
+    pub rotation_notch: u32,
+    /// The cost of each notch a row is shifted by.
+    pub shift_notch: u32,
+    /// The cost of each step the cursor travels from the last ring or row moved to the
+    /// next: one step per ring outward or inward, or per row around.
+    pub travel: u32,
+    /// The cost of switching the cursor between rings and rows.
+    pub switch: u32,
+}
+
+/// The default costs are the total distance.
+impl Default for MoveCosts {
+    fn default() -> Self {
+        MoveCosts {
+            rotation: 0,
+            shift: 0,
+            rotation_notch: 1,
+            shift_notch: 1,
+            travel: 0,
+            switch: 0,
+        }
+    }
+}
+
+impl MoveCosts {
+    /// The cost of a single movement.
+    pub fn movement_cost(&self, movement: &RingMovement) -> u32 {
+        let (start, notch) = if movement.is_rotation() {
+            (self.rotation, self.rotation_notch)
+        } else {
+            (self.shift, self.shift_notch)
+        };
+        start + notch * movement.distance()
+    }
+
+    /// The cost of moving the cursor from the ring or row of `from` to that of `to`.
+    pub fn travel_cost(&self, from: &RingMovement, to: &RingMovement) -> u32 {
+        match (*from, *to) {
+            (RingMovement::Ring { r: a, .. }, RingMovement::Ring { r: b, .. }) => {
+                self.travel * a.abs_diff(b) as u32
+            }
+            (RingMovement::Row { th: a, .. }, RingMovement::Row { th: b, .. }) => {
+                let steps = a.abs_diff(b) % (NUM_ANGLES / 2);
+                self.travel * steps.min(NUM_ANGLES / 2 - steps) as u32
+            }
+            _ => self.switch,
+        }
+    }
+
+    /// The total cost of `moves`, including the cursor's travel between them.
+    pub fn cost<'m>(&self, moves: impl IntoIterator<Item = &'m RingMovement>) -> u32 {
+        let mut last = None;
+        moves
+            .into_iter()
+            .map(|movement| {
+                let travel = last.map_or(0, |last| self.travel_cost(last, movement));
+                last = Some(movement);
+                travel + self.movement_cost(movement)
+            })
+            .sum()
+    }
+
+    /// The least any movement can cost, since every movement is at least one notch and the
+    /// cursor may not need to travel.
+    fn cheapest_movement(&self) -> u32 {
+        (self.rotation + self.rotation_notch).min(self.shift + self.shift_notch)
+    }
+}
+
+/// Calls the given callback for each ring movement allowed by `constraints`,
+/// stopping at the first `Some` result.
 /// This would use an iterator, but this ended up challenging as iterators cannot return
 /// references to data they contain.
-fn iterate_movements<F: Fn(RingMovement, Ring) -> Option<Solution>>(ring: Ring, cb: F) -> Option<Solution> {
-    let mut rotators: ArrayVec<[RingRotations; NUM_RINGS as usize]> = (0..NUM_RINGS)
-        .filter_map(|r| RingRotations::new(ring, r))
-        .collect();
-    let mut shifters: ArrayVec<[RingShifts; (NUM_ANGLES / 2) as usize]> = (0..(NUM_ANGLES / 2))
-        .filter_map(|th| RingShifts::new(ring, th))
-        .collect();
-    for n in 0..NUM_ANGLES {
-        for rotator in rotators.iter_mut() {
-            let (moved, movement) = rotator.next().unwrap();
-            if let Some(solution) = cb(movement, moved) {
-                return Some(solution);
-            }
-        }
-        if n < NUM_RINGS * 2 {
-            for shifter in shifters.iter_mut() {
-                let (moved, movement) = shifter.next().unwrap();
-                if let Some(solution) = cb(movement, moved) {
-                    return Some(solution);
+fn iterate_movements<T, F: FnMut(RingMovement, Ring) -> Option<T>>(
+    ring: Ring,
+    constraints: &MoveConstraints,
+    cb: F,
+) -> Option<T> {
+    Game::iterate_movements(ring, constraints, cb)
+}
+
+impl<const R: usize, const A: u16> Layout<R, A> {
+    /// Calls the given callback for each ring movement allowed by `constraints`, like
+    /// `iterate_movements`.
+    fn iterate_movements<T, F: FnMut(RingMovement, [u16; R]) -> Option<T>>(
+        ring: [u16; R],
+        constraints: &MoveConstraints,
+        mut cb: F,
+    ) -> Option<T> {
+        let () = Self::FITS;
+        let mut rotators: ArrayVec<[RingRotations<R, A>; MAX_LAYOUT]> = (0..R as u16)
+            .filter(|&r| constraints.allows_ring(r))
+            .filter_map(|r| RingRotations::new(ring, r))
+            .collect();
+        let mut shifters: ArrayVec<[RingShifts<R, A>; MAX_LAYOUT]> = (0..(A / 2))
+            .filter(|&th| constraints.allows_row(th))
+            .filter_map(|th| RingShifts::new(ring, th))
+            .collect();
+        // Each zig-zag makes two movements per amount: one each way.
+        // A half turn is the same in either direction, so the last movement of each
+        // zig-zag (the half turn back the other way) is skipped.
+        let rotation_moves = (constraints.max_rotation * 2).min(A - 1);
+        let shift_moves = (constraints.max_shift * 2).min(R as u16 * 2 - 1);
+        for n in 0..rotation_moves.max(shift_moves) {
+            if n < rotation_moves {
+                for rotator in rotators.iter_mut() {
+                    let (moved, movement) = rotator.next().unwrap();
+                    if let Some(solution) = cb(movement, moved) {
+                        return Some(solution);
+                    }
+                }
+            }
+            if n < shift_moves {
+                for shifter in shifters.iter_mut() {
+                    let (moved, movement) = shifter.next().unwrap();
+                    if let Some(solution) = cb(movement, moved) {
+                        return Some(solution);
+                    }
                 }
             }
         }
+        None
+    }
+
+    /// Applies a single movement to a ring, like `apply_movement`.
+    fn apply_movement(mut ring: [u16; R], movement: &RingMovement) -> [u16; R] {
+        let () = Self::FITS;
+        match *movement {
+            RingMovement::Ring { r, amount, clockwise } => {
+                let subring = Subring::<A>(ring[r as usize]);
+                let amount = amount.rem_euclid(A as i16) as u16;
+                ring[r as usize] = if clockwise {
+                    subring.rotate_left(amount)
+                } else {
+                    subring.rotate_right(amount)
+                }.value();
+            }
+            RingMovement::Row { th, amount, outward } => {
+                // A row is the same as the row at the opposite angle, moving the other way.
+                let (th, outward) = if th >= A / 2 {
+                    (th - A / 2, !outward)
+                } else {
+                    (th, outward)
+                };
+                let row = Self::get_row(&ring, th);
+                let amount = amount.rem_euclid((R * 2) as i16) as u16;
+                let row = if outward {
+                    row.rotate_left(amount)
+                } else {
+                    row.rotate_right(amount)
+                };
+                Self::set_row(&mut ring, th, row);
+            }
+        }
+        ring
     }
-    None
 }
 
-/// Perform the actual solve of RingData.
+/// Applies a single movement to a ring, returning the moved ring.
+///
+/// Unlike the search, this accepts any movement a user could make: negative or
+/// oversized amounts, and rows given by their opposite angle (`th >= 6`).
+fn apply_movement(ring: Ring, movement: &RingMovement) -> Ring {
+    Game::apply_movement(ring, movement)
+}
+
+/// Reverse a list of movements, e.g. a solution's `moves`, into the movements that take
+/// its result back to the board it started from.
 #[wasm_bindgen(skip_typescript)]
-pub fn solve(ring: JsValue) -> Result<JsValue> {
+pub fn reverse_moves(moves: JsValue) -> Result<JsValue> {
+    let moves: Vec<RingMovement> = serde_wasm_bindgen::from_value(moves)?;
+    for movement in &moves {
+        check_movement(movement)?;
+    }
+    Ok(serde_wasm_bindgen::to_value(&inverse_moves(&moves))?)
+}
+
+/// Apply a single `RingMovement` to a board and return the moved board, e.g. to animate a
+/// solution one movement at a time or to let the player move the rings by hand.
+#[wasm_bindgen(js_name = apply_movement, skip_typescript)]
+pub fn apply_movement_js(ring: JsValue, movement: JsValue) -> Result<JsValue> {
     let ring: Ring = serde_wasm_bindgen::from_value(ring)?;
-    let solution = find_solution(ring, MAX_TURNS);
+    let movement: RingMovement = serde_wasm_bindgen::from_value(movement)?;
+    check_ring(&ring)?;
+    check_movement(&movement)?;
+    Ok(serde_wasm_bindgen::to_value(&apply_movement(ring, &movement))?)
+}
+
+/// Checks that a movement refers to a ring or row that exists.
+fn check_movement(movement: &RingMovement) -> std::result::Result<(), String> {
+    match *movement {
+        RingMovement::Ring { r, .. } if r >= NUM_RINGS => {
+            Err(format!("ring index {} out of range", r))
+        }
+        RingMovement::Row { th, .. } if th >= NUM_ANGLES => {
+            Err(format!("row angle {} out of range", th))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks that a ring only has enemies at angles that exist.
+fn check_ring(ring: &Ring) -> std::result::Result<(), String> {
+    match ring.iter().position(|&subring| subring >> NUM_ANGLES != 0) {
+        Some(r) => Err(format!("subring {} has bits set above angle {}", r, NUM_ANGLES - 1)),
+        None => Ok(()),
+    }
+}
+
+/// The panels of `cells`, as rings and angles.
+fn panels_of(cells: Ring) -> impl Iterator<Item = (usize, usize)> {
+    (0..NUM_RINGS as usize).flat_map(move |r| {
+        let mut angles = cells[r];
+        std::iter::from_fn(move || {
+            let th = angles.trailing_zeros() as usize;
+            angles &= angles.wrapping_sub(1);
+            (th < NUM_ANGLES as usize).then_some((r, th))
+        })
+    })
+}
+
+/// Options that control how a solve searches.
+/// Every field is optional from JavaScript.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SolveOptions {
+    /// Stop searching after visiting this many boards.
+    pub max_nodes: Option<u64>,
+    /// Stop searching after this many milliseconds.
+    pub max_millis: Option<f64>,
+    /// Only find solutions that start with this movement,
+    /// e.g. one the player has already started making.
+    pub first_move: Option<RingMovement>,
+    /// Which movements the search may make. The forced `first_move` is always allowed.
+    pub constraints: MoveConstraints,
+    /// Which search algorithm to use.
+    pub backend: Backend,
+    /// The order to try movements in.
+    pub move_order: ordering::MoveOrderKind,
+    /// If set, solutions are ranked by their total cost instead of by turns first, so a
+    /// solution may use more turns if they're cheaper, up to the turn limit.
+    /// The quick backends ignore this.
+    pub costs: Option<MoveCosts>,
+    /// The battle rules to solve under; `rules` are only used with `Preset::Custom`.
+    pub preset: preset::Preset,
+    /// The turns, actions, and weapons for a custom preset.
+    pub rules: Option<preset::Rules>,
+    /// How many actions the player has, overriding the rules, e.g. in a boss phase.
+    pub actions: Option<u32>,
+    /// How many more actions than that a board may take, overriding the rules, for players
+    /// who just want to win the fight without the perfect bonus.
+    pub tolerance: Option<u32>,
+    /// Which kinds of attack the player can make, on top of the rules.
+    pub available: preset::Available,
+    /// The partner fighting alongside the player, whose attack also counts toward
+    /// clearing the board.
+    pub partner: Option<partners::Partner>,
+    /// The panels with crumpled Toads on them, which no attack may land on.
+    pub toads: Option<Ring>,
+    /// How to choose between solutions with the fewest turns.
+    pub selection: Selection,
+    /// The turns to start searching at, e.g. from the "solvable in N moves" the game
+    /// shows, skipping the shallower depths that can't have a solution if it's right.
+    /// If nothing is found from there, the shallower depths are searched after all.
+    pub start_turns: Option<u16>,
+    /// Whether to also search the depths below `start_turns` once a solution is found,
+    /// in case the hint was too high.
+    pub verify_start: bool,
+    /// Whether to report `SearchStats` with each solution.
+    pub stats: bool,
+    /// The player's weapons, to report the damage `solve` deals with them.
+    pub battle: Option<battle::BattleSetup>,
+    /// The weapons the player carries, to choose one for each attack `solve` makes.
+    pub inventory: Option<inventory::Inventory>,
+    /// What the battle pays out, to estimate the reward for each solution. The damage is
+    /// worked out with `battle`, or the basic weapons if it isn't set, and the time left
+    /// with `timer`, or the default timer if it isn't set.
+    pub rewards: Option<rewards::RewardRates>,
+    /// The time left to make the movements, to prefer solutions the player can make in
+    /// time.
+    pub timer: Option<countdown::Timer>,
+    /// The accessories the player wears, which change the turns, timer, hints, and rewards.
+    pub accessories: Vec<accessories::Accessory>,
+    /// Whether solves should only say how many moves the solution takes, as a
+    /// `MoveCount`, for players who want to find the moves themselves.
+    pub spoiler_free: bool,
+    /// How the frontend numbers angles and directions, for the board, the options, and
+    /// the solutions passed to and from JavaScript.
+    pub orientation: orientation::Orientation,
+}
+
+/// How to choose between solutions with the same number of turns, which would otherwise
+/// depend on the order the search happens to try movements in.
+///
+/// Solutions that still tie are ordered by their movements, compared one at a time:
+/// rotations before shifts, then by ring or row, then by signed amount, counterclockwise
+/// and inward first. So the same board and options always give the same solutions.
+/// With `costs`, solutions are ranked by cost and then this order, whatever the selection.
+/// The `TwoPhase` and `Fast` backends take the first solutions they find instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Selection {
+    /// The smallest total distance, then the fewest switches between rotations and shifts.
+    #[default]
+    SmallestDistance,
+    /// Rotations as early as possible, then the smallest total distance.
+    RotationsFirst,
+    /// Only the order of the movements.
+    Lexicographic,
+    /// The solutions easiest to follow: the ones that line up the final board's jump
+    /// columns and hammer blocks earliest, then the smallest total distance. See `pedagogy`.
+    HumanLike,
+}
+
+/// The search algorithms available to find a solution.
+/// `Iddfs` and `IdaStar` always find a solution with the fewest turns.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Backend {
+    /// Plain iterative deepening, trying every board at each depth.
+    #[default]
+    Iddfs,
+    /// Iterative deepening A*: boards that `heuristic::occupancy_bound` proves can't be
+    /// solved in the turns left are pruned, and depths that can't have a solution are
+    /// skipped.
+    IdaStar,
+    /// A "quick suggestion" from a much shallower search: see `two_phase`. It may use more
+    /// turns than needed, or find nothing on boards the other backends solve, and only ever
+    /// finds one solution.
+    TwoPhase,
+    /// Trades optimality for speed on dense boards: the solution may take one more turn
+    /// than needed, and solutions with the same turns aren't ranked against each other.
+    Fast,
+}
+
+impl SolveOptions {
+    /// The rules of the preset, with the actions given and only the attacks that are
+    /// available.
+    pub fn battle_rules(&self) -> std::result::Result<preset::Rules, String> {
+        let mut rules = self.preset.rules(self.rules)?;
+        if self.actions.is_some() {
+            rules.actions = self.actions;
+        }
+        if let Some(tolerance) = self.tolerance {
+            rules.tolerance = tolerance;
+        }
+        let rules = accessories::Effects::of(&self.accessories).rules(rules);
+        self.available.restrict(rules)
+    }
+
+    /// Checks that every movement in the options exists.
+    fn check(&self) -> std::result::Result<(), String> {
+        if let Some(first_move) = &self.first_move {
+            check_movement(first_move)?;
+            if self.constraints.moves_locked(first_move) {
+                return Err("the first move moves a locked ring".to_string());
+            }
+        }
+        self.battle_rules()?;
+        accessories::Effects::of(&self.accessories).check()?;
+        self.orientation.check()?;
+        if let Some(toads) = &self.toads {
+            check_ring(toads)?;
+        }
+        if let Some(battle) = &self.battle {
+            battle.check()?;
+        }
+        if let Some(inventory) = &self.inventory {
+            inventory.check()?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads solve options from JavaScript, where `undefined` or `null` means the defaults.
+fn read_options(options: JsValue) -> Result<SolveOptions> {
+    read_options_with(options, SolveOptions::check)
+}
+
+/// Like `read_options`, checking the options with `check`, e.g. for a board of another
+/// size than the game's.
+fn read_options_with(
+    options: JsValue,
+    check: impl FnOnce(&SolveOptions) -> std::result::Result<(), String>,
+) -> Result<SolveOptions> {
+    if options.is_undefined() || options.is_null() {
+        Ok(SolveOptions::default())
+    } else {
+        let options: SolveOptions = serde_wasm_bindgen::from_value(options)?;
+        check(&options)?;
+        Ok(options)
+    }
+}
+
+/// Reads solve options from JavaScript for a function that only takes angles in the
+/// solver's orientation, so it fails if another orientation is given.
+fn options_from_js(options: JsValue) -> Result<SolveOptions> {
+    unoriented(read_options(options)?)
+}
+
+/// `options`, if they're in the solver's orientation.
+fn unoriented(options: SolveOptions) -> Result<SolveOptions> {
+    if options.orientation != orientation::Orientation::default() {
+        return Err("this function only takes angles in the solver's orientation".into());
+    }
+    Ok(options)
+}
+
+/// Reads solve options from JavaScript, with everything in them turned from the
+/// frontend's orientation to the solver's. The orientation is kept, to turn the solutions
+/// back with `solution_to_js`.
+fn oriented_options_from_js(options: JsValue) -> Result<SolveOptions> {
+    let options = read_options(options)?;
+    let orientation = options.orientation;
+    Ok(orientation.options_in(options))
+}
+
+/// Reads a board and its solve options from JavaScript, both turned to the solver's
+/// orientation. An invalid board fails with everything wrong with it.
+fn input_from_js(ring: JsValue, options: JsValue) -> Result<(Ring, SolveOptions)> {
+    let ring = validation::ring_from_js(ring)?;
+    let options = oriented_options_from_js(options)?;
+    Ok((options.orientation.board_in(ring), options))
+}
+
+/// Converts a possible solution to the value handed back to JavaScript, in the
+/// orientation of `options`.
+fn solution_to_js(solution: Option<Solution>, options: &SolveOptions) -> Result<JsValue> {
     Ok(match solution {
-        Some(solution) => serde_wasm_bindgen::to_value(&solution)?,
+        Some(solution) => serde_wasm_bindgen::to_value(&options.orientation.solution_out(solution))?,
         None => JsValue::null(),
     })
 }
 
-/// Find a solution with the minimum number of turns,, given a max number of turns allowed.
-/// This implements an IDDFS, useful for very wide, shallow trees like this solution space.
-fn find_solution(ring: Ring, max_turns: u16) -> Option<Solution> {
-    for turn in 0..=max_turns {
-        if let Some(solution) = find_solution_at_turn(ring, turn) {
-            return Some(solution);
-        }
+/// Perform the actual solve of RingData.
+///
+/// An invalid board fails with everything wrong with it, as `validate_board` reports.
+///
+/// If the search runs out of budget before finding a solution, the closest board found
+/// so far is returned with `truncated` set, instead of `null`. With `spoilerFree`, only
+/// its `MoveCount` is returned.
+///
+/// If `on_progress` is given, it's called about every `PROGRESS_MILLIS` while the search
+/// runs with a `ProgressReport`, e.g. to drive a progress bar on slow devices. With
+/// `spoilerFree`, the reports leave out the closest board so far.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve(ring: JsValue, options: JsValue, on_progress: Option<js_sys::Function>) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let on_progress = match &on_progress {
+        Some(on_progress) => on_progress,
+        None => return solve_to_js(find_solution(ring, MAX_TURNS, &options), &options),
+    };
+    let mut observer = JsObserver::new(&options);
+    observer.on_progress = Some(on_progress);
+    let search = Search::new(&options).with_observer(&mut observer);
+    let solution = find_solution_with(search, ring, MAX_TURNS, &options);
+    if let Some(e) = observer.error {
+        return Err(e);
     }
-    None
+    solve_to_js(solution, &options)
 }
 
-/// Finds a solution after a given number of turns.
-fn find_solution_at_turn(ring: Ring, turn: u16) -> Option<Solution> {
-    if turn == 0  {
-        // Is the current ring a solution?
-        return get_solution(ring);
+/// Fails if `options` ask for `spoilerFree`, for functions whose results are the moves.
+fn with_spoilers(options: &SolveOptions) -> std::result::Result<(), String> {
+    if options.spoiler_free {
+        return Err("this function returns the moves, so it can't be spoiler-free".to_string());
     }
-    // Go through each possible movement to determine if it leads to a solution.
-    iterate_movements(ring, |movement, moved| {
-        match find_solution_at_turn(moved, turn - 1) {
-            Some(mut solution) => {
-                solution.moves.push_front(movement);
-                Some(solution)
-            },
-            None => None,
+    Ok(())
+}
+
+/// Converts the result of `solve` to JavaScript in the orientation of `options`, keeping
+/// the moves hidden if it asks for it.
+fn solve_to_js(solution: Option<Solution>, options: &SolveOptions) -> Result<JsValue> {
+    if options.spoiler_free {
+        Ok(serde_wasm_bindgen::to_value(&solution.as_ref().map(MoveCount::from))?)
+    } else {
+        solution_to_js(solution, options)
+    }
+}
+
+/// The input to `solve_json`.
+#[derive(Deserialize)]
+struct JsonInput {
+    ring: Vec<f64>,
+    #[serde(default)]
+    options: Option<SolveOptions>,
+}
+
+/// Solve a board given as plain JSON, `{"ring": [...], "options": {...}}`, like `solve`,
+/// for hosts where building JavaScript values is awkward. Returns the solution as JSON,
+/// `null` if there's none, or `{"error": "..."}` if the input can't be solved.
+#[wasm_bindgen]
+pub fn solve_json(input: &str) -> String {
+    solve_json_result(input).unwrap_or_else(|e| serde_json::json!({ "error": e }).to_string())
+}
+
+fn solve_json_result(input: &str) -> std::result::Result<String, String> {
+    let input: JsonInput = serde_json::from_str(input).map_err(|e| e.to_string())?;
+    let ring = validation::read_board(&input.ring).map_err(|errors| validation::describe(&errors))?;
+    let options = input.options.unwrap_or_default();
+    options.check()?;
+    let orientation = options.orientation;
+    let ring = orientation.board_in(ring);
+    let options = orientation.options_in(options);
+    let solution = find_solution(ring, MAX_TURNS, &options)
+        .map(|solution| orientation.solution_out(solution));
+    let json = if options.spoiler_free {
+        serde_json::to_string(&solution.as_ref().map(MoveCount::from))
+    } else {
+        serde_json::to_string(&solution)
+    };
+    json.map_err(|e| e.to_string())
+}
+
+/// Find up to `count` distinct solutions, ranked by turns, then total distance, then
+/// how often they switch between rotations and shifts. Fails with `spoilerFree`, since
+/// only the moves tell the solutions apart.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_top_k(ring: JsValue, count: usize, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    with_spoilers(&options)?;
+    let solutions = Search::new(&options).find_solutions(ring, MAX_TURNS, count);
+    let solutions: Vec<Solution> =
+        solutions.into_iter().map(|solution| options.orientation.solution_out(solution)).collect();
+    Ok(serde_wasm_bindgen::to_value(&solutions)?)
+}
+
+/// Passes search updates to JavaScript callbacks.
+struct JsObserver<'a> {
+    on_depth: Option<&'a js_sys::Function>,
+    on_progress: Option<&'a js_sys::Function>,
+    /// The orientation to report the best solutions so far in.
+    orientation: orientation::Orientation,
+    /// Whether to keep the best solutions so far to ourselves, as `spoilerFree` asks.
+    spoiler_free: bool,
+    /// The first error thrown by a callback, reported once the search is done.
+    error: Option<JsValue>,
+}
+
+impl JsObserver<'_> {
+    /// Calls no callbacks yet, and reports in the orientation of `options`.
+    fn new(options: &SolveOptions) -> Self {
+        JsObserver {
+            on_depth: None,
+            on_progress: None,
+            orientation: options.orientation,
+            spoiler_free: options.spoiler_free,
+            error: None,
         }
-    })
+    }
+
+    /// `best` in the orientation to report it in, or None if it's a spoiler.
+    fn best_out(&self, best: &Option<Solution>) -> Option<Solution> {
+        if self.spoiler_free {
+            return None;
+        }
+        best.clone().map(|best| self.orientation.solution_out(best))
+    }
+
+    /// Calls `callback` with `report`, unless a callback has already thrown.
+    fn call(&mut self, callback: Option<&js_sys::Function>, report: &impl Serialize) {
+        let callback = match callback {
+            Some(callback) if self.error.is_none() => callback,
+            _ => return,
+        };
+        let result = serde_wasm_bindgen::to_value(report)
+            .map_err(JsValue::from)
+            .and_then(|report| callback.call1(&JsValue::NULL, &report));
+        if let Err(e) = result {
+            self.error = Some(e);
+        }
+    }
 }
 
-/// Gets a solution for the given ring, or None if the ring isn't a perfect solve.
-fn get_solution(ring: Ring) -> Option<Solution> {
-    // The number of enemies on the board.
-    let enemies: u32 = ring.iter().copied().map(u16::count_ones).sum();
+impl SearchObserver for JsObserver<'_> {
+    fn depth_finished(&mut self, report: &DepthReport) {
+        let report = DepthReport { best: self.best_out(&report.best), ..*report };
+        self.call(self.on_depth, &report);
+    }
 
-    // The enemies of the outer two rings, only accessible through jumps.
-    // We merge the two outer rings because an enemy at any angle requires the whole angle.
-    let outer = ring[2] | ring[3];
+    fn progress(&mut self, report: &ProgressReport) {
+        let report = ProgressReport { best: self.best_out(&report.best), ..*report };
+        self.call(self.on_progress, &report);
+    }
+}
 
-    // The enemies of the inner two rings that can be hit by hammers.
-    // We merge the two inner rings and exclude those in outer, which must be hit with jumps.
-    let mut inner = (ring[0] | ring [1]) & !outer;
+/// Like `solve`, but calls `on_depth` each time a number of turns has been fully searched
+/// without finding a solution, with the closest board found so far. This lets the UI show
+/// e.g. "no solution in 2 moves, trying 3…" during long searches.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_anytime(ring: JsValue, options: JsValue, on_depth: &js_sys::Function) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let mut observer = JsObserver::new(&options);
+    observer.on_depth = Some(on_depth);
+    let solution = Search::new(&options)
+        .with_observer(&mut observer)
+        .find_solution(ring, MAX_TURNS);
+    if let Some(e) = observer.error {
+        return Err(e);
+    }
+    solve_to_js(solution, &options)
+}
 
-    // Guarantee that the lowest bit in inner is a 0, or that all 12 angles have enemies.
-    // This is done to avoid an extra simulated hammer if the inner rings look like e.g.:
-    // 100000000001
-    inner = Subring(inner).rotate_right(inner.trailing_ones() as u16).value();
+/// Continue solving a ring after the user has already made some moves.
+/// `moves` are applied to `ring` in order, and the returned solution only contains
+/// the moves remaining within the reduced turn budget.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_from(ring: JsValue, moves: JsValue, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let moves: Vec<RingMovement> = serde_wasm_bindgen::from_value(moves)?;
+    for movement in &moves {
+        check_movement(movement)?;
+    }
+    let moves: Vec<RingMovement> =
+        moves.iter().map(|movement| options.orientation.movement_in(movement)).collect();
+    solve_to_js(find_solution_from(ring, &moves, &options)?, &options)
+}
 
-    // The number of actions is ceil(enemies / 4).
-    let actions = enemies / 4 + ((enemies % 4 != 0) as u32);
+/// Applies the moves already made to `ring`, then solves with the turns the rules of
+/// `options` leave over.
+fn find_solution_from(
+    ring: Ring,
+    moves: &[RingMovement],
+    options: &SolveOptions,
+) -> std::result::Result<Option<Solution>, String> {
+    let max_turns = options.battle_rules()?.max_turns;
+    let remaining = match max_turns.checked_sub(moves.len() as u16) {
+        Some(remaining) => remaining,
+        None => return Ok(None),
+    };
+    let ring = moves.iter().fold(ring, apply_movement);
+    Ok(find_solution(ring, remaining, options))
+}
 
-    // The number of jumps necessary for this ring.
-    let jump_rows = outer.count_ones();
+/// Finds the best solution allowed by `options`, noting any constraints that ruled out
+/// a better one.
+fn find_solution(ring: Ring, max_turns: u16, options: &SolveOptions) -> Option<Solution> {
+    find_solution_with(Search::new(options), ring, max_turns, options)
+}
 
-    // The number of groups that can be hammered.
-    let mut hammerable_groups = 0;
-
-    // Here, we simulate hammering the inner subrings by clearing bits next to each other.
-    // For an inner subrings of 101110011110, it would take 5 hits:
-    //
-    // Hammer 1:
-    // inner:  101110011110
-    // hammer: 000000000110 (0b11 << 1)
-    //
-    // Hammer 2:
-    // inner:  101110011000
-    // hammer: 000000011000 (0b11 << 3)
-    //
-    // Hammer 3:
-    // inner:  101110000000
-    // hammer: 000110000000 (0b11 << 7)
-    //
-    // Hammer 4:
-    // inner:  101000000000
-    // hammer: 011000000000 (0b11 << 9)
-    //
-    // Hammer 5:
-    // inner:  100000000000
-    // hammer:1100000000000 (0b11 << 11)
-    while inner != 0 {
-        // Clears the enemies in the inner subrings with a single "hammer".
-        inner &= !(0b11 << inner.trailing_zeros());
-        hammerable_groups += 1;
-    }
-    if hammerable_groups + jump_rows > actions {
-        // If it takes more hammers and jumps than we have actions available,
-        // this isn't a solution.
-        None
-    } else {
-        Some(Solution {
-            moves: VecDeque::new(),
-            result: ring,
-            jump_rows,
-            hammerable_groups,
-        })
+/// The goal for `rules`, and the partner, Toads, and priority targets in `options`, or
+/// None if it's the usual perfect clear.
+fn goal(options: &SolveOptions, rules: preset::Rules) -> Option<Rc<dyn evaluator::Evaluator>> {
+    let goal: Option<Rc<dyn evaluator::Evaluator>> = match options.partner {
+        Some(partner) => Some(Rc::new(partners::PartnerGoal::new(partner, rules))),
+        None => rules.evaluator(),
+    };
+    with_targets(options, rules, goal)
+}
+
+/// A custom `goal` to search for in place of the usual clear under `rules`, still keeping
+/// the Toads safe and going for the priority targets in `options`. Fails if `options` has
+/// a partner, whose attack only counts toward the usual clear.
+fn custom_goal(
+    options: &SolveOptions,
+    rules: preset::Rules,
+    goal: Rc<dyn evaluator::Evaluator>,
+) -> std::result::Result<Rc<dyn evaluator::Evaluator>, String> {
+    if options.partner.is_some() {
+        return Err("a partner can't help reach this goal".to_string());
+    }
+    Ok(with_targets(options, rules, Some(goal.clone())).unwrap_or(goal))
+}
+
+/// `goal`, or the usual clear if None, with the Toads and priority targets in `options`.
+fn with_targets(
+    options: &SolveOptions,
+    rules: preset::Rules,
+    goal: Option<Rc<dyn evaluator::Evaluator>>,
+) -> Option<Rc<dyn evaluator::Evaluator>> {
+    let goal = match options.toads {
+        Some(toads) if toads != [0; NUM_RINGS as usize] => {
+            let goal = goal.unwrap_or_else(|| Rc::new(rules));
+            Some(Rc::new(toads::ToadGoal::new(toads, rules, goal)) as Rc<dyn evaluator::Evaluator>)
+        }
+        _ => goal,
+    };
+    match options.battle.and_then(|battle| battle.priority) {
+        Some(priority) if priority != [0; NUM_RINGS as usize] => {
+            let goal = goal.unwrap_or_else(|| Rc::new(rules));
+            Some(Rc::new(priority::PriorityGoal::new(priority, rules, goal)))
+        }
+        _ => goal,
+    }
+}
+
+/// Like `find_solution`, with a search that's already been set up from `options`.
+fn find_solution_with(
+    mut search: Search<'_>,
+    ring: Ring,
+    max_turns: u16,
+    options: &SolveOptions,
+) -> Option<Solution> {
+    let solution = search.find_solution(ring, max_turns)?;
+    Some(without_yielding(finish_solution(&mut search, solution, ring, max_turns, options)))
+}
+
+/// Runs a search that doesn't yield to the end, which it reaches without ever waiting.
+fn without_yielding<T>(search: impl Future<Output = T>) -> T {
+    let mut search = std::pin::pin!(search);
+    match search.as_mut().poll(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(output) => output,
+        Poll::Pending => unreachable!("a search only waits when yielding"),
+    }
+}
+
+/// Fills in everything `solve` reports about `solution` besides its moves, after `search`
+/// found it. Whenever that takes another search, it only gets what's left of the budget
+/// of `search`, stops if `search` was cancelled, and yields as `search` does.
+async fn finish_solution(
+    search: &mut Search<'_>,
+    mut solution: Solution,
+    ring: Ring,
+    max_turns: u16,
+    options: &SolveOptions,
+) -> Solution {
+    let rules = options.battle_rules().unwrap_or_default();
+    let strict = goal(options, preset::Rules { tolerance: 0, ..rules });
+    let has_bonus = |solution: &Solution| {
+        solution.perfect
+            && strict.as_ref().map_or(evaluate(solution.result).is_perfect(), |goal| {
+                goal.distance(solution.result) == 0
+            })
+    };
+    solution.perfect_bonus = has_bonus(&solution);
+    if solution.perfect && !solution.perfect_bonus {
+        // A board with the perfect bonus in as many turns is always better.
+        let strict_options = SolveOptions { tolerance: Some(0), ..options.clone() };
+        let turns = solution.moves.len() as u16;
+        if let Some(mut bonus) = search.search_again(&strict_options, ring, turns, 1).await.pop() {
+            if bonus.perfect {
+                bonus.perfect_bonus = true;
+                solution = bonus;
+            }
+        }
+    }
+    let effects = accessories::Effects::of(&options.accessories);
+    if let Some(timer) = options.timer.map(|timer| effects.timer(timer)) {
+        if solution.perfect && !timer.timing(&solution.moves).in_time {
+            let turns = solution.moves.len() as u16;
+            let candidates = search.search_again(options, ring, turns, countdown::CANDIDATES).await;
+            let in_time = |candidate: &Solution| timer.timing(&candidate.moves).in_time;
+            if let Some(mut fast) = candidates.into_iter().find(|c| c.perfect && in_time(c)) {
+                fast.perfect_bonus = has_bonus(&fast);
+                solution = fast;
+            }
+        }
+        solution.timing = Some(timer.timing(&solution.moves));
+    }
+    solution.binding = binding::find_binding(search, ring, max_turns, options, &solution).await;
+    solution.hazards =
+        binding::find_toad_hazards(search, ring, max_turns, options, &solution).await;
+    if let Some(partner) = options.partner {
+        let (_, attack, rest) = partners::PartnerGoal::new(partner, rules).plan(solution.result);
+        solution.partner_attack = attack;
+        solution.attacks = rules.attack_plan(rest);
+    } else if !(rules.jump && rules.hammer) {
+        solution.attacks = rules.attack_plan(solution.result);
+    }
+    let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+    if let Some(priority) = options.battle.and_then(|battle| battle.priority) {
+        let priority = moves.iter().fold(priority, apply_movement);
+        solution.attacks = priority::prioritize(&solution.attacks, solution.result, priority);
+    }
+    if let Some(battle) = &options.battle {
+        solution.damage = Some(battle::damage_report(&moves, solution.result, battle));
+    }
+    if options.rewards.is_some() {
+        solution.reward = Some(rewards::estimate_with(&solution, options));
+    }
+    solution.toad_alert = effects.toad_alert(options.toads);
+    if let Some(inventory) = &options.inventory {
+        solution.loadout =
+            inventory::assign_weapons(&solution.attacks, solution.result, &moves, inventory);
+    }
+    solution
+}
+
+/// The current time in milliseconds, for search time limits.
+#[cfg(target_arch = "wasm32")]
+fn now_millis() -> f64 {
+    js_sys::Date::now()
+}
+
+/// The current time in milliseconds, for search time limits.
+#[cfg(not(target_arch = "wasm32"))]
+fn now_millis() -> f64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0.0, |d| d.as_secs_f64() * 1000.0)
+}
+
+/// How many first movements are remembered to try first at the next depth.
+const KILLERS: usize = 8;
+
+/// How many boards to visit between checks of the clock and for cancellation.
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// How many boards a search has visited, and whether it's used up the node or time budget
+/// in its `SolveOptions` or been cancelled.
+pub(crate) struct Budget {
+    /// The number of boards visited so far.
+    pub(crate) nodes: u64,
+    max_nodes: Option<u64>,
+    /// When to stop searching, in `now_millis` time.
+    deadline: Option<f64>,
+    /// Stops the search early once cancelled.
+    cancel: Option<cancel::CancelToken>,
+    /// Whether the search ran out of budget.
+    pub(crate) truncated: bool,
+}
+
+impl Budget {
+    pub(crate) fn new(options: &SolveOptions) -> Self {
+        Budget {
+            nodes: 0,
+            max_nodes: options.max_nodes,
+            deadline: options.max_millis.map(|millis| now_millis() + millis),
+            cancel: None,
+            truncated: false,
+        }
+    }
+
+    /// Stops searching once `token` is cancelled, as if out of budget.
+    pub(crate) fn with_cancel(mut self, token: cancel::CancelToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// What's left of this budget, for another search after this one, cancelled along
+    /// with it.
+    fn remaining(&self) -> Budget {
+        Budget {
+            nodes: 0,
+            max_nodes: self.max_nodes.map(|max| max.saturating_sub(self.nodes)),
+            deadline: self.deadline,
+            cancel: self.cancel.clone(),
+            truncated: self.truncated,
+        }
+    }
+
+    /// Whether enough boards have been visited since the last checkpoint to check the
+    /// clock again.
+    fn at_checkpoint(&self) -> bool {
+        self.nodes > 0 && self.nodes.is_multiple_of(CLOCK_INTERVAL)
+    }
+
+    /// Whether the budget has been used up, or the search was cancelled.
+    /// The first board is always checked, so there is always a best-effort result.
+    pub(crate) fn spent(&mut self) -> bool {
+        if !self.truncated && self.nodes > 0 {
+            let out_of_nodes = self.max_nodes.is_some_and(|max| self.nodes >= max);
+            let checkpoint = self.at_checkpoint();
+            let out_of_time =
+                checkpoint && self.deadline.is_some_and(|deadline| now_millis() >= deadline);
+            let cancelled =
+                checkpoint && self.cancel.as_ref().is_some_and(cancel::CancelToken::is_cancelled);
+            self.truncated = out_of_nodes || out_of_time || cancelled;
+        }
+        self.truncated
+    }
+}
+
+/// Receives updates about a search while it runs.
+trait SearchObserver {
+    /// Called after each depth of the search is exhausted without finding a solution.
+    fn depth_finished(&mut self, _report: &DepthReport) {}
+
+    /// Called about every `PROGRESS_MILLIS` while the search runs.
+    fn progress(&mut self, _report: &ProgressReport) {}
+}
+
+/// What a search has found so far, reported after each depth.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DepthReport {
+    /// The number of turns that has been fully searched.
+    pub depth: u16,
+    /// The number of boards visited so far.
+    pub nodes: u64,
+    /// The closest board to a solution found so far.
+    pub best: Option<Solution>,
+}
+
+/// How far a search has got, reported periodically while it runs.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressReport {
+    /// The number of turns being searched.
+    pub depth: u16,
+    /// The number of boards visited so far.
+    pub nodes: u64,
+    /// The closest board to a solution found so far.
+    pub best: Option<Solution>,
+}
+
+/// How often to report progress to an observer, in milliseconds.
+const PROGRESS_MILLIS: f64 = 100.0;
+
+/// The state of a single search for a solution.
+struct Search<'a> {
+    /// The boards visited so far, and what's left of the budget.
+    budget: Budget,
+    /// The number of branches skipped so far.
+    prunes: u64,
+    /// The most turns searched so far.
+    max_depth: u16,
+    /// The number of turns being searched.
+    depth: u16,
+    /// When the search started, in `now_millis` time, if statistics are wanted.
+    started: Option<f64>,
+    /// The moves made to reach the board currently being searched.
+    path: Vec<RingMovement>,
+    /// The board found so far that needs the fewest extra actions, and how it was reached.
+    best: Option<(u32, Vec<RingMovement>, Evaluation)>,
+    /// The best-ranked perfect solutions found at the current depth, best first.
+    found: Vec<Solution>,
+    /// How many solutions to keep in `found`.
+    keep: usize,
+    /// The movement every solution has to start with.
+    first_move: Option<RingMovement>,
+    constraints: MoveConstraints,
+    backend: Backend,
+    /// The most turns the battle rules allow, whatever the caller asks for.
+    max_turns: u16,
+    /// How to weigh movements, or None to rank by turns and then `selection`.
+    costs: Option<MoveCosts>,
+    selection: Selection,
+    /// The board being solved.
+    start: Ring,
+    start_turns: Option<u16>,
+    verify_start: bool,
+    /// The order to try movements in, or None for the zig-zag order of `iterate_movements`,
+    /// which is called directly since it's by far the most common and the search is much
+    /// faster when it's inlined.
+    order: Option<Rc<dyn ordering::MoveOrder>>,
+    /// The goal to search for, or None for a perfect clear, which is checked directly for
+    /// the same reason as `order`.
+    evaluator: Option<Rc<dyn evaluator::Evaluator>>,
+    /// For IDA*, the fewest total turns of any board pruned at the current depth.
+    next_bound: u16,
+    /// The symmetries of the board being solved, if it has any and they can be used.
+    symmetries: Option<symmetry::Symmetries>,
+    /// The boards after each first movement expanded at the current depth.
+    /// A first movement whose board is a symmetric image of one of these is skipped,
+    /// since it leads to solutions that are just mirrored or rotated.
+    seen_roots: Vec<Ring>,
+    /// The first movements expanded at the current depth, and the boards they lead to,
+    /// in the order they're searched.
+    roots: Vec<(RingMovement, Ring)>,
+    /// The first movements that got closest to a solution at the last depth, closest first.
+    killers: Vec<RingMovement>,
+    /// The fewest extra actions left by any board under each first movement at this depth.
+    root_excess: Vec<u32>,
+    /// Which first movement is being searched, as an index into `root_excess`.
+    root_index: usize,
+    observer: Option<&'a mut dyn SearchObserver>,
+    /// When to next report progress to `observer`, in `now_millis` time.
+    next_progress: f64,
+    /// How many boards to visit between yields to the JavaScript event loop, if any.
+    yield_nodes: Option<u64>,
+    /// How many boards to have visited before the next yield.
+    next_yield: u64,
+    /// Records the boards visited, if tracing.
+    trace: Option<Box<trace::TraceRecorder>>,
+}
+
+impl<'a> Search<'a> {
+    fn new(options: &SolveOptions) -> Self {
+        // Options from JavaScript are checked as they're read.
+        let rules = options.battle_rules().unwrap_or_default();
+        let search = Search {
+            budget: Budget::new(options),
+            prunes: 0,
+            max_depth: 0,
+            depth: 0,
+            started: options.stats.then(now_millis),
+            path: Vec::new(),
+            best: None,
+            found: Vec::new(),
+            keep: 1,
+            first_move: options.first_move,
+            constraints: options.constraints,
+            backend: options.backend,
+            max_turns: rules.max_turns,
+            costs: options.costs,
+            selection: options.selection,
+            start: [0; 4],
+            start_turns: options.start_turns,
+            verify_start: options.verify_start,
+            order: match options.move_order {
+                ordering::MoveOrderKind::ZigZag => None,
+                kind => Some(kind.build()),
+            },
+            evaluator: None,
+            next_bound: u16::MAX,
+            symmetries: None,
+            seen_roots: Vec::new(),
+            roots: Vec::new(),
+            killers: Vec::new(),
+            root_excess: Vec::new(),
+            root_index: 0,
+            observer: None,
+            next_progress: 0.0,
+            yield_nodes: None,
+            next_yield: 0,
+            trace: None,
+        };
+        match goal(options, rules) {
+            Some(evaluator) => search.with_evaluator(evaluator),
+            None => search,
+        }
+    }
+
+    /// Searches using `path` as scratch space, so its allocation can be reused.
+    fn with_path(mut self, mut path: Vec<RingMovement>) -> Self {
+        path.clear();
+        self.path = path;
+        self
+    }
+
+    /// Gives back the scratch space used for the path, to reuse in another search.
+    fn into_path(self) -> Vec<RingMovement> {
+        self.path
+    }
+
+    /// Searches for boards that reach the goal of `evaluator` instead of a perfect clear.
+    /// The heuristic backends assume a perfect clear, so this always uses `Backend::Iddfs`.
+    fn with_evaluator(mut self, evaluator: Rc<dyn evaluator::Evaluator>) -> Self {
+        self.evaluator = Some(evaluator);
+        self.backend = Backend::Iddfs;
+        self
+    }
+
+    /// Stops searching once `token` is cancelled, as if out of budget.
+    fn with_cancel(mut self, token: cancel::CancelToken) -> Self {
+        self.budget = self.budget.with_cancel(token);
+        self
+    }
+
+    /// Searches again with `options` for up to `count` solutions, within what's left of
+    /// this search's budget and yielding as it does, e.g. to check if the options ruled
+    /// out a better solution.
+    async fn search_again(
+        &mut self,
+        options: &SolveOptions,
+        ring: Ring,
+        max_turns: u16,
+        count: usize,
+    ) -> Vec<Solution> {
+        let mut search = Search::new(options);
+        search.budget = self.budget.remaining();
+        if let Some(yield_nodes) = self.yield_nodes {
+            search = search.with_yielding(yield_nodes);
+        }
+        let solutions = search.find_solutions_async(ring, max_turns, count).await;
+        self.budget.nodes += search.budget.nodes;
+        solutions
+    }
+
+    /// Records up to `max_nodes` of the boards visited, for `take_trace`.
+    fn with_trace(mut self, max_nodes: usize) -> Self {
+        self.trace = Some(Box::new(trace::TraceRecorder::new(max_nodes)));
+        self
+    }
+
+    /// The boards visited so far, if tracing.
+    fn take_trace(&mut self) -> Option<trace::SearchTrace> {
+        self.trace.take().map(|recorder| recorder.finish())
+    }
+
+    /// Lets the JavaScript event loop run after about every `nodes` boards, between first
+    /// movements, when searching with `find_solutions_async`.
+    fn with_yielding(mut self, nodes: u64) -> Self {
+        self.yield_nodes = Some(nodes);
+        self.next_yield = nodes;
+        self
+    }
+
+    /// Reports progress to `observer` while searching.
+    fn with_observer(mut self, observer: &'a mut dyn SearchObserver) -> Self {
+        self.observer = Some(observer);
+        self.next_progress = now_millis() + PROGRESS_MILLIS;
+        self
+    }
+
+    /// The closest board found so far, as a best-effort solution.
+    fn best_solution(&self, truncated: bool) -> Option<Solution> {
+        self.best.as_ref().map(|(_, moves, evaluation)| {
+            let mut solution = Solution::new(moves.clone(), *evaluation, truncated);
+            // Only boards that didn't reach the goal are kept as the best.
+            solution.perfect = false;
+            solution
+        })
+    }
+
+    /// Find a solution with the minimum number of turns,, given a max number of turns allowed.
+    /// This implements an IDDFS, useful for very wide, shallow trees like this solution space.
+    ///
+    /// Among the solutions with the fewest turns, the one with the smallest total distance
+    /// is chosen, so the player has fewer notches to drag.
+    /// If the search runs out of budget, this returns the best imperfect board found instead.
+    fn find_solution(&mut self, ring: Ring, max_turns: u16) -> Option<Solution> {
+        self.find_solutions(ring, max_turns, 1).pop()
+    }
+
+    /// Finds up to `count` distinct solutions, ranked by turns, then total distance, then
+    /// how often they switch between rotations and shifts.
+    /// Sequences that only differ by the order of commuting movements are only counted once.
+    ///
+    /// If the search runs out of budget before finding any solution, this returns the best
+    /// imperfect board found instead.
+    fn find_solutions(&mut self, ring: Ring, max_turns: u16, count: usize) -> Vec<Solution> {
+        without_yielding(self.find_solutions_async(ring, max_turns, count))
+    }
+
+    /// Like `find_solutions`, but yields to the JavaScript event loop as set by
+    /// `with_yielding`, so it can be awaited without freezing the page.
+    async fn find_solutions_async(&mut self, ring: Ring, max_turns: u16, count: usize) -> Vec<Solution> {
+        let max_turns = max_turns.min(self.max_turns);
+        self.start = ring;
+        if self.backend == Backend::TwoPhase {
+            let solution = two_phase::find_solution(ring, max_turns, self.first_move, &self.constraints);
+            return self.with_stats(solution.into_iter().take(count).collect());
+        }
+        // The fast backend starts from a quick solution, and only searches for a solution
+        // at least two turns shorter, so whatever it returns is at most one turn worse
+        // than the optimal solution.
+        let quick = match self.backend {
+            Backend::Fast => {
+                two_phase::find_solution(ring, max_turns, self.first_move, &self.constraints)
+            }
+            _ => None,
+        };
+        let max_turns = quick
+            .as_ref()
+            .map_or(max_turns, |quick| (quick.moves.len() as u16).saturating_sub(2));
+        // A symmetric image of a solution ranks the same, so when only one solution is
+        // wanted, only one branch of each set of symmetric first movements is needed.
+        // Custom goals may not be symmetric themselves, and neither is pruning by the order
+        // of the movements. Rings are the same under every symmetry, but a symmetry that
+        // maps an allowed row onto a forbidden one can't be used.
+        let symmetric_goal = self.evaluator.is_none();
+        let symmetric_ranking = self.costs.is_some() || self.selection != Selection::Lexicographic;
+        let symmetries = symmetry::find_symmetries(ring).preserving_rows(self.constraints.rows);
+        self.symmetries = Some(symmetries).filter(|symmetries| {
+            count == 1
+                && self.first_move.is_none()
+                && symmetric_goal
+                && symmetric_ranking
+                && symmetries.is_symmetric()
+        });
+        let mut solutions = Vec::new();
+        // With a forced first move, the board as given doesn't count as a solution.
+        let shallowest = self.first_move.is_some() as u16;
+        let start = self
+            .start_turns
+            .map_or(shallowest, |hint| hint.clamp(shallowest, max_turns.max(shallowest)));
+        self.search_depths(ring, start, max_turns, count, &mut solutions).await;
+        let found_any = !solutions.is_empty() || !self.found.is_empty();
+        if start > shallowest && !self.budget.truncated && (!found_any || self.verify_start) {
+            // The hint was wrong, or has to be checked: solutions with fewer turns go first.
+            let mut shallower = Vec::new();
+            self.search_depths(ring, shallowest, start - 1, count, &mut shallower).await;
+            shallower.append(&mut solutions);
+            shallower.truncate(count);
+            solutions = shallower;
+        }
+        if self.costs.is_some() {
+            solutions.append(&mut self.found);
+        }
+        if solutions.is_empty() {
+            solutions.extend(quick);
+        }
+        if self.budget.truncated {
+            if solutions.is_empty() {
+                solutions.extend(self.best_solution(true));
+            }
+            for solution in &mut solutions {
+                solution.truncated = true;
+            }
+        }
+        self.with_stats(solutions)
+    }
+
+    /// Adds the statistics of the search so far to each solution, if they're wanted.
+    fn with_stats(&self, mut solutions: Vec<Solution>) -> Vec<Solution> {
+        if let Some(started) = self.started {
+            let stats = SearchStats {
+                nodes: self.budget.nodes,
+                max_depth: self.max_depth,
+                prunes: self.prunes,
+                elapsed_millis: now_millis() - started,
+            };
+            for solution in &mut solutions {
+                solution.stats = Some(stats);
+            }
+        }
+        solutions
+    }
+
+    /// Searches each depth from `first` to `max_turns` in turn, until `count` solutions are
+    /// added to `solutions`, or with costs, until deeper solutions can't be cheaper than
+    /// those in `found`.
+    async fn search_depths(
+        &mut self,
+        ring: Ring,
+        first: u16,
+        max_turns: u16,
+        count: usize,
+        solutions: &mut Vec<Solution>,
+    ) {
+        // With costs, a longer solution can be cheaper, so every depth adds to the same
+        // ranking in `found`, and shorter solutions help prune the longer ones.
+        let costs = self.costs;
+        let mut turn = first;
+        while turn <= max_turns {
+            self.keep = count - solutions.len();
+            self.next_bound = u16::MAX;
+            self.seen_roots.clear();
+            self.depth = turn;
+            self.max_depth = self.max_depth.max(turn);
+            self.search_turn(ring, turn).await;
+            if costs.is_none() {
+                solutions.append(&mut self.found);
+            }
+            if solutions.len() >= count || self.budget.truncated {
+                break;
+            }
+            if let Some(costs) = costs {
+                let cheapest = (turn as u32 + 1) * costs.cheapest_movement();
+                let full = self.found.len() >= count;
+                if full && self.found.last().is_some_and(|worst| (cheapest, 0) > self.rank(worst)) {
+                    break;
+                }
+            }
+            if let Some(observer) = self.observer.take() {
+                observer.depth_finished(&DepthReport {
+                    depth: turn,
+                    nodes: self.budget.nodes,
+                    best: self.best_solution(false),
+                });
+                self.observer = Some(observer);
+            }
+            turn = match self.backend {
+                Backend::Iddfs | Backend::TwoPhase | Backend::Fast => turn + 1,
+                // No board pruned at this depth could be solved before `next_bound`.
+                Backend::IdaStar => self.next_bound.max(turn + 1),
+            };
+        }
+    }
+
+    /// Whether the budget has been used up, reporting progress to the observer at each
+    /// checkpoint until it is.
+    fn out_of_budget(&mut self) -> bool {
+        if self.budget.spent() {
+            return true;
+        }
+        if self.observer.is_some() && self.budget.at_checkpoint() {
+            self.report_progress();
+        }
+        false
+    }
+
+    /// Reports progress to the observer if it's been long enough since the last report.
+    #[inline(never)]
+    fn report_progress(&mut self) {
+        let now = now_millis();
+        if now < self.next_progress {
+            return;
+        }
+        self.next_progress = now + PROGRESS_MILLIS;
+        if let Some(observer) = self.observer.take() {
+            observer.progress(&ProgressReport {
+                depth: self.depth,
+                nodes: self.budget.nodes,
+                best: self.best_solution(false),
+            });
+            self.observer = Some(observer);
+        }
+    }
+
+    /// Searches every board `turn` movements from `ring`, keeping the best-ranked solutions
+    /// in `found`. The first movements are searched one at a time, so the search can yield
+    /// between them.
+    async fn search_turn(&mut self, ring: Ring, turn: u16) {
+        self.roots.clear();
+        if self.find_solution_at_turn(ring, turn).is_some() || self.roots.is_empty() {
+            return;
+        }
+        let roots = std::mem::take(&mut self.roots);
+        for (index, &(movement, moved)) in roots.iter().enumerate() {
+            if self.is_symmetric_duplicate(moved) {
+                self.path.push(movement);
+                self.trace_board(moved, trace::Outcome::Pruned { reason: trace::PruneReason::Symmetric });
+                self.path.pop();
+                continue;
+            }
+            self.root_index = index;
+            self.path.push(movement);
+            let stop = self.find_solution_at_turn(moved, turn - 1);
+            self.path.pop();
+            if stop.is_some() {
+                break;
+            }
+            if let Some(yield_nodes) = self.yield_nodes.filter(|_| self.budget.nodes >= self.next_yield) {
+                async_solve::yield_to_event_loop().await;
+                self.next_yield = self.budget.nodes + yield_nodes;
+            }
+        }
+        let mut closest: Vec<usize> = (0..roots.len()).collect();
+        closest.sort_by_key(|&index| self.root_excess[index]);
+        self.killers = closest
+            .into_iter()
+            .take_while(|&index| self.root_excess[index] != u32::MAX)
+            .take(KILLERS)
+            .map(|index| roots[index].0)
+            .collect();
+        // Keep the allocation for the next depth.
+        self.roots = roots;
+    }
+
+    /// Searches for solutions after a given number of turns, keeping the best-ranked ones
+    /// in `found`. At the root, the first movements are only expanded into `roots`, for
+    /// `search_turn` to search.
+    /// Returns Some when there's no point in searching further.
+    fn find_solution_at_turn(&mut self, ring: Ring, turn: u16) -> Option<()> {
+        if self.out_of_budget() {
+            return Some(());
+        }
+        if self.found.len() >= self.keep && self.cannot_improve(turn) {
+            self.prunes += 1;
+            self.trace_board(ring, trace::Outcome::Pruned { reason: trace::PruneReason::CannotImprove });
+            return None;
+        }
+        if self.backend == Backend::IdaStar {
+            let estimate = heuristic::occupancy_bound(ring);
+            if estimate > turn {
+                let total = self.path.len() as u16 + estimate;
+                self.next_bound = self.next_bound.min(total);
+                self.prunes += 1;
+                self.trace_board(ring, trace::Outcome::Pruned { reason: trace::PruneReason::Heuristic });
+                return None;
+            }
+        }
+        if self.evaluator.as_ref().is_some_and(|evaluator| !evaluator.allows(&self.path)) {
+            self.prunes += 1;
+            self.trace_board(ring, trace::Outcome::Pruned { reason: trace::PruneReason::Disallowed });
+            return None;
+        }
+        self.budget.nodes += 1;
+        if self.trace.is_some() {
+            self.trace_visit(ring, turn);
+        }
+        if turn == 0 {
+            // Is the current ring a solution?
+            return self.check(ring);
+        }
+        if let Some(movement) = self.first_move.filter(|_| self.path.is_empty()) {
+            // Only the forced movement can be made first.
+            self.path.push(movement);
+            let stop = self.find_solution_at_turn(apply_movement(ring, &movement), turn - 1);
+            self.path.pop();
+            return stop;
+        }
+        if self.path.is_empty() {
+            self.expand_root(ring);
+            return None;
+        }
+        // Go through each possible movement to determine if it leads to a solution.
+        let constraints = self.constraints;
+        // The order is cloned so the search can recurse while it visits; without a custom
+        // order, that's only copying a None.
+        let order = self.order.clone();
+        let mut visit = |movement, moved| {
+            self.path.push(movement);
+            let stop = self.find_solution_at_turn(moved, turn - 1);
+            self.path.pop();
+            stop
+        };
+        match order {
+            Some(order) => order.visit(ring, &constraints, &mut visit),
+            None => iterate_movements(ring, &constraints, visit),
+        }
+    }
+
+    /// Lists each first movement in `roots`, with those that got closest to a solution at
+    /// the last depth first, so `search_turn` can remember which get closest at this depth.
+    fn expand_root(&mut self, ring: Ring) {
+        let mut first_moves = std::mem::take(&mut self.roots);
+        let mut visit = |movement, moved| {
+            first_moves.push((movement, moved));
+            None
+        };
+        match &self.order {
+            Some(order) => order.visit(ring, &self.constraints, &mut visit),
+            None => iterate_movements(ring, &self.constraints, visit),
+        };
+        // The sort is stable, so the rest keep the order's usual order.
+        first_moves.sort_by_key(|(movement, _)| {
+            self.killers.iter().position(|killer| killer == movement).unwrap_or(usize::MAX)
+        });
+        self.root_excess = vec![u32::MAX; first_moves.len()];
+        self.roots = first_moves;
+    }
+
+    /// Records a board being visited with `turn` movements left, when tracing.
+    #[inline(never)]
+    fn trace_visit(&mut self, ring: Ring, turn: u16) {
+        let outcome = if turn > 0 {
+            trace::Outcome::Expanded
+        } else {
+            let excess = match &self.evaluator {
+                Some(evaluator) => evaluator.distance_after(ring, &self.path),
+                None => evaluate(ring).excess_actions(),
+            };
+            match excess {
+                0 => trace::Outcome::Solution,
+                excess => trace::Outcome::Leaf { excess },
+            }
+        };
+        self.trace_board(ring, outcome);
+    }
+
+    /// Records a board reached by the current path, when tracing.
+    fn trace_board(&mut self, ring: Ring, outcome: trace::Outcome) {
+        if let Some(trace) = &mut self.trace {
+            trace.record(&self.path, self.depth, ring, outcome);
+        }
+    }
+
+    /// Whether a board reached by a first movement is a symmetric image of one already
+    /// expanded at this depth, remembering it if not.
+    fn is_symmetric_duplicate(&mut self, moved: Ring) -> bool {
+        let symmetries = match &self.symmetries {
+            Some(symmetries) => symmetries,
+            None => return false,
+        };
+        if symmetries.images(moved).any(|image| self.seen_roots.contains(&image)) {
+            self.prunes += 1;
+            return true;
+        }
+        self.seen_roots.push(moved);
+        false
+    }
+
+    /// Records `ring` if it's a perfect solve ranked better than those found so far, and
+    /// otherwise remembers it if it's the closest imperfect board so far.
+    /// Returns Some if no other solution at this depth could be kept.
+    fn check(&mut self, ring: Ring) -> Option<()> {
+        let evaluation = evaluate(ring);
+        let excess = match &self.evaluator {
+            Some(evaluator) => evaluator.distance_after(ring, &self.path),
+            None => evaluation.excess_actions(),
+        };
+        // There's nothing to order when the first movement is forced.
+        if let Some(root_excess) = self.root_excess.get_mut(self.root_index) {
+            *root_excess = (*root_excess).min(excess);
+        }
+        if excess == 0 {
+            let mut solution = Solution::new(self.path.clone(), evaluation, false);
+            solution.perfect = true;
+            self.keep_solution(solution);
+            // The fast backend takes the first solutions it finds, however they rank.
+            // Otherwise, a solution that ties with the worst kept could still replace it if
+            // its movements are ordered first, and `cannot_improve` prunes the rest.
+            let full = self.found.len() >= self.keep;
+            return if full && self.backend == Backend::Fast { Some(()) } else { None };
+        }
+        if self.best.as_ref().is_none_or(|&(best, _, _)| excess < best) {
+            self.best = Some((excess, self.path.clone(), evaluation));
+        }
+        None
+    }
+
+    /// Whether every solution under the current path ranks no better than the worst
+    /// solution kept, given `turn` more movements.
+    /// This is kept out of line, which keeps the rest of the search noticeably faster.
+    #[inline(never)]
+    fn cannot_improve(&self, turn: u16) -> bool {
+        let worst = match self.found.last() {
+            Some(worst) => worst,
+            None => return false,
+        };
+        // Every remaining movement is at least one notch, and could all be rotations.
+        let best_possible = match (&self.costs, self.selection) {
+            (Some(costs), _) => (costs.cost(&self.path) + turn as u32 * costs.cheapest_movement(), 0),
+            (None, Selection::SmallestDistance) => (total_distance(&self.path) + turn as u32, 0),
+            (None, Selection::RotationsFirst) => {
+                (shift_pattern(&self.path) << turn, total_distance(&self.path) + turn as u32)
+            }
+            (None, Selection::Lexicographic) => (0, 0),
+            // Any board could still finish with its structure already in place.
+            (None, Selection::HumanLike) => (0, total_distance(&self.path) + turn as u32),
+        };
+        match best_possible.cmp(&self.rank(worst)) {
+            // A tie is broken by the movements, and every solution under here starts with
+            // the path, or with one of its images when symmetric branches are skipped.
+            Ordering::Equal => {
+                let worst_start = worst.moves.iter().take(self.path.len()).map(RingMovement::sort_key);
+                let ordered_after = |path: &[RingMovement]| {
+                    path.iter().map(RingMovement::sort_key).gt(worst_start.clone())
+                };
+                ordered_after(&self.path)
+                    && self.symmetries.as_ref().is_none_or(|symmetries| {
+                        symmetries.movement_images(&self.path).all(|image| ordered_after(&image))
+                    })
+            }
+            order => order == Ordering::Greater,
+        }
+    }
+
+    /// How `solution` ranks against others at the same depth, or at any depth with costs;
+    /// lower is better. Ties are broken by `lexicographic_order`.
+    fn rank(&self, solution: &Solution) -> (u32, u32) {
+        match (&self.costs, self.selection) {
+            (Some(costs), _) => (costs.cost(&solution.moves), type_switches(&solution.moves)),
+            (None, Selection::SmallestDistance) => solution.rank(),
+            (None, Selection::RotationsFirst) => {
+                (shift_pattern(&solution.moves), solution.total_distance)
+            }
+            (None, Selection::Lexicographic) => (0, 0),
+            (None, Selection::HumanLike) => {
+                let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+                (pedagogy::structure_delay(self.start, &moves), solution.total_distance)
+            }
+        }
+    }
+
+    /// Whether `a` should be kept ahead of `b`.
+    fn ranks_before(&self, a: &Solution, b: &Solution) -> bool {
+        match self.rank(a).cmp(&self.rank(b)) {
+            Ordering::Equal => lexicographic_order(&a.moves, &b.moves) == Ordering::Less,
+            order => order == Ordering::Less,
+        }
+    }
+
+    /// Adds a solution to `found` if it ranks well enough and isn't equivalent to one
+    /// already there.
+    ///
+    /// When the search skips first movements whose boards are symmetric images of others,
+    /// the images of each solution are considered too, so the same solution is chosen as
+    /// if the search had tried them.
+    #[inline(never)]
+    fn keep_solution(&mut self, solution: Solution) {
+        if let Some(symmetries) = &self.symmetries {
+            let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+            let images: Vec<Solution> = symmetries
+                .movement_images(&moves)
+                .zip(symmetries.images(solution.result))
+                .map(|(moves, result)| Solution {
+                    moves: moves.into(),
+                    result,
+                    attacks: attack_plan(result),
+                    ..solution.clone()
+                })
+                // The search only reduces by symmetries that keep the constraints, but an
+                // image the constraints don't allow must never be chosen.
+                .filter(|image| {
+                    image.moves.iter().all(|movement| self.constraints.violation(movement).is_none())
+                })
+                .collect();
+            for image in images {
+                self.keep_ranked(image);
+            }
+        }
+        self.keep_ranked(solution);
+    }
+
+    fn keep_ranked(&mut self, solution: Solution) {
+        let full = self.found.len() >= self.keep;
+        if full && self.found.last().is_some_and(|worst| !self.ranks_before(&solution, worst)) {
+            return;
+        }
+        // Equivalent sequences rank the same, so only keep whichever is ordered first.
+        let canonical = canonical_order(solution.moves.iter().copied().collect());
+        let duplicate = self.found.iter().position(|found| {
+            canonical_order(found.moves.iter().copied().collect()) == canonical
+        });
+        if let Some(duplicate) = duplicate {
+            if !self.ranks_before(&solution, &self.found[duplicate]) {
+                return;
+            }
+            self.found.remove(duplicate);
+        }
+        let index = self.found.partition_point(|found| !self.ranks_before(&solution, found));
+        self.found.insert(index, solution);
+        self.found.truncate(self.keep);
+    }
+}
+
+/// Sorts runs of commuting movements, so equivalent sequences compare equal.
+///
+/// Rotations always commute with each other, and so do shifts, since distinct rows
+/// never share a cell. A rotation and a shift generally don't commute.
+fn canonical_order(mut moves: Vec<RingMovement>) -> Vec<RingMovement> {
+    for run in moves.chunk_by_mut(|a, b| a.is_rotation() == b.is_rotation()) {
+        run.sort_by_key(RingMovement::sort_key);
+    }
+    moves
+}
+
+/// Orders sequences by their movements one at a time, as documented on `Selection`.
+fn lexicographic_order(a: &VecDeque<RingMovement>, b: &VecDeque<RingMovement>) -> Ordering {
+    a.iter().map(RingMovement::sort_key).cmp(b.iter().map(RingMovement::sort_key))
+}
+
+/// A bit for each movement that's a shift, with the first movement highest, so sequences
+/// of the same length that rotate earlier compare lower.
+fn shift_pattern<'m>(moves: impl IntoIterator<Item = &'m RingMovement>) -> u32 {
+    moves.into_iter().fold(0, |pattern, movement| (pattern << 1) | !movement.is_rotation() as u32)
+}
+
+/// The number of times a sequence switches between rotations and shifts.
+fn type_switches(moves: &VecDeque<RingMovement>) -> u32 {
+    moves
+        .iter()
+        .zip(moves.iter().skip(1))
+        .filter(|(a, b)| a.is_rotation() != b.is_rotation())
+        .count() as u32
+}
+
+/// The total number of notches the player has to drag to make `moves`.
+fn total_distance(moves: &[RingMovement]) -> u32 {
+    moves.iter().map(RingMovement::distance).sum()
+}
+
+/// The deepest search `turns_required` will count every solution for. Each extra turn
+/// costs as much more as it does for `heuristic::EXACT_TURNS`.
+const MAX_COUNT_TURNS: u16 = MAX_TURNS;
+
+/// The most boards `turns_required` visits to count the solutions at one number of turns.
+const MAX_COUNT_NODES: u64 = 2_000_000;
+
+/// How many move sequences of a given length solve a board.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolutionCount {
+    pub turns: u16,
+    /// The number of solving sequences, or None if it's too expensive to count, more than
+    /// `MAX_COUNT_TURNS` or `MAX_COUNT_NODES` boards.
+    pub solutions: Option<u64>,
+}
+
+/// How many turns a board needs, and how much an extra turn would help.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TurnsRequired {
+    /// The fewest turns that solve the board, or None if it can't be solved in `MAX_TURNS`.
+    pub min_turns: Option<u16>,
+    /// The number of solutions with the minimum number of turns and the two after it.
+    pub solution_counts: Vec<SolutionCount>,
+}
+
+/// Finds the minimum number of turns for a board, and counts the solutions at that
+/// number of turns and the two after it. Players can use this to judge whether an extra
+/// move (e.g. from a Toad cheer) opens up many more ways to solve it.
+#[wasm_bindgen(skip_typescript)]
+pub fn turns_required(ring: JsValue) -> Result<JsValue> {
+    let ring = validation::ring_from_js(ring)?;
+    Ok(serde_wasm_bindgen::to_value(&find_turns_required(ring))?)
+}
+
+fn find_turns_required(ring: Ring) -> TurnsRequired {
+    let min_turns = Search::new(&SolveOptions::default())
+        .find_solution(ring, MAX_TURNS)
+        .map(|solution| solution.moves.len() as u16);
+    let counting = SolveOptions { max_nodes: Some(MAX_COUNT_NODES), ..Default::default() };
+    let solution_counts = match min_turns {
+        Some(min_turns) => (min_turns..=min_turns + 2)
+            .map(|turns| SolutionCount {
+                turns,
+                solutions: if turns <= MAX_COUNT_TURNS {
+                    count_solutions(ring, turns, &mut Budget::new(&counting))
+                } else {
+                    None
+                },
+            })
+            .collect(),
+        None => Vec::new(),
+    };
+    TurnsRequired { min_turns, solution_counts }
+}
+
+/// Counts the sequences of exactly `turns` movements that leave `ring` perfectly solved,
+/// or None if `budget` runs out first.
+fn count_solutions(ring: Ring, turns: u16, budget: &mut Budget) -> Option<u64> {
+    if budget.spent() {
+        return None;
+    }
+    budget.nodes += 1;
+    if turns == 0 {
+        return Some(evaluate(ring).is_perfect() as u64);
+    }
+    let mut count = 0;
+    let spent = iterate_movements(ring, &MoveConstraints::default(), |_, moved| {
+        match count_solutions(moved, turns - 1, budget) {
+            Some(solutions) => {
+                count += solutions;
+                None
+            }
+            None => Some(()),
+        }
+    });
+    spent.is_none().then_some(count)
+}
+
+/// How many actions a board needs to be cleared, and how many it has available.
+#[derive(Clone, Copy, Debug)]
+struct Evaluation {
+    result: Ring,
+    /// The number of attacks the player gets, one per four enemies.
+    actions: u32,
+    /// The number of jumps needed, one for each angle with an enemy in the outer rings.
+    jump_rows: u32,
+    /// The number of hammers needed for the enemies that aren't jumped on, and to break
+    /// up the lines with gaps.
+    hammerable_groups: u32,
+}
+
+impl Evaluation {
+    /// Whether every enemy can be hit with the actions available.
+    fn is_perfect(&self) -> bool {
+        self.hammerable_groups + self.jump_rows <= self.actions
+    }
+
+    /// How many more actions than are available it would take to hit every enemy.
+    fn excess_actions(&self) -> u32 {
+        (self.hammerable_groups + self.jump_rows).saturating_sub(self.actions)
+    }
+}
+
+/// How a board stands without moving it, for live feedback as the player edits or moves
+/// it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardEvaluation {
+    /// The number of enemies on the board.
+    pub enemies: u32,
+    /// The number of attacks the player gets, one per four enemies.
+    pub actions: u32,
+    /// The number of jumps needed, one for each angle with an enemy in the outer rings.
+    pub jump_rows: u32,
+    /// The number of hammers needed for the enemies that aren't jumped on.
+    pub hammerable_groups: u32,
+    /// Whether every enemy can already be hit with the actions available.
+    pub perfect: bool,
+}
+
+impl From<Evaluation> for BoardEvaluation {
+    fn from(evaluation: Evaluation) -> Self {
+        BoardEvaluation {
+            enemies: evaluation.result.iter().map(|angles| angles.count_ones()).sum(),
+            actions: evaluation.actions,
+            jump_rows: evaluation.jump_rows,
+            hammerable_groups: evaluation.hammerable_groups,
+            perfect: evaluation.is_perfect(),
+        }
+    }
+}
+
+/// Evaluate a board as it stands, as a `BoardEvaluation`, without searching, e.g. to show
+/// "already solved" or "needs N actions" as the player moves the rings.
+#[wasm_bindgen(skip_typescript)]
+pub fn evaluate_board(ring: JsValue) -> Result<JsValue> {
+    let ring: Ring = serde_wasm_bindgen::from_value(ring)?;
+    check_ring(&ring)?;
+    Ok(serde_wasm_bindgen::to_value(&BoardEvaluation::from(evaluate(ring)))?)
+}
+
+impl Solution {
+    /// How solutions with the same number of turns are ranked; lower is better.
+    fn rank(&self) -> (u32, u32) {
+        (self.total_distance, type_switches(&self.moves))
+    }
+
+    fn new(moves: Vec<RingMovement>, evaluation: Evaluation, truncated: bool) -> Self {
+        Solution {
+            total_distance: total_distance(&moves),
+            moves: moves.into(),
+            result: evaluation.result,
+            jump_rows: evaluation.jump_rows,
+            hammerable_groups: evaluation.hammerable_groups,
+            attacks: attack_plan(evaluation.result),
+            perfect: evaluation.is_perfect(),
+            perfect_bonus: evaluation.is_perfect(),
+            truncated,
+            binding: Vec::new(),
+            hazards: Vec::new(),
+            stats: None,
+            damage: None,
+            loadout: None,
+            items: Vec::new(),
+            partner_attack: None,
+            reward: None,
+            timing: None,
+            toad_alert: None,
+        }
+    }
+}
+
+/// Counts the jumps and hammers it takes to hit every enemy on the board.
+fn evaluate(ring: Ring) -> Evaluation {
+    // The number of enemies on the board.
+    let enemies: u32 = ring.iter().copied().map(u16::count_ones).sum();
+
+    // The enemies of the outer two rings, only accessible through jumps.
+    // We merge the two outer rings because an enemy at any angle requires a jump there.
+    let outer = ring[2] | ring[3];
+
+    // The enemies of the inner two rings that have to be hit by hammers, since they aren't
+    // in an angle that's jumped anyway.
+    let hammer_targets = [ring[0] & !outer, ring[1] & !outer];
+
+    // The number of actions is ceil(enemies / 4).
+    let actions = enemies.div_ceil(4);
+
+    // The number of jumps necessary for this ring.
+    let jump_rows = outer.count_ones();
+
+    // The number of hammers needed. Every hammer hits both inner rings, so this only
+    // depends on which angles have a target in either. An angle whose line has a gap
+    // takes either a second jump or a hammer, and the hammer is never worse.
+    let inner = hammer_targets[0] | hammer_targets[1] | gapped_angles(ring);
+    let hammerable_groups = tables().hammer_groups[inner as usize] as u32;
+
+    Evaluation {
+        result: ring,
+        actions,
+        jump_rows,
+        hammerable_groups,
+    }
+}
+
+/// The angles whose enemies don't form one contiguous line outward from the innermost.
+///
+/// A jump only hits the enemies in a line outward from the innermost one still standing
+/// in its angle, so these angles take a second jump to clear, unless the inner two rings
+/// are hammered first. That always leaves a single line, since the gap has to be in the
+/// inner two rings or just inside the outermost enemy.
+fn gapped_angles(ring: Ring) -> u16 {
+    let outer = ring[2] | ring[3];
+    (ring[0] & !ring[1] & outer) | ((ring[0] | ring[1]) & !ring[2] & ring[3])
+}
+
+/// The attacks that hit every enemy on the board, with as many jumps as `jump_rows` and
+/// as many hammers as `hammerable_groups`.
+///
+/// The hammers come first, since they break up the lines with gaps for the jumps after.
+fn attack_plan(ring: Ring) -> Vec<Attack> {
+    let outer = ring[2] | ring[3];
+    let targets = [(ring[0] & !outer) | gapped_angles(ring), ring[1] & !outer, 0, 0];
+    let hammers = hammer_blocks(targets).into_iter().map(|th| Attack::Hammer { th });
+    let jumps = (0..NUM_ANGLES).filter(|th| outer & (1 << th) != 0).map(|th| Attack::Jump { th });
+    hammers.chain(jumps).collect()
+}
+
+/// The cells a hammer aimed at `angle` hits: that angle and the next one clockwise, in
+/// both of the inner two rings.
+fn hammer_block(angle: u16) -> Ring {
+    let angles = Subring::<NUM_ANGLES>(0b11).rotate_left(angle).value();
+    [angles, angles, 0, 0]
+}
+
+/// The angles to aim hammers at so every enemy of `targets` in the inner two rings is hit,
+/// with as few hammers as possible. Enemies in the outer two rings are ignored.
+///
+/// Starting from an angle without targets, each hammer is aimed at the first cell clockwise
+/// that's still standing. Any hammer hitting that cell can at best also hit the next angle,
+/// so this is optimal.
+///
+/// For an inner two rings of 101110011110 (either ring, since a hammer hits both), it
+/// takes 5 hammers, aimed at angles 1, 3, 7, 9, and 11:
+///
+/// ```text
+/// targets:  101110011110
+/// hammer 1: 000000000110
+/// hammer 2: 000000011000
+/// hammer 3: 000110000000
+/// hammer 4: 011000000000
+/// hammer 5: 100000000001 (wrapping around to angle 0)
+/// ```
+fn hammer_blocks(targets: Ring) -> ArrayVec<[u16; (NUM_ANGLES / 2) as usize]> {
+    let mut targets = targets;
+    let mut blocks = ArrayVec::new();
+    let standing = |targets: &Ring, th: u16| (targets[0] | targets[1]) & (1 << th) != 0;
+    // Starting after an empty angle means no hammer has to be aimed across the start.
+    // If every angle has a target, any start is as good as another.
+    let start = (0..NUM_ANGLES).find(|&th| !standing(&targets, th)).unwrap_or(0);
+    for offset in 1..=NUM_ANGLES {
+        let th = (start + offset) % NUM_ANGLES;
+        if standing(&targets, th) {
+            blocks.push(th);
+            let block = hammer_block(th);
+            targets[0] &= !block[0];
+            targets[1] &= !block[1];
+        }
+    }
+    blocks
+}
+
+/// Lookup tables the solver uses, built once on first use or by `warm_up`.
+struct Tables {
+    /// How many `hammer_blocks` it takes to hit targets at every possible set of angles.
+    hammer_groups: Box<[u8; 1 << NUM_ANGLES]>,
+}
+
+static TABLES: OnceLock<Tables> = OnceLock::new();
+
+/// Gets the lookup tables, building them if they don't exist yet.
+fn tables() -> &'static Tables {
+    TABLES.get_or_init(|| {
+        let mut hammer_groups = Box::new([0; 1 << NUM_ANGLES]);
+        for (inner, groups) in hammer_groups.iter_mut().enumerate() {
+            *groups = hammer_blocks([inner as u16, 0, 0, 0]).len() as u8;
+        }
+        Tables { hammer_groups }
+    })
+}
+
+/// Builds the solver's lookup tables ahead of time, so apps can pay for them during a
+/// loading screen instead of on the first solve.
+///
+/// `level` chooses how much to build:
+/// - 0: nothing; tables are built lazily on first use.
+/// - 1 or more: every table the solver currently has (the hammer group table).
+///
+/// Levels above 1 are accepted so callers can ask for more eagerly built data
+/// (e.g. pattern databases) without changes once those tables exist.
+#[wasm_bindgen]
+pub fn warm_up(level: u32) {
+    if level >= 1 {
+        tables();
     }
 }
 
@@ -0,0 +1,151 @@
+//! Whole-board symmetries: rotating every ring together, and mirroring across an axis.
+
+use crate::{validation, MaskedInt, Result, Ring, RingMovement, Subring, NUM_ANGLES, NUM_RINGS};
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+/// The symmetries of a board.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Symmetries {
+    /// Every clockwise rotation in `1..12` that leaves the board unchanged.
+    pub rotations: Vec<u16>,
+    /// How many ways the board can be rotated onto itself, counting the identity,
+    /// e.g. 3 for "3-fold symmetry". 1 if the board has no rotational symmetry.
+    pub rotational_order: u16,
+    /// Every mirror that leaves the board unchanged, given as the `axis` of `mirror_board`.
+    pub mirrors: Vec<u16>,
+}
+
+impl Symmetries {
+    /// Whether the board has any symmetry besides the identity.
+    pub fn is_symmetric(&self) -> bool {
+        !self.rotations.is_empty() || !self.mirrors.is_empty()
+    }
+
+    /// Every board `ring` maps to under these symmetries, except the identity.
+    pub fn images(&self, ring: Ring) -> impl Iterator<Item = Ring> + '_ {
+        let rotated = self.rotations.iter().map(move |&amount| rotate_board(ring, amount));
+        let mirrored = self.mirrors.iter().map(move |&axis| mirror_board(ring, axis));
+        rotated.chain(mirrored)
+    }
+
+    /// The movements that do to each image of a board what `moves` do to the board itself,
+    /// in the same order as `images`.
+    pub fn movement_images<'m>(
+        &'m self,
+        moves: &'m [RingMovement],
+    ) -> impl Iterator<Item = Vec<RingMovement>> + 'm {
+        let rotated = self.rotations.iter().map(move |&amount| {
+            moves.iter().map(|movement| rotate_movement(movement, amount)).collect()
+        });
+        let mirrored = self.mirrors.iter().map(move |&axis| {
+            moves.iter().map(|movement| mirror_movement(movement, axis)).collect()
+        });
+        rotated.chain(mirrored)
+    }
+
+    /// Only the symmetries that map the rows in `rows`, bit `th` for the row at `th` and
+    /// `th + 6`, onto each other, so the images of allowed movements are allowed too.
+    pub fn preserving_rows(mut self, rows: u16) -> Self {
+        let half = NUM_ANGLES / 2;
+        let preserved = |image: &dyn Fn(u16) -> u16| {
+            (0..half).filter(|&th| rows & (1 << th) != 0).all(|th| rows & (1 << (image(th) % half)) != 0)
+        };
+        self.rotations.retain(|&amount| preserved(&|th| th + amount));
+        self.mirrors.retain(|&axis| preserved(&|th| axis + NUM_ANGLES - th));
+        self.rotational_order = self.rotations.len() as u16 + 1;
+        self
+    }
+}
+
+/// Writes a movement the way the search makes it: rows at angles below 6, and half turns
+/// clockwise or outward.
+fn normalize_movement(movement: RingMovement) -> RingMovement {
+    match movement {
+        RingMovement::Ring { r, amount, clockwise } => RingMovement::Ring {
+            r,
+            amount,
+            clockwise: clockwise || amount as u16 == NUM_ANGLES / 2,
+        },
+        RingMovement::Row { th, amount, outward } => {
+            let (th, outward) = if th >= NUM_ANGLES / 2 {
+                (th - NUM_ANGLES / 2, !outward)
+            } else {
+                (th, outward)
+            };
+            RingMovement::Row { th, amount, outward: outward || amount as u16 == NUM_RINGS }
+        }
+    }
+}
+
+/// The movement on `rotate_board(ring, amount)` that matches `movement` on `ring`.
+pub fn rotate_movement(movement: &RingMovement, amount: u16) -> RingMovement {
+    normalize_movement(match *movement {
+        RingMovement::Row { th, amount: shift, outward } => {
+            RingMovement::Row { th: (th + amount) % NUM_ANGLES, amount: shift, outward }
+        }
+        // Rotating a ring commutes with rotating the whole board.
+        ring => ring,
+    })
+}
+
+/// The movement on `mirror_board(ring, axis)` that matches `movement` on `ring`.
+pub fn mirror_movement(movement: &RingMovement, axis: u16) -> RingMovement {
+    normalize_movement(match *movement {
+        RingMovement::Ring { r, amount, clockwise } => {
+            RingMovement::Ring { r, amount, clockwise: !clockwise }
+        }
+        RingMovement::Row { th, amount, outward } => RingMovement::Row {
+            th: (axis + NUM_ANGLES - th % NUM_ANGLES) % NUM_ANGLES,
+            amount,
+            outward,
+        },
+    })
+}
+
+/// Rotates every subring clockwise by `amount` angles.
+pub fn rotate_board(ring: Ring, amount: u16) -> Ring {
+    let mut rotated = ring;
+    for subring in rotated.iter_mut() {
+        *subring = Subring::<NUM_ANGLES>(*subring).rotate_left(amount).value();
+    }
+    rotated
+}
+
+/// Mirrors every subring, so the enemy at angle `th` moves to angle `(axis - th) mod 12`.
+///
+/// Even axes mirror across the line through the middle of angle `axis / 2`,
+/// and odd axes mirror across the border between two angles.
+pub fn mirror_board(ring: Ring, axis: u16) -> Ring {
+    let mut mirrored = ring;
+    for subring in mirrored.iter_mut() {
+        // Reversing moves angle th to 11 - th; rotating then moves it to axis - th.
+        let reversed = subring.reverse_bits() >> (16 - NUM_ANGLES);
+        *subring = Subring::<NUM_ANGLES>(reversed).rotate_left((axis + 1) % NUM_ANGLES).value();
+    }
+    mirrored
+}
+
+/// Finds every rotation and mirror that leaves the board unchanged.
+pub fn find_symmetries(ring: Ring) -> Symmetries {
+    let rotations: Vec<u16> = (1..NUM_ANGLES)
+        .filter(|&amount| rotate_board(ring, amount) == ring)
+        .collect();
+    let mirrors = (0..NUM_ANGLES)
+        .filter(|&axis| mirror_board(ring, axis) == ring)
+        .collect();
+    Symmetries {
+        rotational_order: rotations.len() as u16 + 1,
+        rotations,
+        mirrors,
+    }
+}
+
+/// Report the rotational and mirror symmetries of a board,
+/// e.g. so the UI can show "this puzzle has 3-fold symmetry".
+#[wasm_bindgen(skip_typescript)]
+pub fn symmetries(ring: JsValue) -> Result<JsValue> {
+    let ring = validation::ring_from_js(ring)?;
+    Ok(serde_wasm_bindgen::to_value(&find_symmetries(ring))?)
+}
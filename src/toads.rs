@@ -0,0 +1,52 @@
+//! Crumpled Toads stuck on the board, which the player wants to rescue rather than hit.
+//!
+//! ```json
+//! {"toads": [0, 4, 0, 0]}
+//! ```
+//!
+//! The Toads move with the rings like the enemies do. A board only counts as solved if
+//! none of the attacks that clear it would land on a Toad.
+
+use crate::evaluator::Evaluator;
+use crate::preset::Rules;
+use crate::{apply_movement, Ring, RingMovement};
+use std::rc::Rc;
+
+/// Clears the board for `goal` without attacking a Toad. The distance is that of `goal`,
+/// plus one for every Toad an attack would land on.
+pub struct ToadGoal {
+    toads: Ring,
+    rules: Rules,
+    goal: Rc<dyn Evaluator>,
+}
+
+impl ToadGoal {
+    /// The Toads start on `toads`, and the attacks are the ones `rules` allow.
+    pub fn new(toads: Ring, rules: Rules, goal: Rc<dyn Evaluator>) -> Self {
+        ToadGoal { toads, rules, goal }
+    }
+
+    /// How many Toads the attacks on `ring` land on, after the Toads are moved by `moves`.
+    pub fn toads_hit(&self, ring: Ring, moves: &[RingMovement]) -> u32 {
+        let toads = moves.iter().fold(self.toads, apply_movement);
+        let hit = self.rules.attack_plan(ring).iter().fold([0; 4], |hit: Ring, attack| {
+            let footprint = attack.footprint();
+            std::array::from_fn(|r| hit[r] | footprint[r])
+        });
+        toads.iter().zip(&hit).map(|(toads, hit)| (toads & hit).count_ones()).sum()
+    }
+}
+
+impl Evaluator for ToadGoal {
+    fn distance(&self, ring: Ring) -> u32 {
+        self.distance_after(ring, &[])
+    }
+
+    fn distance_after(&self, ring: Ring, moves: &[RingMovement]) -> u32 {
+        self.goal.distance_after(ring, moves) + self.toads_hit(ring, moves)
+    }
+
+    fn allows(&self, moves: &[RingMovement]) -> bool {
+        self.goal.allows(moves)
+    }
+}
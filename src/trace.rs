@@ -0,0 +1,206 @@
+//! A record of the boards a search visits, and why it skipped the ones it did, for
+//! visualizing how the solver works.
+//!
+//! Each depth of the iterative deepening searches the tree again from the root, so a trace
+//! has one tree for each `iteration`. Traces get big quickly: a 4-turn search visits
+//! millions of boards, so only the first `max_nodes` are recorded.
+
+use crate::{options_from_js, Result, Ring, RingMovement, Search, SolveOptions, MAX_TURNS};
+use serde::Serialize;
+use std::fmt::Write;
+use wasm_bindgen::prelude::*;
+
+/// How many boards `trace_search` records by default.
+pub const TRACE_NODES: usize = 5000;
+
+/// The boards a search visited, in the order it visited them.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchTrace {
+    pub nodes: Vec<TraceNode>,
+    /// Whether the search visited more boards than were recorded.
+    pub truncated: bool,
+}
+
+/// A board the search visited or skipped.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceNode {
+    /// The index of this node in `SearchTrace::nodes`.
+    pub id: usize,
+    /// The node this board was reached from, or None for the root of an iteration.
+    pub parent: Option<usize>,
+    /// The number of turns the search was looking for solutions with.
+    pub iteration: u16,
+    /// The movement made from the parent's board.
+    pub movement: Option<RingMovement>,
+    pub board: Ring,
+    pub outcome: Outcome,
+}
+
+/// What the search did with a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Outcome {
+    /// Every movement from the board was tried.
+    Expanded,
+    /// The board is a solution.
+    Solution,
+    /// The board is as deep as the iteration goes, but isn't a solution.
+    Leaf { excess: u32 },
+    /// The board was skipped without trying any movement from it.
+    Pruned { reason: PruneReason },
+}
+
+/// Why a board was skipped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PruneReason {
+    /// Nothing under it could rank better than the solutions already found.
+    CannotImprove,
+    /// `heuristic::occupancy_bound` proves it can't be solved in the turns left.
+    Heuristic,
+    /// It's a rotated or mirrored image of a board already searched.
+    Symmetric,
+    /// The goal doesn't allow the movements that reached it.
+    Disallowed,
+}
+
+/// Builds a `SearchTrace` as the search runs.
+pub(crate) struct TraceRecorder {
+    trace: SearchTrace,
+    max_nodes: usize,
+    /// The last node recorded at each distance from the root, which are the ancestors of
+    /// the next node.
+    open: Vec<usize>,
+}
+
+impl TraceRecorder {
+    pub(crate) fn new(max_nodes: usize) -> Self {
+        TraceRecorder { trace: SearchTrace::default(), max_nodes, open: Vec::new() }
+    }
+
+    /// Records a board reached by `path` from the root of `iteration`.
+    pub(crate) fn record(
+        &mut self,
+        path: &[RingMovement],
+        iteration: u16,
+        board: Ring,
+        outcome: Outcome,
+    ) {
+        if self.trace.nodes.len() >= self.max_nodes {
+            self.trace.truncated = true;
+            return;
+        }
+        let id = self.trace.nodes.len();
+        self.open.truncate(path.len());
+        let parent = path.len().checked_sub(1).and_then(|depth| self.open.get(depth).copied());
+        self.open.push(id);
+        self.trace.nodes.push(TraceNode {
+            id,
+            parent,
+            iteration,
+            movement: path.last().copied(),
+            board,
+            outcome,
+        });
+    }
+
+    pub(crate) fn finish(self) -> SearchTrace {
+        self.trace
+    }
+}
+
+impl Outcome {
+    fn dot_style(&self) -> &'static str {
+        match self {
+            Outcome::Expanded => "",
+            Outcome::Solution => ", style=filled, fillcolor=palegreen",
+            Outcome::Leaf { .. } => ", color=gray",
+            Outcome::Pruned { .. } => ", style=dashed, color=red",
+        }
+    }
+}
+
+impl SearchTrace {
+    /// The trace as a Graphviz graph, with one cluster for each iteration.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph search {\n  node [shape=box, fontname=monospace];\n");
+        let mut iteration = None;
+        for node in &self.nodes {
+            if iteration != Some(node.iteration) {
+                if iteration.is_some() {
+                    dot.push_str("  }\n");
+                }
+                iteration = Some(node.iteration);
+                let _ = writeln!(
+                    dot,
+                    "  subgraph cluster_{0} {{\n    label=\"{0} turns\";",
+                    node.iteration
+                );
+            }
+            let movement = match node.movement {
+                Some(RingMovement::Ring { r, amount, clockwise }) => {
+                    format!("ring {} {}{}", r, if clockwise { '+' } else { '-' }, amount)
+                }
+                Some(RingMovement::Row { th, amount, outward }) => {
+                    format!("row {} {}{}", th, if outward { '+' } else { '-' }, amount)
+                }
+                None => "start".to_string(),
+            };
+            let outcome = match node.outcome {
+                Outcome::Expanded => String::new(),
+                Outcome::Solution => "\\nsolution".to_string(),
+                Outcome::Leaf { excess } => format!("\\n{} short", excess),
+                Outcome::Pruned { reason } => format!("\\npruned: {:?}", reason),
+            };
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}{}\"{}];",
+                node.id,
+                movement,
+                outcome,
+                node.outcome.dot_style()
+            );
+            if let Some(parent) = node.parent {
+                let _ = writeln!(dot, "    n{} -> n{};", parent, node.id);
+            }
+        }
+        if iteration.is_some() {
+            dot.push_str("  }\n");
+        }
+        if self.truncated {
+            dot.push_str("  truncated [label=\"…more boards not recorded\", shape=plaintext];\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// Solves `ring` like `solve`, recording the first `max_nodes` boards the search visits.
+pub fn trace(ring: Ring, options: &SolveOptions, max_nodes: usize) -> SearchTrace {
+    let mut search = Search::new(options).with_trace(max_nodes);
+    search.find_solution(ring, MAX_TURNS);
+    search.take_trace().unwrap_or_default()
+}
+
+/// Record the boards the search visits while solving `ring`, as `"json"` or `"dot"`
+/// (Graphviz) text. Only the first `max_nodes` boards are recorded, or `TRACE_NODES`
+/// if not given.
+#[wasm_bindgen(skip_typescript)]
+pub fn trace_search(
+    ring: JsValue,
+    options: JsValue,
+    format: &str,
+    max_nodes: Option<u32>,
+) -> Result<String> {
+    let ring: Ring = serde_wasm_bindgen::from_value(ring)?;
+    let options = options_from_js(options)?;
+    let max_nodes = max_nodes.map_or(TRACE_NODES, |max_nodes| max_nodes as usize);
+    match format {
+        "json" => Ok(serde_json::to_string(&trace(ring, &options, max_nodes))
+            .map_err(|e| e.to_string())?),
+        "dot" => Ok(trace(ring, &options, max_nodes).to_dot()),
+        _ => Err(format!("unknown trace format {:?}, expected \"json\" or \"dot\"", format).into()),
+    }
+}
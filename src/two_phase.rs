@@ -0,0 +1,103 @@
+//! A quick, non-optimal solver that works like a human: first line up the outer rings
+//! for jumps, then rotate the inner rings into place for hammers.
+//!
+//! The first phase only has to reach a much easier goal than a full solve, within a few
+//! turns, and the second phase only tries a couple of inner rotations, so this searches far
+//! fewer boards than the full search does on hard boards. In exchange, it can take more
+//! turns than the optimal solution, and it finds nothing when those rotations can't fix the
+//! inner rings, e.g. on boards that can only be solved by shifting inner enemies into place.
+
+use crate::{evaluate, iterate_movements, MoveConstraints, Ring, RingMovement, Solution};
+
+/// The most turns the second phase tries: enough to rotate each inner ring once. Boards
+/// whose inner rings need more are left unsolved.
+const INNER_TURNS: u16 = 2;
+
+/// The most turns the first phase tries. Boards that need more to line up the outer rings
+/// are left to the full search.
+const MAX_OUTER_TURNS: u16 = 3;
+
+/// Whether the outer rings leave few enough jumps that the inner enemies could fit in the
+/// remaining actions, if the inner rings were rotated to suit.
+///
+/// Each jump also hits the two inner cells at its angle, and each hammer covers at most
+/// four inner cells.
+fn outer_aligned(ring: Ring) -> bool {
+    let evaluation = evaluate(ring);
+    let inner = ring[0].count_ones() + ring[1].count_ones();
+    let hammers = inner.saturating_sub(2 * evaluation.jump_rows).div_ceil(4);
+    evaluation.jump_rows + hammers <= evaluation.actions
+}
+
+/// The state of a two-phase solve.
+struct TwoPhase {
+    /// The moves made to reach the board currently being searched.
+    path: Vec<RingMovement>,
+    constraints: MoveConstraints,
+    /// The movements allowed in the second phase: rotations of the inner rings.
+    inner_constraints: MoveConstraints,
+}
+
+impl TwoPhase {
+    /// Searches for boards `turn` moves away with aligned outer rings, then tries to
+    /// finish each within the turns left.
+    fn align_outer(&mut self, ring: Ring, turn: u16, turns_left: u16) -> Option<Ring> {
+        if turn == 0 {
+            if !outer_aligned(ring) {
+                return None;
+            }
+            return (0..=INNER_TURNS.min(turns_left)).find_map(|inner| self.fix_inner(ring, inner));
+        }
+        let constraints = self.constraints;
+        iterate_movements(ring, &constraints, |movement, moved| {
+            self.path.push(movement);
+            let solved = self.align_outer(moved, turn - 1, turns_left - 1);
+            if solved.is_none() {
+                self.path.pop();
+            }
+            solved
+        })
+    }
+
+    /// Searches for a perfect board exactly `turn` inner rotations away.
+    fn fix_inner(&mut self, ring: Ring, turn: u16) -> Option<Ring> {
+        if turn == 0 {
+            return evaluate(ring).is_perfect().then_some(ring);
+        }
+        let constraints = self.inner_constraints;
+        iterate_movements(ring, &constraints, |movement, moved| {
+            self.path.push(movement);
+            let solved = self.fix_inner(moved, turn - 1);
+            if solved.is_none() {
+                self.path.pop();
+            }
+            solved
+        })
+    }
+}
+
+/// Finds a solution in two phases, trying the fewest outer moves first.
+/// `first_move`, if given, is made before either phase and counts as one of the turns.
+pub(crate) fn find_solution(
+    ring: Ring,
+    max_turns: u16,
+    first_move: Option<RingMovement>,
+    constraints: &MoveConstraints,
+) -> Option<Solution> {
+    let mut inner_constraints = *constraints;
+    inner_constraints.shifts = false;
+    inner_constraints.rings &= 0b0011;
+    // Rotating an inner ring doesn't help align the outer rings.
+    let mut outer_constraints = *constraints;
+    outer_constraints.rings &= 0b1100;
+    let mut search = TwoPhase { path: Vec::new(), constraints: outer_constraints, inner_constraints };
+    let (start, max_turns) = match first_move {
+        Some(movement) => {
+            search.path.push(movement);
+            (crate::apply_movement(ring, &movement), max_turns.checked_sub(1)?)
+        }
+        None => (ring, max_turns),
+    };
+    let result = (0..=max_turns.min(MAX_OUTER_TURNS)).find_map(|turn| search.align_outer(start, turn, max_turns))?;
+    Some(Solution::new(search.path, evaluate(result), false))
+}
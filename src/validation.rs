@@ -0,0 +1,129 @@
+//! Checks a board from JavaScript before it's solved, reporting every problem with it as a
+//! `BoardError`, instead of the one opaque message reading it as a `Ring` gives.
+
+use crate::{Result, Ring, NUM_ANGLES, NUM_RINGS};
+use serde::Serialize;
+use std::fmt;
+use wasm_bindgen::prelude::*;
+
+/// Something wrong with a board.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BoardError {
+    /// The board isn't an array of numbers.
+    NotAnArray { reason: String },
+    /// The board doesn't have one number for each ring.
+    WrongLength { expected: usize, actual: usize },
+    /// A ring's number isn't a whole number of at least 0.
+    NotAWholeNumber { ring: usize, value: f64 },
+    /// A ring has enemies at angles that don't exist, 12 and up.
+    AnglesOutOfRange { ring: usize, angles: Vec<u32> },
+    /// The board has no enemies, so there's no battle to solve.
+    NoEnemies,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardError::NotAnArray { reason } => {
+                write!(f, "the board isn't an array of numbers: {}", reason)
+            }
+            BoardError::WrongLength { expected, actual } => {
+                write!(f, "the board has {} rings, expected {}", actual, expected)
+            }
+            BoardError::NotAWholeNumber { ring, value } => {
+                write!(f, "ring {} is {}, which isn't a whole number", ring, value)
+            }
+            BoardError::AnglesOutOfRange { ring, angles } => write!(
+                f,
+                "ring {} has enemies at angles {:?}, but angles only go up to {}",
+                ring,
+                angles,
+                NUM_ANGLES - 1
+            ),
+            BoardError::NoEnemies => write!(f, "the board has no enemies"),
+        }
+    }
+}
+
+impl std::error::Error for BoardError {}
+
+/// The result of `validate_board`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoardValidation {
+    /// Whether nothing is wrong with the board.
+    pub valid: bool,
+    /// Everything wrong with the board, empty if nothing is.
+    pub errors: Vec<BoardError>,
+}
+
+/// Reads a board from the number for each ring, or everything that makes it not a board.
+pub fn read_board(values: &[f64]) -> std::result::Result<Ring, Vec<BoardError>> {
+    let mut ring = Ring::default();
+    let mut errors = Vec::new();
+    if values.len() != ring.len() {
+        errors.push(BoardError::WrongLength { expected: ring.len(), actual: values.len() });
+    }
+    // Numbers past 2^53 can't be told apart from their neighbors, so they aren't
+    // whole numbers as far as a board is concerned.
+    const MAX_EXACT: f64 = (1u64 << f64::MANTISSA_DIGITS) as f64;
+    for (r, &value) in values.iter().enumerate() {
+        if !(0.0..MAX_EXACT).contains(&value) || value.fract() != 0.0 {
+            errors.push(BoardError::NotAWholeNumber { ring: r, value });
+            continue;
+        }
+        let bits = value as u64;
+        let extra = bits >> NUM_ANGLES;
+        if extra != 0 {
+            let angles = (0..64 - NUM_ANGLES as u32).filter(|bit| extra & (1 << bit) != 0);
+            let angles = angles.map(|bit| bit + NUM_ANGLES as u32).collect();
+            errors.push(BoardError::AnglesOutOfRange { ring: r, angles });
+        } else if r < NUM_RINGS as usize {
+            ring[r] = bits as u16;
+        }
+    }
+    match errors.is_empty() {
+        true => Ok(ring),
+        false => Err(errors),
+    }
+}
+
+/// Everything wrong with a board given as the number for each ring.
+pub fn validate(values: &[f64]) -> BoardValidation {
+    let errors = match read_board(values) {
+        Ok(ring) if ring.iter().all(|&subring| subring == 0) => vec![BoardError::NoEnemies],
+        Ok(_) => Vec::new(),
+        Err(errors) => errors,
+    };
+    BoardValidation { valid: errors.is_empty(), errors }
+}
+
+/// Reads the numbers of a board from JavaScript.
+fn values_from_js(ring: JsValue) -> std::result::Result<Vec<f64>, BoardError> {
+    serde_wasm_bindgen::from_value(ring)
+        .map_err(|e| BoardError::NotAnArray { reason: e.to_string() })
+}
+
+/// Every problem in `errors`, as one message.
+pub(crate) fn describe(errors: &[BoardError]) -> String {
+    let messages: Vec<String> = errors.iter().map(BoardError::to_string).collect();
+    messages.join("; ")
+}
+
+/// Reads a board from JavaScript, failing with every problem with it.
+pub(crate) fn ring_from_js(ring: JsValue) -> Result<Ring> {
+    let values = values_from_js(ring).map_err(|error| vec![error]);
+    let ring = values.and_then(|values| read_board(&values));
+    Ok(ring.map_err(|errors| describe(&errors))?)
+}
+
+/// Check a board before solving it, as a `BoardValidation` listing every `BoardError`.
+#[wasm_bindgen(skip_typescript)]
+pub fn validate_board(ring: JsValue) -> Result<JsValue> {
+    let validation = match values_from_js(ring) {
+        Ok(values) => validate(&values),
+        Err(error) => BoardValidation { valid: false, errors: vec![error] },
+    };
+    Ok(serde_wasm_bindgen::to_value(&validation)?)
+}
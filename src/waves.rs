@@ -0,0 +1,103 @@
+//! Battles fought in waves, where the next board of enemies appears once the last one is
+//! cleared. Items and the partner carry over from wave to wave, so each wave is solved
+//! with what the waves before it left.
+//!
+//! ```json
+//! {"waves": [[1, 0, 0, 0], [3, 0, 0, 0]], "items": {"fireFlowers": 1}, "partnerCooldown": 1}
+//! ```
+//!
+//! Each wave takes the partner's attack if it's ready, and only spends items if it can't
+//! be cleared without them.
+
+use crate::items::{self, ItemBag, ItemUse};
+use crate::{
+    check_ring, find_solution, options_from_js, with_spoilers, Result, Ring, Solution,
+    SolveOptions, MAX_TURNS,
+};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// The boards of a battle in the order they appear, and what the player brings to it.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Waves {
+    pub waves: Vec<Ring>,
+    /// The items the player carries into the first wave.
+    pub items: ItemBag,
+    /// How many waves the partner sits out after attacking.
+    pub partner_cooldown: u32,
+}
+
+/// The plan for one wave, and what's left for the waves after it.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WavePlan {
+    pub solution: Solution,
+    /// The items still carried once the wave is cleared.
+    pub items_left: ItemBag,
+    /// Whether the partner was ready to attack this wave.
+    pub partner_ready: bool,
+}
+
+/// Solves each wave in turn, carrying over the items used and the partner's cooldown.
+/// The plans stop at the first wave that can't be cleared, which gets an imperfect plan if
+/// the search found one. Fails with `spoiler_free`, since the plans have the moves.
+pub fn solve_waves(
+    waves: &Waves,
+    options: &SolveOptions,
+) -> std::result::Result<Vec<WavePlan>, String> {
+    options.battle_rules()?;
+    with_spoilers(options)?;
+    let mut bag = waves.items;
+    let mut cooldown = 0;
+    let mut plans = Vec::new();
+    for ring in &waves.waves {
+        check_ring(ring)?;
+        let partner_ready = cooldown == 0;
+        let wave_options = SolveOptions {
+            partner: options.partner.filter(|_| partner_ready),
+            ..options.clone()
+        };
+        let mut solution = find_solution(*ring, MAX_TURNS, &wave_options);
+        let cleared = solution.as_ref().is_some_and(|solution| solution.perfect);
+        if !cleared && bag != ItemBag::default() {
+            // Items and the partner aren't planned together, so items go without it.
+            let item_options = SolveOptions { partner: None, ..options.clone() };
+            if let Some(with_items) = items::find_solution(*ring, bag, &item_options)? {
+                if with_items.perfect {
+                    solution = Some(with_items);
+                }
+            }
+        }
+        let solution = match solution {
+            Some(solution) => solution,
+            None => break,
+        };
+        for item in &solution.items {
+            match item {
+                ItemUse::PowBlock => bag.pow_blocks -= 1,
+                ItemUse::FireFlower { .. } => bag.fire_flowers -= 1,
+            }
+        }
+        if solution.partner_attack.is_some() {
+            cooldown = waves.partner_cooldown;
+        } else {
+            cooldown = cooldown.saturating_sub(1);
+        }
+        let perfect = solution.perfect;
+        plans.push(WavePlan { solution, items_left: bag, partner_ready });
+        if !perfect {
+            break;
+        }
+    }
+    Ok(plans)
+}
+
+/// Solve a battle fought in waves, given as `{waves, items, partnerCooldown}`, returning a
+/// plan for each wave up to the first that can't be cleared.
+#[wasm_bindgen(js_name = solve_waves, skip_typescript)]
+pub fn solve_waves_js(waves: JsValue, options: JsValue) -> Result<JsValue> {
+    let waves: Waves = serde_wasm_bindgen::from_value(waves)?;
+    let options = options_from_js(options)?;
+    Ok(serde_wasm_bindgen::to_value(&solve_waves(&waves, &options)?)?)
+}
@@ -0,0 +1,446 @@
+//! Weapons and enemy types described by a table, so what each weapon can hit follows the
+//! community's notes instead of the assumptions built into the solver.
+//!
+//! ```json
+//! {
+//!   "table": {
+//!     "weapons": [
+//!       {"name": "Basic Boots", "kind": "jump", "power": 3},
+//!       {"name": "Basic Hammer", "kind": "hammer", "power": 4, "hits": ["Goomba"]}
+//!     ],
+//!     "enemies": [{"name": "Goomba", "hp": 4}, {"name": "Paragoomba", "hp": 6}]
+//!   },
+//!   "types": [[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], ...],
+//!   "boots": "Basic Boots",
+//!   "hammer": "Basic Hammer"
+//! }
+//! ```
+//!
+//! Each enemy type becomes the attributes of `attributes`: an enemy the boots can't defeat
+//! in two jumps is spiked, one the hammer can't defeat in two swings is flying, and one
+//! that takes two hits from either weapon is shielded.
+//!
+//! Weapons can reach farther than the basic ones, like a Hurlhammer hitting the outer
+//! rings, or gear that hits several angles at once. Those only fit the attributes when
+//! they hit a single line or the inner rings of two angles, so for the rest, `actions`
+//! gives the weapon used for each action, in order, and every way of aiming them is tried
+//! against the HP of each enemy.
+
+use crate::attributes::{self, AttributeGoal, EnemyAttributes, Reach};
+use crate::evaluator::Evaluator;
+use crate::hp::{moved_hp, HpGrid};
+use crate::{
+    input_from_js, solve_to_js, Attack, Result, Ring, RingMovement, Search, Solution,
+    SolveOptions, MAX_TURNS, NUM_ANGLES, NUM_RINGS,
+};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::ops::Range;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// Which attack a weapon makes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WeaponKind {
+    Jump,
+    Hammer,
+}
+
+/// The panels an attack hits, starting from the angle it's aimed at and going clockwise.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Footprint {
+    /// The line outward from the innermost enemy standing in each of `width` angles, up to
+    /// `depth` enemies long.
+    Line { depth: usize, width: u16 },
+    /// Every enemy in `rings` of each of `width` angles.
+    Area { rings: Range<usize>, width: u16 },
+}
+
+impl Footprint {
+    /// The panels hit on a board with enemies wherever `hp` is above 0, by ring and angle.
+    fn panels(&self, hp: &HpGrid, th: usize) -> Vec<(usize, usize)> {
+        let angles = |width: u16| (0..width as usize).map(move |i| (th + i) % NUM_ANGLES as usize);
+        match self {
+            Footprint::Line { depth, width } => angles(*width)
+                .flat_map(|th| {
+                    (0..NUM_RINGS as usize)
+                        .skip_while(move |&r| hp[r][th] == 0)
+                        .take_while(move |&r| hp[r][th] > 0)
+                        .take(*depth)
+                        .map(move |r| (r, th))
+                })
+                .collect(),
+            Footprint::Area { rings, width } => angles(*width)
+                .flat_map(|th| rings.clone().map(move |r| (r, th)))
+                .filter(|&(r, th)| hp[r][th] > 0)
+                .collect(),
+        }
+    }
+}
+
+/// A weapon the player could have equipped.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Weapon {
+    pub name: String,
+    pub kind: WeaponKind,
+    /// The damage it deals to each enemy it hits.
+    pub power: u16,
+    /// For boots, the most enemies in a line a jump hits; for hammers, how many of the
+    /// inner rings a swing hits. None for the usual reach.
+    #[serde(default)]
+    pub range: Option<usize>,
+    /// For hammers that hit rings other than the inner ones, the first ring hit and one
+    /// past the last.
+    #[serde(default)]
+    pub rings: Option<[usize; 2]>,
+    /// How many angles an attack hits, or None for one angle for boots and two for hammers.
+    #[serde(default)]
+    pub width: Option<u16>,
+    /// The names of the enemy types it can hit, or None for every type.
+    #[serde(default)]
+    pub hits: Option<Vec<String>>,
+}
+
+impl Weapon {
+    /// The panels each attack with this weapon hits.
+    pub fn footprint(&self) -> Footprint {
+        match self.kind {
+            WeaponKind::Jump => Footprint::Line {
+                depth: self.range.unwrap_or(NUM_RINGS as usize),
+                width: self.width.unwrap_or(1),
+            },
+            WeaponKind::Hammer => {
+                let [first, last] = self.rings.unwrap_or([0, self.range.unwrap_or(2)]);
+                Footprint::Area { rings: first..last, width: self.width.unwrap_or(2) }
+            }
+        }
+    }
+
+    /// Whether this weapon can hit enemies of `kind`.
+    fn can_hit(&self, kind: &EnemyKind) -> bool {
+        self.hits.as_ref().is_none_or(|hits| hits.contains(&kind.name))
+    }
+
+    /// How many hits this weapon takes to defeat `enemy`, or None if it can't hit it.
+    fn hits_to_defeat(&self, enemy: &EnemyKind) -> Option<u16> {
+        self.can_hit(enemy).then(|| enemy.hp.div_ceil(self.power))
+    }
+}
+
+/// A type of enemy that can be on the board.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnemyKind {
+    pub name: String,
+    pub hp: u16,
+}
+
+/// The weapons and enemy types known to a front end.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WeaponTable {
+    pub weapons: Vec<Weapon>,
+    pub enemies: Vec<EnemyKind>,
+}
+
+impl WeaponTable {
+    /// Checks that every weapon deals damage and that no two weapons or enemy types share
+    /// a name.
+    pub fn check(&self) -> std::result::Result<(), String> {
+        for (i, weapon) in self.weapons.iter().enumerate() {
+            if weapon.power == 0 {
+                return Err(format!("weapon {:?} must deal some damage", weapon.name));
+            }
+            if weapon.range.is_some_and(|range| !(1..=NUM_RINGS as usize).contains(&range)) {
+                return Err(format!("weapon {:?} must reach 1 to {} rings", weapon.name, NUM_RINGS));
+            }
+            if weapon.width.is_some_and(|width| !(1..=NUM_ANGLES).contains(&width)) {
+                return Err(format!("weapon {:?} must hit 1 to {} angles", weapon.name, NUM_ANGLES));
+            }
+            match weapon.rings {
+                Some(_) if weapon.kind == WeaponKind::Jump => {
+                    return Err(format!("boots {:?} can't hit chosen rings", weapon.name));
+                }
+                Some([first, last]) if first >= last || last > NUM_RINGS as usize => {
+                    return Err(format!("weapon {:?} must hit rings 0 to {}", weapon.name, NUM_RINGS));
+                }
+                _ => {}
+            }
+            if self.weapons[..i].iter().any(|other| other.name == weapon.name) {
+                return Err(format!("there are two weapons named {:?}", weapon.name));
+            }
+        }
+        for (i, enemy) in self.enemies.iter().enumerate() {
+            if self.enemies[..i].iter().any(|other| other.name == enemy.name) {
+                return Err(format!("there are two enemy types named {:?}", enemy.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// The weapon named `name`, which has to make attacks of `kind`.
+    fn weapon(&self, name: &str, kind: WeaponKind) -> std::result::Result<&Weapon, String> {
+        match self.weapons.iter().find(|weapon| weapon.name == name) {
+            Some(weapon) if weapon.kind == kind => Ok(weapon),
+            Some(_) => Err(format!("weapon {:?} isn't a {:?} weapon", name, kind)),
+            None => Err(format!("there's no weapon named {:?}", name)),
+        }
+    }
+
+    /// The index of the weapon named `name`, of either kind.
+    pub(crate) fn weapon_index(&self, name: &str) -> std::result::Result<usize, String> {
+        let index = self.weapons.iter().position(|weapon| weapon.name == name);
+        index.ok_or_else(|| format!("there's no weapon named {:?}", name))
+    }
+
+    /// The HP of the enemy on each panel of `ring`, given the type on each panel, and 0
+    /// for panels without an enemy of a known type.
+    pub(crate) fn hp(&self, ring: &Ring, types: &HpGrid) -> HpGrid {
+        let mut hp = [[0; NUM_ANGLES as usize]; NUM_RINGS as usize];
+        for (r, hps) in hp.iter_mut().enumerate() {
+            for (th, hp) in hps.iter_mut().enumerate() {
+                let kind = (types[r][th] as usize).checked_sub(1);
+                if let Some(enemy) = kind.and_then(|kind| self.enemies.get(kind)) {
+                    *hp = enemy.hp * (ring[r] >> th & 1);
+                }
+            }
+        }
+        hp
+    }
+
+    /// The damage an attack with `weapon` aimed at `th` deals to each panel it hits, or
+    /// None if it can't be made, like a jump onto an enemy the boots can't hit.
+    pub(crate) fn damage(
+        &self,
+        weapon: usize,
+        hp: &HpGrid,
+        types: &HpGrid,
+        th: usize,
+    ) -> Option<HpGrid> {
+        let weapon = &self.weapons[weapon];
+        let mut damage = [[0; NUM_ANGLES as usize]; NUM_RINGS as usize];
+        for (r, th) in weapon.footprint().panels(hp, th) {
+            if weapon.can_hit(&self.enemies[types[r][th] as usize - 1]) {
+                damage[r][th] = weapon.power.min(hp[r][th]);
+            } else if weapon.kind == WeaponKind::Jump {
+                return None;
+            }
+        }
+        Some(damage)
+    }
+}
+
+/// The weapons the player has equipped, and the type of each enemy on the board.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WeaponSetup {
+    pub table: WeaponTable,
+    /// The type of the enemy on each panel, by ring and then angle, as 1 more than its
+    /// index in `table.enemies`, and 0 where there's none.
+    pub types: HpGrid,
+    /// The names of the equipped boots and hammer, or None if the player can't use them.
+    #[serde(default)]
+    pub boots: Option<String>,
+    #[serde(default)]
+    pub hammer: Option<String>,
+    /// The names of the weapons used for each action, in order, or None to use the
+    /// equipped boots and hammer for as many actions as the rules give.
+    #[serde(default)]
+    pub actions: Option<Vec<String>>,
+}
+
+impl WeaponSetup {
+    /// Checks that the types are known, and that exactly the panels with enemies have one.
+    fn check_types(&self, ring: &Ring) -> std::result::Result<(), String> {
+        self.table.check()?;
+        for (r, types) in self.types.iter().enumerate() {
+            for (th, &kind) in types.iter().enumerate() {
+                let enemy = ring[r] & (1 << th) != 0;
+                if (kind != 0) != enemy {
+                    return Err(format!("ring {} angle {} needs a type only if it has an enemy", r, th));
+                }
+                if kind as usize > self.table.enemies.len() {
+                    return Err(format!("ring {} angle {} has an unknown enemy type {}", r, th, kind));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The attributes and reach of every enemy on `ring`, given the weapons equipped.
+    pub fn attributes(&self, ring: &Ring) -> std::result::Result<(EnemyAttributes, Reach), String> {
+        self.check_types(ring)?;
+        let boots = self.boots.as_deref().map(|name| self.table.weapon(name, WeaponKind::Jump));
+        let boots = boots.transpose()?;
+        let hammer = self.hammer.as_deref().map(|name| self.table.weapon(name, WeaponKind::Hammer));
+        let hammer = hammer.transpose()?;
+        let mut attributes = EnemyAttributes::default();
+        for (r, types) in self.types.iter().enumerate() {
+            for (th, &kind) in types.iter().enumerate() {
+                let enemy = match kind {
+                    0 => continue,
+                    kind => &self.table.enemies[kind as usize - 1],
+                };
+                // Nothing on the board takes more than two hits, so a weapon that would take
+                // more is as good as one that can't hit the enemy at all.
+                let hits = |weapon: Option<&Weapon>| {
+                    weapon.and_then(|weapon| weapon.hits_to_defeat(enemy)).filter(|&hits| hits <= 2)
+                };
+                let (jumps, hammers) = (hits(boots), hits(hammer));
+                let bit = 1 << th;
+                if jumps.is_none() {
+                    attributes.spiked[r] |= bit;
+                }
+                if hammers.is_none() {
+                    attributes.flying[r] |= bit;
+                }
+                if jumps.max(hammers) == Some(2) {
+                    attributes.shielded[r] |= bit;
+                }
+            }
+        }
+        let mut reach = Reach::default();
+        if let Some(boots) = boots {
+            match boots.footprint() {
+                Footprint::Line { depth, width: 1 } => reach.jump_depth = depth,
+                _ => return Err(format!("boots {:?} need the actions to be given", boots.name)),
+            }
+        }
+        if let Some(hammer) = hammer {
+            match hammer.footprint() {
+                Footprint::Area { rings, width: 2 } if rings.start == 0 => {
+                    reach.hammer_rings = rings.end
+                }
+                _ => return Err(format!("hammer {:?} needs the actions to be given", hammer.name)),
+            }
+        }
+        Ok((attributes, reach))
+    }
+}
+
+/// Every enemy is defeated by the actions, each made with its own weapon, in order.
+/// The distance is the least HP left over.
+pub struct ActionGoal {
+    table: WeaponTable,
+    types: HpGrid,
+    /// The weapon of each action, by index in the table.
+    actions: Vec<usize>,
+}
+
+impl ActionGoal {
+    pub fn new(setup: &WeaponSetup, ring: &Ring) -> std::result::Result<Self, String> {
+        setup.check_types(ring)?;
+        let actions = setup.actions.as_ref().ok_or("the actions must be given")?;
+        let actions = actions.iter().map(|name| setup.table.weapon_index(name));
+        Ok(ActionGoal {
+            table: setup.table.clone(),
+            types: setup.types,
+            actions: actions.collect::<std::result::Result<_, _>>()?,
+        })
+    }
+
+    /// The best way to aim the actions from `step` on, as the HP left over and the angle
+    /// each action is aimed at, stopping once every enemy is defeated.
+    fn best(
+        &self,
+        hp: HpGrid,
+        types: &HpGrid,
+        step: usize,
+        seen: &mut HashSet<(usize, HpGrid)>,
+    ) -> (u32, Vec<u16>) {
+        let left: u32 = hp.iter().flatten().map(|&hp| hp as u32).sum();
+        if left == 0 || step == self.actions.len() || !seen.insert((step, hp)) {
+            return (left, Vec::new());
+        }
+        let mut best = (left, Vec::new());
+        let mut tried = HashSet::new();
+        for th in 0..NUM_ANGLES as usize {
+            let damage = match self.table.damage(self.actions[step], &hp, types, th) {
+                Some(damage) if damage.iter().flatten().any(|&damage| damage > 0) => damage,
+                _ => continue,
+            };
+            // Aims that hit the same enemies just as hard are the same.
+            if !tried.insert(damage) {
+                continue;
+            }
+            let mut after = hp;
+            for (hp, damage) in after.iter_mut().flatten().zip(damage.iter().flatten()) {
+                *hp -= damage;
+            }
+            let (left, aims) = self.best(after, types, step + 1, seen);
+            if left < best.0 {
+                best = (left, std::iter::once(th as u16).chain(aims).collect());
+                if left == 0 {
+                    break;
+                }
+            }
+        }
+        best
+    }
+
+    /// Where to aim each action once the enemies have made `moves`, and the HP left over.
+    fn plan_after(&self, ring: Ring, moves: &[RingMovement]) -> (u32, Vec<u16>) {
+        let types = moved_hp(&self.types, moves);
+        self.best(self.table.hp(&ring, &types), &types, 0, &mut HashSet::new())
+    }
+
+    /// The attacks to make once the enemies have made `moves`, for each action used.
+    pub fn attacks_after(&self, ring: Ring, moves: &[RingMovement]) -> Vec<Attack> {
+        let (_, aims) = self.plan_after(ring, moves);
+        let attacks = aims.iter().zip(&self.actions).map(|(&th, &weapon)| {
+            match self.table.weapons[weapon].kind {
+                WeaponKind::Jump => Attack::Jump { th },
+                WeaponKind::Hammer => Attack::Hammer { th },
+            }
+        });
+        attacks.collect()
+    }
+}
+
+impl Evaluator for ActionGoal {
+    fn distance(&self, ring: Ring) -> u32 {
+        self.distance_after(ring, &[])
+    }
+
+    fn distance_after(&self, ring: Ring, moves: &[RingMovement]) -> u32 {
+        self.plan_after(ring, moves).0
+    }
+}
+
+/// Finds the best way to clear `ring` with the weapons of `setup`, with the same ranking
+/// as `solve`.
+pub fn find_solution(
+    ring: Ring,
+    setup: &WeaponSetup,
+    options: &SolveOptions,
+) -> std::result::Result<Option<Solution>, String> {
+    if setup.actions.is_some() {
+        let goal = Rc::new(ActionGoal::new(setup, &ring)?);
+        let mut search = Search::new(options).with_evaluator(goal.clone());
+        let solution = search.find_solution(ring, MAX_TURNS).map(|mut solution| {
+            let moves: Vec<RingMovement> = solution.moves.iter().copied().collect();
+            solution.attacks = goal.attacks_after(solution.result, &moves);
+            solution
+        });
+        return Ok(solution);
+    }
+    let (enemy_attributes, reach) = setup.attributes(&ring)?;
+    enemy_attributes.check(&ring)?;
+    let mut rules = options.battle_rules()?;
+    rules.jump &= setup.boots.is_some();
+    rules.hammer &= setup.hammer.is_some();
+    let goal = AttributeGoal::new(enemy_attributes, rules).with_reach(reach);
+    Ok(attributes::find_solution_with(ring, Rc::new(goal), options))
+}
+
+/// Solve a board with the weapons in a table, given as `{table, types, boots, hammer}`,
+/// and optionally the weapon of each action as `actions`.
+#[wasm_bindgen(skip_typescript)]
+pub fn solve_with_weapons(ring: JsValue, setup: JsValue, options: JsValue) -> Result<JsValue> {
+    let (ring, options) = input_from_js(ring, options)?;
+    let setup: WeaponSetup = serde_wasm_bindgen::from_value(setup)?;
+    let setup = WeaponSetup { types: options.orientation.grid_in(setup.types), ..setup };
+    solve_to_js(find_solution(ring, &setup, &options)?, &options)
+}
@@ -26,17 +26,22 @@ export default class WebpackWorker extends Worker {
   }
 }
 
-type SolverModule = {solve(ringData: RingData): Solution | null};
-let solverModule: SolverModule | null = null;
+interface Solver {
+  solve(ringData: RingData): Solution | null;
+}
+type SolverModule = {Solver: new (options?: object) => Solver};
+// One solver is kept for the worker's lifetime, so repeated boards are answered from its cache.
+let solver: Solver | null = null;
 
 self.addEventListener('message', async (e: MessageEvent<SolverInput>) => {
-  if (solverModule === null) {
+  if (solver === null) {
     // @ts-ignore
-    solverModule = await import('../pkg/solver') as SolverModule;
+    const solverModule = await import('../pkg/solver') as SolverModule;
+    solver = new solverModule.Solver();
   }
   const ondone = e.data.ondone;
   try {
-    const solution = solverModule.solve(e.data.ringData);
+    const solution = solver.solve(e.data.ringData);
     ondone.postMessage({type: 'done', solution: solution});
   } catch (e) {
     ondone.postMessage({type: 'error', error: e});